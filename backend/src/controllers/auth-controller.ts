@@ -1,4 +1,5 @@
 import type { Context } from "hono";
+import { randomUUID } from "node:crypto";
 import prismadb from "../lib/prisma.js";
 import { sign } from 'hono/jwt';
 import { jwtSecret } from "../utils/env.js";
@@ -133,14 +134,25 @@ export default {
             })
             if (!account) throw new Error("Account not found by address")
             if (account.xp <= 0) throw new Error("You have 0 spins");
-            await prismadb.accounts.update({
-                data: {
-                    xp: account.xp - 1
-                },
-                where: {
-                    address
-                }
-            })
+            await prismadb.$transaction([
+                prismadb.accounts.update({
+                    data: {
+                        xp: account.xp - 1
+                    },
+                    where: {
+                        address
+                    }
+                }),
+                // The indexer reconciles accounts.xp against the ledger
+                prismadb.xp_ledger.create({
+                    data: {
+                        address,
+                        delta: -1,
+                        source_type: "spin_cost",
+                        source_ref: randomUUID(),
+                    }
+                }),
+            ])
             return c.json({ data: "Spinning..." })
         } catch (error: any) {
             return c.json({ message: error.message }, 500);
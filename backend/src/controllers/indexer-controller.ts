@@ -8,10 +8,6 @@ function safeData(obj: any) {
     return JSON.parse(JSON.stringify(obj, replacer));
 }
 
-const REWARD_CHANCE = 0.2; // 20% of trades get XP
-const MIN_XP = 1;
-const MAX_XP = 5;
-const MIN_APTOS = 2500000000;
 // Events to emit from Indexer processor 
 export default {
     async tokenCreated(c: Context) {
@@ -267,24 +263,13 @@ export default {
                 token_amount: trade.token_amount,
                 ts: trade.ts,
             }));
-            if (Math.random() < REWARD_CHANCE && Number(trade.aptos_amount) >= MIN_APTOS) {
-                const xpReward = Math.floor(Math.random() * (MAX_XP - MIN_XP + 1)) + MIN_XP;
-
-                await prismadb.accounts.updateMany({
-                    where: {
-                        address: trade.user_addr
-                    },
-                    data: {
-                        xp: {
-                            increment: xpReward
-                        },
-                        xp_earned: {
-                            increment: xpReward
-                        }
-                    }
-                });
-
-                sendMessageIO(`xp-${trade.token_address}-${trade.user_addr}`, xpReward);
+            // XP is awarded by the indexer in the same transaction as the trade
+            const xpRewards: { delta: number }[] = await prismadb.$queryRawUnsafe(
+                `SELECT delta FROM xp_ledger WHERE source_type = 'trade' AND source_ref = $1 LIMIT 1`,
+                trade.txn_version.toString()
+            );
+            if (xpRewards.length > 0) {
+                sendMessageIO(`xp-${trade.token_address}-${trade.user_addr}`, xpRewards[0].delta);
             }
             sendMessageIO(`token-${trade.token_address}`, safeData(data[0]));
             const token = data[0];
@@ -93,25 +93,36 @@ export default {
             };
             const { progress } = await checkTaskProgress(task.requirement, address, task.description, claims.length + 1);
             if(progress < 100) throw new Error("Task is not completed yet");
-            await prismadb.task_claims.create({
-                data: {
-                    address,
-                    task_id: task.id,
-                    xp_earned: task.xp,
-                }
-            });
-            await prismadb.accounts.update({
-                where: {
-                    address
-                },
-                data: {
-                    xp: {
-                        increment: task.xp
+            await prismadb.$transaction(async (tx) => {
+                const claim = await tx.task_claims.create({
+                    data: {
+                        address,
+                        task_id: task.id,
+                        xp_earned: task.xp,
+                    }
+                });
+                await tx.accounts.update({
+                    where: {
+                        address
                     },
-                    xp_earned: {
-                        increment: task.xp
+                    data: {
+                        xp: {
+                            increment: task.xp
+                        },
+                        xp_earned: {
+                            increment: task.xp
+                        }
                     }
-                }
+                });
+                // The indexer reconciles accounts.xp against the ledger
+                await tx.xp_ledger.create({
+                    data: {
+                        address,
+                        delta: task.xp,
+                        source_type: "task_claim",
+                        source_ref: String(claim.id),
+                    }
+                });
             });
             return c.json({ message: "Task claimed successfully" })
 
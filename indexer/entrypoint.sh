@@ -24,6 +24,10 @@ server_config:
     db_pool_size: 25
   contract_config:
     contract_address: "${CONTRACT_ADDRESS:-0x83a96247ab57ca2b4cac6934ec351e0a44b62dfe9220d4eec5a122715aeeb02f}"
+  webhook_config:
+    enabled: ${WEBHOOK_ENABLED:-true}
+    backend_url: "${BACKEND_URL:-https://fun-api.meowtos.xyz}"
+    access_token: "${ACCESS_TOKEN:-INDEXER}"
 EOF
 
 # Run the Rust binary
@@ -1,58 +1,83 @@
 use anyhow::{Ok, Result};
-use dotenv::var;
-use once_cell::sync::Lazy;
-use reqwest::Client;
+use once_cell::sync::OnceCell;
+use reqwest::{Client, RequestBuilder};
+use std::time::Duration;
 
-use crate::db_models::accounts::Spin;
+use crate::{
+    config::indexer_processor_config::WebhookConfig,
+    db_models::{accounts::Spin, graduation_signals::GraduationSignal},
+};
 
-#[allow(dead_code)]
-static BASE_URL: Lazy<String> = Lazy::new(||var("BACKEND_URL").unwrap_or("https://fun-api.meowtos.xyz".to_string()));
-#[allow(dead_code)]
-static ACCESS_TOKEN: Lazy<String> = Lazy::new(||var("ACCESS_TOKEN").unwrap_or("INDEXER".to_string()));
+struct Backend {
+    client: Client,
+    base_url: String,
+    access_token: String,
+}
 
-#[allow(dead_code)]
-pub async fn emit_token_created(addr: String) -> Result<()> {
-    let client = Client::new();
-    let url = format!("{}/api/indexer/created/{}", *BASE_URL, addr);
-    let response = client.get(url).header("x-indexer", ACCESS_TOKEN.as_str()).send().await?;
-    if !response.status().is_success() {
-        eprintln!("Request failed with status: {}", response.status());
-    }
-    if !response.status().is_success() {
-        anyhow::bail!("Request failed with status {}", response.status());
-    }
-    eprintln!("Created Request succeed with status: {}", response.status());
+static BACKEND: OnceCell<Backend> = OnceCell::new();
 
+/// Sets up the backend client from the validated config, the emit functions do nothing
+/// until this is called or when the webhook is disabled.
+pub fn init(config: &WebhookConfig) -> Result<()> {
+    if !config.enabled {
+        return Ok(());
+    }
+    let (Some(base_url), Some(access_token)) = (&config.backend_url, &config.access_token) else {
+        anyhow::bail!("webhook_config.backend_url and access_token must be set");
+    };
+    let client = Client::builder()
+        .timeout(Duration::from_secs(config.timeout_secs))
+        .build()?;
+    // Already set when the processor restarts within the same process
+    let _ = BACKEND.set(Backend {
+        client,
+        base_url: base_url.trim_end_matches('/').to_string(),
+        access_token: access_token.clone(),
+    });
     Ok(())
 }
 
-#[allow(dead_code)]
-pub async fn emit_token_traded(txn_version: i64) -> Result<()> {
-    let client = Client::new();
-    let url = format!("{}/api/indexer/traded/{}", *BASE_URL, txn_version);
-    let response = client.get(url).header("x-indexer", ACCESS_TOKEN.as_str()).send().await?;
-    if !response.status().is_success() {
-        eprintln!("Request failed with status: {}", response.status());
-    }
+async fn send(name: &str, request: RequestBuilder, backend: &Backend) -> Result<()> {
+    let response = request
+        .header("x-indexer", backend.access_token.as_str())
+        .send()
+        .await?;
     if !response.status().is_success() {
+        tracing::warn!("{} request failed with status: {}", name, response.status());
         anyhow::bail!("Request failed with status {}", response.status());
     }
-    eprintln!("Traded Request succeed with status: {}", response.status());
+    tracing::info!("{} request succeeded with status: {}", name, response.status());
     Ok(())
 }
 
-#[allow(dead_code)]
+pub async fn emit_token_created(addr: String) -> Result<()> {
+    let Some(backend) = BACKEND.get() else {
+        return Ok(());
+    };
+    let url = format!("{}/api/indexer/created/{}", backend.base_url, addr);
+    send("Created", backend.client.get(url), backend).await
+}
+
+pub async fn emit_token_traded(txn_version: i64) -> Result<()> {
+    let Some(backend) = BACKEND.get() else {
+        return Ok(());
+    };
+    let url = format!("{}/api/indexer/traded/{}", backend.base_url, txn_version);
+    send("Traded", backend.client.get(url), backend).await
+}
+
 pub async fn emit_spin_win(spin_event: Spin) -> Result<()> {
-    
-    let client = Client::new();
-    let url = format!("{}/api/indexer/spin", *BASE_URL);
-    let response = client.post(url).header("x-indexer", ACCESS_TOKEN.as_str()).json(&spin_event).send().await?;
-    if !response.status().is_success() {
-        eprintln!("Request failed with status: {}", response.status());
-    }
-    if !response.status().is_success() {
-        anyhow::bail!("Request failed with status {}", response.status());
-    }
-    eprintln!("Spin event request succeed with status: {}", response.status());
-    Ok(())
-}
\ No newline at end of file
+    let Some(backend) = BACKEND.get() else {
+        return Ok(());
+    };
+    let url = format!("{}/api/indexer/spin", backend.base_url);
+    send("Spin event", backend.client.post(url).json(&spin_event), backend).await
+}
+
+pub async fn emit_near_graduation(signal: GraduationSignal) -> Result<()> {
+    let Some(backend) = BACKEND.get() else {
+        return Ok(());
+    };
+    let url = format!("{}/api/indexer/graduation", backend.base_url);
+    send("Near graduation", backend.client.post(url).json(&signal), backend).await
+}
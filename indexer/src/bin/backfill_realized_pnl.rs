@@ -0,0 +1,38 @@
+//! Records the pnl realized by the sells indexed before `realized_pnl` existed, so the
+//! windowed pnl leaderboards cover them.
+//!
+//! ```sh
+//! cargo run --release --bin backfill_realized_pnl -- -c config.yaml
+//! ```
+
+use anyhow::Result;
+use clap::Parser;
+use indexer::{
+    config::indexer_processor_config::IndexerProcessorConfig,
+    utils::{
+        database_connection::{get_db_connection, new_db_pool},
+        pnl::backfill_realized_pnl,
+    },
+};
+use std::path::PathBuf;
+
+#[derive(Parser)]
+struct Args {
+    /// Path to the processor config file
+    #[clap(short, long)]
+    config_path: PathBuf,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    let config = IndexerProcessorConfig::load(&args.config_path)?;
+    config.validate_db()?;
+
+    let pool = new_db_pool(&config.db_config.postgres_connection_string, 1).await;
+    let conn = &mut get_db_connection(&pool).await?;
+
+    let sells = backfill_realized_pnl(conn, &config.fee_config).await?;
+    println!("Recorded the realized pnl of {} sells", sells);
+    Ok(())
+}
@@ -0,0 +1,38 @@
+//! Decodes the package manifests and module sources of upgrades indexed before they were
+//! decoded on insert, and fills `package_dependencies` from their manifests.
+//!
+//! ```sh
+//! cargo run --release --bin decode_package_metadata -- -c config.yaml
+//! ```
+
+use anyhow::Result;
+use clap::Parser;
+use indexer::{
+    config::indexer_processor_config::IndexerProcessorConfig,
+    utils::{
+        database_connection::{get_db_connection, new_db_pool},
+        package_metadata::backfill_package_metadata,
+    },
+};
+use std::path::PathBuf;
+
+#[derive(Parser)]
+struct Args {
+    /// Path to the processor config file
+    #[clap(short, long)]
+    config_path: PathBuf,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    let config = IndexerProcessorConfig::load(&args.config_path)?;
+    config.validate_db()?;
+
+    let pool = new_db_pool(&config.db_config.postgres_connection_string, 1).await;
+    let conn = &mut get_db_connection(&pool).await?;
+
+    let (packages, modules) = backfill_package_metadata(conn).await?;
+    println!("Decoded {} package manifests and {} module sources", packages, modules);
+    Ok(())
+}
@@ -0,0 +1,58 @@
+//! Recomputes account xp from the xp ledger and reports accounts that drifted from it. Run
+//! the migrations first, they carry the xp credited before the ledger over as opening entries.
+//! Only reports by default, pass `--apply` to overwrite the drifted accounts.
+//!
+//! ```sh
+//! cargo run --release --bin reconcile_xp -- -c config.yaml [--apply]
+//! ```
+
+use anyhow::Result;
+use clap::Parser;
+use indexer::{
+    config::indexer_processor_config::IndexerProcessorConfig,
+    utils::{
+        database_connection::{get_db_connection, new_db_pool},
+        xp_reconcile::{apply_xp_drift, find_xp_drift},
+    },
+};
+use std::path::PathBuf;
+
+#[derive(Parser)]
+struct Args {
+    /// Path to the processor config file
+    #[clap(short, long)]
+    config_path: PathBuf,
+    /// Overwrite drifted accounts with the ledger values
+    #[clap(long)]
+    apply: bool,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    let config = IndexerProcessorConfig::load(&args.config_path)?;
+    config.validate_db()?;
+
+    let pool = new_db_pool(&config.db_config.postgres_connection_string, 1).await;
+    let conn = &mut get_db_connection(&pool).await?;
+
+    let drift = find_xp_drift(conn).await?;
+    for account in drift.iter() {
+        println!(
+            "{}{} xp {} (ledger {}), xp_earned {} (ledger {})",
+            account.address,
+            if account.missing_account { " (no account)" } else { "" },
+            account.xp,
+            account.ledger_xp,
+            account.xp_earned,
+            account.ledger_xp_earned
+        );
+    }
+    println!("{} accounts drifted from the xp ledger", drift.len());
+
+    if args.apply && !drift.is_empty() {
+        apply_xp_drift(conn, drift).await?;
+        println!("Applied ledger values");
+    }
+    Ok(())
+}
@@ -0,0 +1,49 @@
+//! Overrides applied on top of the YAML config, so secrets don't have to live in the file.
+//! For every setting `NAME`, a file named by `NAME_FILE` wins over the `NAME` variable,
+//! which wins over the YAML value. Variables are also read from a `.env` file. The webhook
+//! settings fall back to the `BACKEND_URL` and `ACCESS_TOKEN` variables deployments used
+//! before `webhook_config`.
+
+use anyhow::{Context, Result};
+
+pub const POSTGRES_CONNECTION_STRING: &str = "INDEXER_POSTGRES_CONNECTION_STRING";
+pub const GRPC_AUTH_TOKEN: &str = "INDEXER_GRPC_AUTH_TOKEN";
+pub const WEBHOOK_BACKEND_URL: &str = "INDEXER_WEBHOOK_BACKEND_URL";
+pub const WEBHOOK_ACCESS_TOKEN: &str = "INDEXER_WEBHOOK_ACCESS_TOKEN";
+pub const LEGACY_WEBHOOK_BACKEND_URL: &str = "BACKEND_URL";
+pub const LEGACY_WEBHOOK_ACCESS_TOKEN: &str = "ACCESS_TOKEN";
+
+/// Value of the setting from `NAME_FILE` or `NAME`, `None` when neither is set.
+pub fn env_or_file(name: &str) -> Result<Option<String>> {
+    let file_var = format!("{}_FILE", name);
+    if let Ok(path) = dotenv::var(&file_var) {
+        let value = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {} from {}", file_var, path))?;
+        return Ok(Some(value.trim_end_matches(['\r', '\n']).to_string()));
+    }
+    Ok(dotenv::var(name).ok())
+}
+
+/// Replaces `value` with the override of the setting, if any.
+pub fn override_value(value: &mut String, name: &str) -> Result<()> {
+    if let Some(v) = env_or_file(name)? {
+        *value = v;
+    }
+    Ok(())
+}
+
+/// Same as `override_value` for settings the YAML may leave out.
+pub fn override_option(value: &mut Option<String>, name: &str) -> Result<()> {
+    if let Some(v) = env_or_file(name)? {
+        *value = Some(v);
+    }
+    Ok(())
+}
+
+/// Sets a setting the YAML and its override left out from a legacy variable, if any.
+pub fn fallback_option(value: &mut Option<String>, legacy_name: &str) -> Result<()> {
+    if value.is_none() {
+        *value = env_or_file(legacy_name)?;
+    }
+    Ok(())
+}
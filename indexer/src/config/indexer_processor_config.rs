@@ -1,9 +1,20 @@
-use super::processor_config::ProcessorConfig;
-use crate::steps::processor::ContractProcessor;
-use anyhow::Result;
-use aptos_indexer_processor_sdk::aptos_indexer_transaction_stream::TransactionStreamConfig;
+use super::{
+    env_overrides::{
+        fallback_option, override_option, override_value, GRPC_AUTH_TOKEN,
+        LEGACY_WEBHOOK_ACCESS_TOKEN, LEGACY_WEBHOOK_BACKEND_URL, POSTGRES_CONNECTION_STRING,
+        WEBHOOK_ACCESS_TOKEN, WEBHOOK_BACKEND_URL,
+    },
+    processor_config::ProcessorConfig,
+};
+use crate::{api_client::events, steps::processor::ContractProcessor};
+use anyhow::{Context, Result};
+use aptos_indexer_processor_sdk::{
+    aptos_indexer_transaction_stream::TransactionStreamConfig,
+    utils::convert::standardize_address,
+};
 use aptos_indexer_processor_sdk_server_framework::RunnableConfig;
 use serde::{Deserialize, Serialize};
+use std::path::Path;
 
 pub const QUERY_DEFAULT_RETRIES: u32 = 5;
 pub const QUERY_DEFAULT_RETRY_DELAY_MS: u64 = 500;
@@ -15,14 +26,155 @@ pub struct IndexerProcessorConfig {
     pub transaction_stream_config: TransactionStreamConfig,
     pub db_config: DbConfig,
     pub contract_config: ContractConfig,
+    #[serde(default)]
+    pub xp_config: XpConfig,
+    #[serde(default)]
+    pub fee_config: FeeConfig,
+    #[serde(default)]
+    pub leaderboard_config: LeaderboardConfig,
+    #[serde(default)]
+    pub api_config: ApiConfig,
+    #[serde(default)]
+    pub graphql_config: GraphqlConfig,
+    #[serde(default)]
+    pub webhook_config: WebhookConfig,
+    #[serde(default)]
+    pub raw_events_config: RawEventsConfig,
+    #[serde(default)]
+    pub upgrade_config: UpgradeConfig,
+    #[serde(default)]
+    pub trade_flags_config: TradeFlagsConfig,
+    #[serde(default)]
+    pub address_labels_config: AddressLabelsConfig,
+    #[serde(default)]
+    pub creator_config: CreatorConfig,
+    #[serde(default)]
+    pub curve_config: CurveConfig,
+    #[serde(default)]
+    pub price_config: PriceConfig,
+    #[serde(default)]
+    pub token_metrics_config: TokenMetricsConfig,
+    #[serde(default)]
+    pub trending_config: TrendingConfig,
+    #[serde(default)]
+    pub chat_context_config: ChatContextConfig,
+}
+
+/// Layout of the config file read by the server framework.
+#[derive(Deserialize)]
+struct ConfigFile {
+    server_config: IndexerProcessorConfig,
+}
+
+impl IndexerProcessorConfig {
+    /// Reads the `server_config` section of a config file with its overrides, for the
+    /// binaries and servers that run next to the server framework. It is not validated,
+    /// callers check the sections they use.
+    pub fn load(path: &Path) -> Result<Self> {
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("Failed to open config {:?}", path))?;
+        let config: ConfigFile = serde_yaml::from_reader(file).context("Failed to parse config")?;
+        config.server_config.with_overrides()
+    }
+
+    /// Applies the environment and secret file overrides, see `env_overrides`.
+    pub fn with_overrides(mut self) -> Result<Self> {
+        override_value(
+            &mut self.db_config.postgres_connection_string,
+            POSTGRES_CONNECTION_STRING,
+        )?;
+        override_value(&mut self.transaction_stream_config.auth_token, GRPC_AUTH_TOKEN)?;
+        override_option(&mut self.webhook_config.backend_url, WEBHOOK_BACKEND_URL)?;
+        override_option(&mut self.webhook_config.access_token, WEBHOOK_ACCESS_TOKEN)?;
+        fallback_option(&mut self.webhook_config.backend_url, LEGACY_WEBHOOK_BACKEND_URL)?;
+        fallback_option(&mut self.webhook_config.access_token, LEGACY_WEBHOOK_ACCESS_TOKEN)?;
+        Ok(self)
+    }
+
+    /// Fails on a missing connection string, for the entry points that only use the database.
+    pub fn validate_db(&self) -> Result<()> {
+        anyhow::ensure!(
+            !self.db_config.postgres_connection_string.is_empty(),
+            "db_config.postgres_connection_string is not set, set it in the config or {}",
+            POSTGRES_CONNECTION_STRING
+        );
+        Ok(())
+    }
+
+    /// Fails on a missing required value, so a bad deployment stops at startup.
+    pub fn validate(&self) -> Result<()> {
+        self.validate_db()?;
+        anyhow::ensure!(
+            !self.transaction_stream_config.auth_token.is_empty(),
+            "transaction_stream_config.auth_token is not set, set it in the config or {}",
+            GRPC_AUTH_TOKEN
+        );
+        anyhow::ensure!(
+            !self.contract_config.contracts().is_empty(),
+            "contract_config must set contract_address or at least one entry in contracts"
+        );
+        anyhow::ensure!(
+            self.contract_config
+                .contracts
+                .iter()
+                .filter(|contract| contract.untagged_rows)
+                .count()
+                <= 1,
+            "contract_config.contracts can mark a single entry with untagged_rows"
+        );
+        anyhow::ensure!(
+            self.leaderboard_config.refresh_interval_secs > 0,
+            "leaderboard_config.refresh_interval_secs must be positive"
+        );
+        anyhow::ensure!(
+            self.token_metrics_config.snapshot_interval_secs > 0,
+            "token_metrics_config.snapshot_interval_secs must be positive"
+        );
+        anyhow::ensure!(
+            self.trending_config.refresh_interval_secs > 0
+                && self.trending_config.window_secs > 0
+                && self.trending_config.half_life_secs > 0,
+            "trending_config intervals must be positive"
+        );
+        anyhow::ensure!(
+            self.price_config.poll_interval_secs > 0,
+            "price_config.poll_interval_secs must be positive"
+        );
+        anyhow::ensure!(
+            self.chat_context_config.poll_interval_secs > 0,
+            "chat_context_config.poll_interval_secs must be positive"
+        );
+        if self.price_config.source == PriceSource::Csv {
+            anyhow::ensure!(
+                self.price_config.csv_file.is_some(),
+                "price_config.csv_file must be set when the price source is csv"
+            );
+        }
+        if self.webhook_config.enabled {
+            anyhow::ensure!(
+                self.webhook_config.backend_url.is_some(),
+                "webhook_config.backend_url is not set, set it in the config or {}",
+                WEBHOOK_BACKEND_URL
+            );
+            anyhow::ensure!(
+                self.webhook_config.access_token.is_some(),
+                "webhook_config.access_token is not set, set it in the config or {}",
+                WEBHOOK_ACCESS_TOKEN
+            );
+        }
+        Ok(())
+    }
 }
 
 #[async_trait::async_trait]
 impl RunnableConfig for IndexerProcessorConfig {
     async fn run(&self) -> Result<()> {
-        match self.processor_config {
+        let config = self.clone().with_overrides()?;
+        config.validate()?;
+        events::init(&config.webhook_config)?;
+        match config.processor_config {
             ProcessorConfig::ContractProcessor => {
-                let events_processor = ContractProcessor::new(self.clone()).await?;
+                let events_processor = ContractProcessor::new(config).await?;
                 events_processor.run_processor().await
             }
         }
@@ -58,5 +210,787 @@ impl DbConfig {
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
 pub struct ContractConfig {
-    pub contract_address: String,
+    // Single contract indexing every module, kept for configs written before `contracts`
+    #[serde(default)]
+    pub contract_address: Option<String>,
+    #[serde(default)]
+    pub contracts: Vec<ContractEntry>,
+}
+
+impl ContractConfig {
+    /// Every contract to index, with standardized addresses.
+    pub fn contracts(&self) -> Vec<ContractEntry> {
+        self.contract_address
+            .iter()
+            .map(|address| ContractEntry {
+                address: address.clone(),
+                modules: vec![],
+                starting_version: None,
+                module_names: ContractModules::default(),
+                untagged_rows: false,
+            })
+            .chain(self.contracts.iter().cloned())
+            .map(|contract| ContractEntry {
+                address: standardize_address(&contract.address),
+                ..contract
+            })
+            .collect()
+    }
+
+    /// Contract of the rows indexed before rows were tagged with their contract: the single
+    /// `contract_address`, else the entry marked with `untagged_rows` or the only entry.
+    pub fn untagged_contract(&self) -> Option<String> {
+        let contracts = self.contracts();
+        if self.contract_address.is_some() || contracts.len() == 1 {
+            return contracts.first().map(|contract| contract.address.clone());
+        }
+        contracts
+            .into_iter()
+            .find(|contract| contract.untagged_rows)
+            .map(|contract| contract.address)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ContractEntry {
+    pub address: String,
+    // Modules whose events are indexed, every module when empty
+    #[serde(default)]
+    pub modules: Vec<String>,
+    // Events and upgrades of this contract before this version are skipped
+    #[serde(default)]
+    pub starting_version: Option<u64>,
+    // Names of the modules emitting the decoded events, they differ between deployments
+    #[serde(default)]
+    pub module_names: ContractModules,
+    // Rows indexed before rows were tagged with their contract belong to this contract
+    #[serde(default)]
+    pub untagged_rows: bool,
+}
+
+impl ContractEntry {
+    pub fn is_active(&self, txn_version: i64) -> bool {
+        match self.starting_version {
+            Some(starting_version) => txn_version >= starting_version as i64,
+            None => true,
+        }
+    }
+
+    pub fn indexes_module(&self, module: &str) -> bool {
+        self.modules.is_empty() || self.modules.iter().any(|m| m == module)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ContractModules {
+    // Module of the curve: TokenCreated, TokenTraded, PoolCompleted and the Config resource
+    #[serde(default = "ContractModules::default_money")]
+    pub money: String,
+    // Module of the spin game: SpinEvent and GameInitEvent
+    #[serde(default = "ContractModules::default_spin")]
+    pub spin: String,
+    // Module of the staking positions: PositionCreated, RewardsClaimed and PositionRemoved
+    #[serde(default = "ContractModules::default_staking")]
+    pub staking: String,
+}
+
+impl ContractModules {
+    pub fn default_money() -> String {
+        "mooner_money".to_string()
+    }
+
+    pub fn default_spin() -> String {
+        "mooner_spin".to_string()
+    }
+
+    pub fn default_staking() -> String {
+        "staking".to_string()
+    }
+}
+
+impl Default for ContractModules {
+    fn default() -> Self {
+        Self {
+            money: Self::default_money(),
+            spin: Self::default_spin(),
+            staking: Self::default_staking(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct XpConfig {
+    // Seed mixed into every roll, changing it changes which trades are rewarded
+    #[serde(default = "XpConfig::default_seed")]
+    pub seed: u64,
+    // Chance of a trade being rewarded, in basis points
+    #[serde(default = "XpConfig::default_reward_chance_bps")]
+    pub reward_chance_bps: u64,
+    #[serde(default = "XpConfig::default_min_xp")]
+    pub min_xp: i32,
+    #[serde(default = "XpConfig::default_max_xp")]
+    pub max_xp: i32,
+    // Minimum aptos amount of a trade to be eligible, in octas
+    #[serde(default = "XpConfig::default_min_aptos")]
+    pub min_aptos: i64,
+}
+
+impl XpConfig {
+    pub const fn default_seed() -> u64 {
+        0
+    }
+
+    pub const fn default_reward_chance_bps() -> u64 {
+        2000
+    }
+
+    pub const fn default_min_xp() -> i32 {
+        1
+    }
+
+    pub const fn default_max_xp() -> i32 {
+        5
+    }
+
+    pub const fn default_min_aptos() -> i64 {
+        2_500_000_000
+    }
+}
+
+impl Default for XpConfig {
+    fn default() -> Self {
+        Self {
+            seed: Self::default_seed(),
+            reward_chance_bps: Self::default_reward_chance_bps(),
+            min_xp: Self::default_min_xp(),
+            max_xp: Self::default_max_xp(),
+            min_aptos: Self::default_min_aptos(),
+        }
+    }
+}
+
+/// Mirrors the fee settings of the on-chain `Config`, used to split the amounts of trades made
+/// before the indexer saw a write of the `Config` into fees.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct FeeConfig {
+    // Trading fee, in basis points of the trade
+    #[serde(default = "FeeConfig::default_fee_bps")]
+    pub fee_bps: i64,
+    // Creator share of the trading fee, in basis points of the fee
+    #[serde(default = "FeeConfig::default_creator_fee_bps")]
+    pub creator_fee_bps: i64,
+}
+
+impl FeeConfig {
+    pub const fn default_fee_bps() -> i64 {
+        120
+    }
+
+    pub const fn default_creator_fee_bps() -> i64 {
+        3000
+    }
+}
+
+impl Default for FeeConfig {
+    fn default() -> Self {
+        Self {
+            fee_bps: Self::default_fee_bps(),
+            creator_fee_bps: Self::default_creator_fee_bps(),
+        }
+    }
 }
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct LeaderboardConfig {
+    #[serde(default = "LeaderboardConfig::default_refresh_interval_secs")]
+    pub refresh_interval_secs: u64,
+    // Number of ranked rows kept per leaderboard and window
+    #[serde(default = "LeaderboardConfig::default_size")]
+    pub size: i64,
+}
+
+impl LeaderboardConfig {
+    pub const fn default_refresh_interval_secs() -> u64 {
+        60
+    }
+
+    pub const fn default_size() -> i64 {
+        100
+    }
+}
+
+impl Default for LeaderboardConfig {
+    fn default() -> Self {
+        Self {
+            refresh_interval_secs: Self::default_refresh_interval_secs(),
+            size: Self::default_size(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiConfig {
+    // Serves the read api from the health server
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "ApiConfig::default_page_size")]
+    pub default_page_size: i64,
+    #[serde(default = "ApiConfig::default_max_page_size")]
+    pub max_page_size: i64,
+    // Size of the pool used by the api, separate from the processor's
+    #[serde(default = "ApiConfig::default_db_pool_size")]
+    pub db_pool_size: u32,
+}
+
+impl ApiConfig {
+    pub const fn default_page_size() -> i64 {
+        50
+    }
+
+    pub const fn default_max_page_size() -> i64 {
+        500
+    }
+
+    pub const fn default_db_pool_size() -> u32 {
+        10
+    }
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            default_page_size: Self::default_page_size(),
+            max_page_size: Self::default_max_page_size(),
+            db_pool_size: Self::default_db_pool_size(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct GraphqlConfig {
+    // Serves the graphql api at /graphql from the health server
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "GraphqlConfig::default_page_size")]
+    pub default_page_size: i64,
+    #[serde(default = "GraphqlConfig::default_max_page_size")]
+    pub max_page_size: i64,
+    // Queries above this complexity are rejected, list fields count as limit x selection
+    #[serde(default = "GraphqlConfig::default_max_complexity")]
+    pub max_complexity: usize,
+    #[serde(default = "GraphqlConfig::default_max_depth")]
+    pub max_depth: usize,
+    #[serde(default = "GraphqlConfig::default_db_pool_size")]
+    pub db_pool_size: u32,
+}
+
+impl GraphqlConfig {
+    pub const fn default_page_size() -> i64 {
+        20
+    }
+
+    pub const fn default_max_page_size() -> i64 {
+        100
+    }
+
+    pub const fn default_max_complexity() -> usize {
+        2000
+    }
+
+    pub const fn default_max_depth() -> usize {
+        6
+    }
+
+    pub const fn default_db_pool_size() -> u32 {
+        10
+    }
+}
+
+impl Default for GraphqlConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            default_page_size: Self::default_page_size(),
+            max_page_size: Self::default_max_page_size(),
+            max_complexity: Self::default_max_complexity(),
+            max_depth: Self::default_max_depth(),
+            db_pool_size: Self::default_db_pool_size(),
+        }
+    }
+}
+
+/// Backend notified of new tokens, trades and spin wins.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebhookConfig {
+    #[serde(default = "WebhookConfig::default_enabled")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub backend_url: Option<String>,
+    // Sent in the x-indexer header
+    #[serde(default)]
+    pub access_token: Option<String>,
+    #[serde(default = "WebhookConfig::default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+impl WebhookConfig {
+    pub const fn default_enabled() -> bool {
+        true
+    }
+
+    pub const fn default_timeout_secs() -> u64 {
+        10
+    }
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            enabled: Self::default_enabled(),
+            backend_url: None,
+            access_token: None,
+            timeout_secs: Self::default_timeout_secs(),
+        }
+    }
+}
+
+/// Catch-all capture of contract events into `raw_contract_events`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawEventsConfig {
+    #[serde(default = "RawEventsConfig::default_enabled")]
+    pub enabled: bool,
+    // Also store every write of a resource defined by the contract
+    #[serde(default = "RawEventsConfig::default_include_resources")]
+    pub include_resources: bool,
+}
+
+impl RawEventsConfig {
+    pub const fn default_enabled() -> bool {
+        true
+    }
+
+    pub const fn default_include_resources() -> bool {
+        true
+    }
+}
+
+impl Default for RawEventsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: Self::default_enabled(),
+            include_resources: Self::default_include_resources(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct UpgradeConfig {
+    // Stops the processor when an upgrade changes the shape of an event it decodes
+    #[serde(default)]
+    pub halt_on_breaking_change: bool,
+}
+
+/// Heuristics flagging botted launches into `trade_flags` and `token_risk_scores`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct TradeFlagsConfig {
+    #[serde(default = "TradeFlagsConfig::default_enabled")]
+    pub enabled: bool,
+    // A sell this soon after a buy of the same user is a round trip
+    #[serde(default = "TradeFlagsConfig::default_round_trip_secs")]
+    pub round_trip_secs: i64,
+    // Opposite trades of the creator and the wallets it funded this close are wash trades
+    #[serde(default = "TradeFlagsConfig::default_wash_window_secs")]
+    pub wash_window_secs: i64,
+    // Transfers from wallets that are not creators yet are kept in creator_fundings this long,
+    // to link the wallets funded right before a launch
+    #[serde(default = "TradeFlagsConfig::default_funding_lookback_secs")]
+    pub funding_lookback_secs: i64,
+}
+
+impl TradeFlagsConfig {
+    pub const fn default_enabled() -> bool {
+        true
+    }
+
+    pub const fn default_round_trip_secs() -> i64 {
+        300
+    }
+
+    pub const fn default_wash_window_secs() -> i64 {
+        600
+    }
+
+    pub const fn default_funding_lookback_secs() -> i64 {
+        86400
+    }
+}
+
+impl Default for TradeFlagsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: Self::default_enabled(),
+            round_trip_secs: Self::default_round_trip_secs(),
+            wash_window_secs: Self::default_wash_window_secs(),
+            funding_lookback_secs: Self::default_funding_lookback_secs(),
+        }
+    }
+}
+
+/// Labels of known addresses in `address_labels`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct AddressLabelsConfig {
+    // Csv or yaml file imported at startup, replacing the labels of the previous import
+    #[serde(default)]
+    pub file: Option<String>,
+    // Label the pools, config object, fee wallet, Thala pools and games of the contracts
+    #[serde(default = "AddressLabelsConfig::default_auto_labels")]
+    pub auto_labels: bool,
+}
+
+impl AddressLabelsConfig {
+    pub const fn default_auto_labels() -> bool {
+        true
+    }
+}
+
+impl Default for AddressLabelsConfig {
+    fn default() -> Self {
+        Self {
+            file: None,
+            auto_labels: Self::default_auto_labels(),
+        }
+    }
+}
+
+/// Thresholds of the trust badge in `creators`, percentages are in percent.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreatorConfig {
+    // Tokens a creator needs to have launched to be trusted
+    #[serde(default = "CreatorConfig::default_trusted_min_tokens")]
+    pub trusted_min_tokens: i32,
+    #[serde(default = "CreatorConfig::default_trusted_min_graduation_rate")]
+    pub trusted_min_graduation_rate: i64,
+    // Average share of the supply a trusted creator sells within 24h of a launch
+    #[serde(default = "CreatorConfig::default_trusted_max_early_sell_percentage")]
+    pub trusted_max_early_sell_percentage: i64,
+    // Creators selling more than this share within 24h are risky, whatever their history
+    #[serde(default = "CreatorConfig::default_risky_early_sell_percentage")]
+    pub risky_early_sell_percentage: i64,
+}
+
+impl CreatorConfig {
+    pub const fn default_trusted_min_tokens() -> i32 {
+        3
+    }
+
+    pub const fn default_trusted_min_graduation_rate() -> i64 {
+        20
+    }
+
+    pub const fn default_trusted_max_early_sell_percentage() -> i64 {
+        5
+    }
+
+    pub const fn default_risky_early_sell_percentage() -> i64 {
+        50
+    }
+}
+
+impl Default for CreatorConfig {
+    fn default() -> Self {
+        Self {
+            trusted_min_tokens: Self::default_trusted_min_tokens(),
+            trusted_min_graduation_rate: Self::default_trusted_min_graduation_rate(),
+            trusted_max_early_sell_percentage: Self::default_trusted_max_early_sell_percentage(),
+            risky_early_sell_percentage: Self::default_risky_early_sell_percentage(),
+        }
+    }
+}
+
+/// Bonding curve progress, buy velocity and graduation signals kept on `tokens`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct CurveConfig {
+    // Net buys over this window make the velocity the graduation ETA is projected with
+    #[serde(default = "CurveConfig::default_velocity_window_secs")]
+    pub velocity_window_secs: i64,
+    // Curve progress, in percent, at which a near graduation signal is emitted once per token
+    #[serde(default = "CurveConfig::default_near_graduation_thresholds")]
+    pub near_graduation_thresholds: Vec<i32>,
+}
+
+impl CurveConfig {
+    pub const fn default_velocity_window_secs() -> i64 {
+        3600
+    }
+
+    pub fn default_near_graduation_thresholds() -> Vec<i32> {
+        vec![80, 95]
+    }
+}
+
+impl Default for CurveConfig {
+    fn default() -> Self {
+        Self {
+            velocity_window_secs: Self::default_velocity_window_secs(),
+            near_graduation_thresholds: Self::default_near_graduation_thresholds(),
+        }
+    }
+}
+
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceSource {
+    // Trades are left without USD values
+    #[default]
+    None,
+    Http,
+    Csv,
+    Pyth,
+}
+
+/// Source of the APT/USD minute grid in `apt_usd_prices`, trades are valued in USD from it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct PriceConfig {
+    #[serde(default)]
+    pub source: PriceSource,
+    // Binance compatible klines endpoint, polled for the closed minutes after the last stored one
+    #[serde(default = "PriceConfig::default_http_url")]
+    pub http_url: String,
+    #[serde(default = "PriceConfig::default_poll_interval_secs")]
+    pub poll_interval_secs: u64,
+    // Csv with a ts,price header imported at startup, whatever the source, to backfill history
+    #[serde(default)]
+    pub csv_file: Option<String>,
+    // Pyth contract whose PriceFeedUpdate events of the price feed are read
+    #[serde(default = "PriceConfig::default_pyth_address")]
+    pub pyth_address: String,
+    #[serde(default = "PriceConfig::default_pyth_price_id")]
+    pub pyth_price_id: String,
+    // Trades are valued at the latest price of their minute or before, if it is at most this old
+    #[serde(default = "PriceConfig::default_max_price_age_secs")]
+    pub max_price_age_secs: i64,
+}
+
+impl PriceConfig {
+    /// Whether trades are valued, from a live source or an imported file
+    pub fn enabled(&self) -> bool {
+        self.source != PriceSource::None || self.csv_file.is_some()
+    }
+
+    pub fn default_http_url() -> String {
+        "https://api.binance.com/api/v3/klines?symbol=APTUSDT&interval=1m".to_string()
+    }
+
+    pub const fn default_poll_interval_secs() -> u64 {
+        60
+    }
+
+    pub fn default_pyth_address() -> String {
+        "0x7e783b349d3e89cf5931af376ebeadbfab855b3fa239b7ada8f5a92fbea6b387".to_string()
+    }
+
+    pub fn default_pyth_price_id() -> String {
+        "0x03ae4db29ed4ae33d323568895aa00337e658e348b37509f5372ae51f0af00d5".to_string()
+    }
+
+    pub const fn default_max_price_age_secs() -> i64 {
+        300
+    }
+}
+
+impl Default for PriceConfig {
+    fn default() -> Self {
+        Self {
+            source: PriceSource::default(),
+            http_url: Self::default_http_url(),
+            poll_interval_secs: Self::default_poll_interval_secs(),
+            csv_file: None,
+            pyth_address: Self::default_pyth_address(),
+            pyth_price_id: Self::default_pyth_price_id(),
+            max_price_age_secs: Self::default_max_price_age_secs(),
+        }
+    }
+}
+
+/// Periodic market snapshots of the traded tokens in `token_metrics`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct TokenMetricsConfig {
+    #[serde(default = "TokenMetricsConfig::default_enabled")]
+    pub enabled: bool,
+    // Snapshots are taken at multiples of this interval of chain time
+    #[serde(default = "TokenMetricsConfig::default_snapshot_interval_secs")]
+    pub snapshot_interval_secs: i64,
+}
+
+impl TokenMetricsConfig {
+    pub const fn default_enabled() -> bool {
+        true
+    }
+
+    pub const fn default_snapshot_interval_secs() -> i64 {
+        300
+    }
+}
+
+impl Default for TokenMetricsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: Self::default_enabled(),
+            snapshot_interval_secs: Self::default_snapshot_interval_secs(),
+        }
+    }
+}
+
+/// Trending ranking of the recently traded tokens in `trending_tokens`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct TrendingConfig {
+    #[serde(default = "TrendingConfig::default_enabled")]
+    pub enabled: bool,
+    #[serde(default = "TrendingConfig::default_refresh_interval_secs")]
+    pub refresh_interval_secs: u64,
+    // Trades older than this, in chain time, are not counted
+    #[serde(default = "TrendingConfig::default_window_secs")]
+    pub window_secs: i64,
+    // The weight of a trade halves every half life
+    #[serde(default = "TrendingConfig::default_half_life_secs")]
+    pub half_life_secs: i64,
+    // Number of tokens ranked
+    #[serde(default = "TrendingConfig::default_size")]
+    pub size: i64,
+    #[serde(default)]
+    pub weights: TrendingWeights,
+}
+
+impl TrendingConfig {
+    pub const fn default_enabled() -> bool {
+        true
+    }
+
+    pub const fn default_refresh_interval_secs() -> u64 {
+        60
+    }
+
+    pub const fn default_window_secs() -> i64 {
+        86_400
+    }
+
+    pub const fn default_half_life_secs() -> i64 {
+        3_600
+    }
+
+    pub const fn default_size() -> i64 {
+        100
+    }
+}
+
+impl Default for TrendingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: Self::default_enabled(),
+            refresh_interval_secs: Self::default_refresh_interval_secs(),
+            window_secs: Self::default_window_secs(),
+            half_life_secs: Self::default_half_life_secs(),
+            size: Self::default_size(),
+            weights: TrendingWeights::default(),
+        }
+    }
+}
+
+/// Weights of the trending score components, each component is scaled to the highest
+/// token's before weighting.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct TrendingWeights {
+    #[serde(default = "TrendingWeights::default_volume")]
+    pub volume: f64,
+    #[serde(default = "TrendingWeights::default_unique_buyers")]
+    pub unique_buyers: f64,
+    #[serde(default = "TrendingWeights::default_trade_count")]
+    pub trade_count: f64,
+    #[serde(default = "TrendingWeights::default_holder_growth")]
+    pub holder_growth: f64,
+    #[serde(default = "TrendingWeights::default_curve_progress")]
+    pub curve_progress: f64,
+}
+
+impl TrendingWeights {
+    pub const fn default_volume() -> f64 {
+        0.35
+    }
+
+    pub const fn default_unique_buyers() -> f64 {
+        0.25
+    }
+
+    pub const fn default_trade_count() -> f64 {
+        0.15
+    }
+
+    pub const fn default_holder_growth() -> f64 {
+        0.15
+    }
+
+    pub const fn default_curve_progress() -> f64 {
+        0.1
+    }
+}
+
+impl Default for TrendingWeights {
+    fn default() -> Self {
+        Self {
+            volume: Self::default_volume(),
+            unique_buyers: Self::default_unique_buyers(),
+            trade_count: Self::default_trade_count(),
+            holder_growth: Self::default_holder_growth(),
+            curve_progress: Self::default_curve_progress(),
+        }
+    }
+}
+
+/// On-chain context of the chat message authors in `chat_author_context`.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChatContextConfig {
+    #[serde(default = "ChatContextConfig::default_enabled")]
+    pub enabled: bool,
+    #[serde(default = "ChatContextConfig::default_poll_interval_secs")]
+    pub poll_interval_secs: u64,
+}
+
+impl ChatContextConfig {
+    pub const fn default_enabled() -> bool {
+        true
+    }
+
+    pub const fn default_poll_interval_secs() -> u64 {
+        10
+    }
+}
+
+impl Default for ChatContextConfig {
+    fn default() -> Self {
+        Self {
+            enabled: Self::default_enabled(),
+            poll_interval_secs: Self::default_poll_interval_secs(),
+        }
+    }
+}
\ No newline at end of file
@@ -1,2 +1,3 @@
+pub mod env_overrides;
 pub mod indexer_processor_config;
 pub mod processor_config;
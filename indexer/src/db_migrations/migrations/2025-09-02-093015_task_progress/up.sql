@@ -0,0 +1,12 @@
+-- Your SQL goes here
+CREATE TABLE task_progress (
+    task_id INT NOT NULL REFERENCES tasks(id) ON DELETE CASCADE,
+    address VARCHAR(66) NOT NULL,
+    progress DECIMAL(39,0) DEFAULT 0 NOT NULL, -- accumulated amount in the requirement's unit
+    hold_started_at BIGINT, -- only used by "hold" requirements
+    completed_at_version BIGINT,
+    last_txn_version BIGINT NOT NULL,
+    PRIMARY KEY (task_id, address)
+);
+
+CREATE INDEX idx_task_progress_address ON task_progress(address);
@@ -0,0 +1,26 @@
+-- Your SQL goes here
+CREATE TABLE xp_ledger (
+    id BIGSERIAL PRIMARY KEY,
+    address VARCHAR(66) NOT NULL,
+    delta INT NOT NULL,
+    source_type VARCHAR(50) NOT NULL, -- e.g. trade
+    source_ref VARCHAR(300) NOT NULL, -- id of the source within its type, e.g. the txn_version of a trade
+    txn_version BIGINT,
+    created_at TIMESTAMP DEFAULT NOW() NOT NULL,
+    CONSTRAINT uq_xp_ledger_source UNIQUE (source_type, source_ref)
+);
+
+CREATE INDEX idx_xp_ledger_address ON xp_ledger(address);
+
+-- XP credited before the ledger existed, or by the backend outside of it, is carried over as
+-- one opening entry per account so the ledger sums to the stored xp. Earned and spent xp are
+-- kept apart since xp_earned only counts positive entries.
+INSERT INTO xp_ledger (address, delta, source_type, source_ref)
+SELECT address, xp_earned, 'opening_balance', address
+FROM accounts
+WHERE xp_earned > 0;
+
+INSERT INTO xp_ledger (address, delta, source_type, source_ref)
+SELECT address, xp - GREATEST(xp_earned, 0), 'opening_spent', address
+FROM accounts
+WHERE xp - GREATEST(xp_earned, 0) < 0;
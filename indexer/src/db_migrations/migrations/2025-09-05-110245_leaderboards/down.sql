@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE stakings DROP COLUMN IF EXISTS ts;
+DROP TABLE IF EXISTS leaderboards;
\ No newline at end of file
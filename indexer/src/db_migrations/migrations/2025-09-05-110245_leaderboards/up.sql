@@ -0,0 +1,19 @@
+-- Your SQL goes here
+CREATE TABLE leaderboards (
+    board VARCHAR(50) NOT NULL, -- trader_volume, trader_pnl, creator_fees or staker_amount
+    time_window VARCHAR(10) NOT NULL, -- 24h, 7d or all
+    rank INT NOT NULL,
+    address VARCHAR(66) NOT NULL,
+    value DECIMAL(39,0) NOT NULL,
+    refreshed_at_ts BIGINT NOT NULL,
+    PRIMARY KEY (board, time_window, rank)
+);
+
+CREATE INDEX idx_leaderboards_address ON leaderboards(address);
+
+-- Time the position was created, the staker boards are windowed on it. NULL for positions
+-- stored before it was tracked, they only count over all time.
+ALTER TABLE stakings
+ADD COLUMN ts BIGINT;
+
+CREATE INDEX idx_stakings_ts ON stakings(ts);
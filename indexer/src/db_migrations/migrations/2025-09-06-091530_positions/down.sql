@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP VIEW IF EXISTS position_pnl;
+DROP INDEX IF EXISTS trades_ts_idx;
+DROP TABLE IF EXISTS realized_pnl;
+DROP TABLE IF EXISTS positions;
\ No newline at end of file
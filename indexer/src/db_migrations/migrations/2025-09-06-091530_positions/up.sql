@@ -0,0 +1,44 @@
+-- Your SQL goes here
+CREATE TABLE positions (
+    user_addr VARCHAR(66) NOT NULL,
+    token_address VARCHAR(66) NOT NULL,
+    balance DECIMAL(39,0) NOT NULL, -- tokens held, from trades only
+    cost_basis DECIMAL(39,0) NOT NULL, -- average cost of the held balance, in octas
+    realized_pnl DECIMAL(39,0) NOT NULL, -- in octas, net of fees
+    aptos_bought DECIMAL(39,0) NOT NULL,
+    aptos_sold DECIMAL(39,0) NOT NULL,
+    last_txn_version BIGINT NOT NULL,
+    volume DECIMAL(39,0) NOT NULL, -- gross aptos traded
+    PRIMARY KEY (user_addr, token_address)
+);
+
+CREATE INDEX idx_positions_token_address ON positions(token_address);
+
+-- PnL realized by each sell, written when the sell is applied to its position
+CREATE TABLE realized_pnl (
+    txn_version BIGINT PRIMARY KEY,
+    user_addr VARCHAR(66) NOT NULL,
+    token_address VARCHAR(66) NOT NULL,
+    pnl DECIMAL(39,0) NOT NULL, -- in octas, net of fees
+    ts BIGINT NOT NULL
+);
+
+CREATE INDEX realized_pnl_ts_idx ON realized_pnl (ts);
+
+-- Leaderboard windows are read as ranges of the trade timestamps
+CREATE INDEX trades_ts_idx ON trades (ts);
+
+-- Marks every position to the reserves of the latest trade on its token
+CREATE VIEW position_pnl AS
+SELECT
+    p.*,
+    TRUNC(p.balance * t.virtual_aptos_reserves / t.virtual_token_reserves) AS market_value,
+    TRUNC(p.balance * t.virtual_aptos_reserves / t.virtual_token_reserves) - p.cost_basis AS unrealized_pnl
+FROM positions p
+JOIN LATERAL (
+    SELECT virtual_aptos_reserves, virtual_token_reserves
+    FROM trades
+    WHERE trades.token_address = p.token_address
+    ORDER BY txn_version DESC
+    LIMIT 1
+) t ON t.virtual_token_reserves > 0;
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE tokens DROP COLUMN IF EXISTS contract_address;
+ALTER TABLE trades DROP COLUMN IF EXISTS contract_address;
+ALTER TABLE stakings DROP COLUMN IF EXISTS contract_address;
\ No newline at end of file
@@ -0,0 +1,10 @@
+-- Your SQL goes here
+-- Rows indexed before contracts were tagged are left empty, the processor tags them with the
+-- configured contract at startup, see ContractConfig::untagged_contract
+ALTER TABLE tokens ADD COLUMN contract_address VARCHAR(66) NOT NULL DEFAULT '';
+ALTER TABLE trades ADD COLUMN contract_address VARCHAR(66) NOT NULL DEFAULT '';
+ALTER TABLE stakings ADD COLUMN contract_address VARCHAR(66) NOT NULL DEFAULT '';
+
+CREATE INDEX idx_tokens_contract_address ON tokens(contract_address);
+CREATE INDEX idx_trades_contract_address ON trades(contract_address);
+CREATE INDEX idx_stakings_contract_address ON stakings(contract_address);
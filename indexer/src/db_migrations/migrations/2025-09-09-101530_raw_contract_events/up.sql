@@ -0,0 +1,21 @@
+-- Your SQL goes here
+CREATE TABLE
+    raw_contract_events (
+        txn_version BIGINT NOT NULL,
+        -- 'event' or 'resource'
+        kind VARCHAR(16) NOT NULL,
+        -- Index of the event, or of the write set change for resources
+        event_index BIGINT NOT NULL,
+        contract_address VARCHAR(66) NOT NULL,
+        module_name VARCHAR(300) NOT NULL,
+        struct_name VARCHAR(300) NOT NULL,
+        type_str TEXT NOT NULL,
+        -- Account holding the resource, null for events
+        account_address VARCHAR(66),
+        data JSONB NOT NULL,
+        -- False when no ABI covered the type and data is the undecoded json
+        decoded BOOLEAN NOT NULL,
+        PRIMARY KEY (txn_version, kind, event_index)
+    );
+
+CREATE INDEX raw_contract_events_type_idx ON raw_contract_events (contract_address, module_name, struct_name, txn_version);
@@ -0,0 +1,20 @@
+-- Your SQL goes here
+CREATE TABLE
+    module_abi_changes (
+        module_addr VARCHAR(300) NOT NULL,
+        module_name VARCHAR(300) NOT NULL,
+        upgrade_number BIGINT NOT NULL,
+        previous_upgrade_number BIGINT NOT NULL,
+        -- 'function', 'struct' or 'field'
+        item_kind VARCHAR(16) NOT NULL,
+        -- Function or struct name, Struct.field for fields
+        item_name VARCHAR(600) NOT NULL,
+        -- 'added', 'removed' or 'changed'
+        change_type VARCHAR(16) NOT NULL,
+        old_abi JSONB,
+        new_abi JSONB,
+        -- Touches an event struct the indexer decodes
+        is_breaking BOOLEAN NOT NULL,
+        tx_version BIGINT NOT NULL,
+        PRIMARY KEY (module_addr, module_name, upgrade_number, item_kind, item_name)
+    );
@@ -0,0 +1,4 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS package_dependencies;
+ALTER TABLE module_upgrade_history DROP COLUMN IF EXISTS module_source_text;
+ALTER TABLE package_upgrade_history DROP COLUMN IF EXISTS manifest_text;
\ No newline at end of file
@@ -0,0 +1,22 @@
+-- Your SQL goes here
+ALTER TABLE package_upgrade_history ADD COLUMN manifest_text TEXT;
+ALTER TABLE module_upgrade_history ADD COLUMN module_source_text TEXT;
+
+CREATE TABLE
+    package_dependencies (
+        package_addr VARCHAR(300) NOT NULL,
+        package_name VARCHAR(300) NOT NULL,
+        upgrade_number BIGINT NOT NULL,
+        dependency_name VARCHAR(300) NOT NULL,
+        -- Account the dependency is published at, from the on-chain deps
+        dependency_addr VARCHAR(66),
+        -- Source declared in the manifest
+        git TEXT,
+        rev TEXT,
+        subdir TEXT,
+        local_path TEXT,
+        tx_version BIGINT NOT NULL,
+        PRIMARY KEY (package_addr, package_name, upgrade_number, dependency_name)
+    );
+
+CREATE INDEX package_dependencies_name_idx ON package_dependencies (dependency_name);
@@ -0,0 +1,3 @@
+-- This file should undo anything in `up.sql`
+ALTER TABLE module_upgrade_history DROP COLUMN IF EXISTS partial_reason;
+ALTER TABLE package_upgrade_history DROP COLUMN IF EXISTS deployment;
\ No newline at end of file
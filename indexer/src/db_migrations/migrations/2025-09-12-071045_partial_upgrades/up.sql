@@ -0,0 +1,3 @@
+-- Your SQL goes here
+ALTER TABLE module_upgrade_history ADD COLUMN partial_reason TEXT;
+ALTER TABLE package_upgrade_history ADD COLUMN deployment VARCHAR(16) NOT NULL DEFAULT 'account';
@@ -0,0 +1,21 @@
+-- Your SQL goes here
+CREATE TABLE
+    failed_transactions (
+        txn_version BIGINT NOT NULL PRIMARY KEY,
+        contract_address VARCHAR(66) NOT NULL,
+        sender VARCHAR(66) NOT NULL,
+        -- Entry function id, address::module::function
+        function VARCHAR(600) NOT NULL,
+        type_arguments JSONB NOT NULL,
+        arguments JSONB NOT NULL,
+        gas_used BIGINT NOT NULL,
+        vm_status TEXT NOT NULL,
+        -- Set when the transaction aborted in Move
+        abort_module VARCHAR(300),
+        abort_code BIGINT,
+        abort_name VARCHAR(300),
+        ts BIGINT NOT NULL
+    );
+
+CREATE INDEX failed_transactions_sender_idx ON failed_transactions (sender, txn_version);
+CREATE INDEX failed_transactions_abort_idx ON failed_transactions (abort_name, txn_version);
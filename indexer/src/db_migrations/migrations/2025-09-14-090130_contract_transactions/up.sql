@@ -0,0 +1,25 @@
+-- Your SQL goes here
+CREATE TABLE
+    contract_transactions (
+        txn_version BIGINT NOT NULL PRIMARY KEY,
+        contract_address VARCHAR(66) NOT NULL,
+        sender VARCHAR(66) NOT NULL,
+        sequence_number BIGINT NOT NULL,
+        module_name VARCHAR(255) NOT NULL,
+        function_name VARCHAR(255) NOT NULL,
+        -- Entry function id, address::module::function
+        function VARCHAR(600) NOT NULL,
+        type_arguments JSONB NOT NULL,
+        -- Decoded with the function params when `decoded`, as sent otherwise
+        arguments JSONB NOT NULL,
+        decoded BOOLEAN NOT NULL,
+        success BOOLEAN NOT NULL,
+        vm_status TEXT NOT NULL,
+        gas_used BIGINT NOT NULL,
+        gas_unit_price BIGINT NOT NULL,
+        max_gas_amount BIGINT NOT NULL,
+        ts BIGINT NOT NULL
+    );
+
+CREATE INDEX contract_transactions_sender_idx ON contract_transactions (sender, txn_version);
+CREATE INDEX contract_transactions_function_idx ON contract_transactions (module_name, function_name, txn_version);
@@ -0,0 +1,5 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS trades_whitelist_idx;
+ALTER TABLE trades
+DROP COLUMN IF EXISTS in_whitelist_window;
+DROP TABLE IF EXISTS whitelist_windows;
\ No newline at end of file
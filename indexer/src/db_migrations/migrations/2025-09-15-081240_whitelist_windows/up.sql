@@ -0,0 +1,20 @@
+-- Your SQL goes here
+CREATE TABLE
+    whitelist_windows (
+        -- pre_addr of the token, as in trades.token_address
+        token_address VARCHAR(66) NOT NULL PRIMARY KEY,
+        pool_addr VARCHAR(66) NOT NULL,
+        contract_address VARCHAR(66) NOT NULL,
+        -- Config.whitelist_duration when the token was created
+        whitelist_duration BIGINT NOT NULL,
+        starts_at BIGINT NOT NULL,
+        ends_at BIGINT NOT NULL,
+        txn_version BIGINT NOT NULL
+    );
+
+-- NULL when the window of the token is unknown, trades of tokens created before the
+-- windows were indexed need a reindex from their creation
+ALTER TABLE trades
+ADD COLUMN in_whitelist_window BOOLEAN;
+
+CREATE INDEX trades_whitelist_idx ON trades (token_address, in_whitelist_window);
@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS token_risk_scores;
+DROP TABLE IF EXISTS trade_flags;
+DROP TABLE IF EXISTS creator_fundings;
+ALTER TABLE trades
+DROP COLUMN IF EXISTS block_height;
+ALTER TABLE tokens
+DROP COLUMN IF EXISTS block_height;
\ No newline at end of file
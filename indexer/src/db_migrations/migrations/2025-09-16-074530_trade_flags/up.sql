@@ -0,0 +1,56 @@
+-- Your SQL goes here
+ALTER TABLE tokens
+ADD COLUMN block_height BIGINT;
+
+ALTER TABLE trades
+ADD COLUMN block_height BIGINT;
+
+-- APT sent by a token creator, before or after it created a token. Transfers of wallets
+-- that did not create a token yet are also kept, and pruned by ts once older than the
+-- funding lookback.
+CREATE TABLE
+    creator_fundings (
+        txn_version BIGINT NOT NULL,
+        recipient VARCHAR(66) NOT NULL,
+        creator VARCHAR(66) NOT NULL,
+        amount BIGINT NOT NULL,
+        ts BIGINT NOT NULL,
+        PRIMARY KEY (txn_version, recipient)
+    );
+
+CREATE INDEX creator_fundings_creator_idx ON creator_fundings (creator, recipient);
+
+CREATE INDEX creator_fundings_ts_idx ON creator_fundings (ts);
+
+CREATE TABLE
+    trade_flags (
+        txn_version BIGINT NOT NULL,
+        -- sniper, bundle, round_trip or wash
+        flag VARCHAR(20) NOT NULL,
+        token_address VARCHAR(66) NOT NULL,
+        user_addr VARCHAR(66) NOT NULL,
+        -- What triggered the flag, e.g. the related txn_version
+        detail JSONB NOT NULL,
+        ts BIGINT NOT NULL,
+        PRIMARY KEY (txn_version, flag)
+    );
+
+CREATE INDEX trade_flags_token_idx ON trade_flags (token_address, flag);
+
+CREATE INDEX trade_flags_user_idx ON trade_flags (user_addr, txn_version);
+
+CREATE TABLE
+    token_risk_scores (
+        token_address VARCHAR(66) NOT NULL PRIMARY KEY,
+        -- 0 to 100, share of the traded volume that was flagged
+        risk_score INT NOT NULL,
+        sniper_trades INT NOT NULL,
+        bundle_trades INT NOT NULL,
+        round_trip_trades INT NOT NULL,
+        wash_trades INT NOT NULL,
+        flagged_volume BIGINT NOT NULL,
+        total_volume BIGINT NOT NULL,
+        updated_at_version BIGINT NOT NULL
+    );
+
+CREATE INDEX token_risk_scores_score_idx ON token_risk_scores (risk_score);
@@ -0,0 +1,23 @@
+-- Your SQL goes here
+CREATE TABLE
+    address_labels (
+        address VARCHAR(66) NOT NULL,
+        label VARCHAR(100) NOT NULL,
+        -- fee_wallet, team, bot, cex, pool, config, game, thala_pool, ...
+        category VARCHAR(50) NOT NULL,
+        -- addresses sharing a cluster belong to the same party, e.g. the team wallets
+        cluster VARCHAR(100),
+        -- file when imported from address_labels_config.file, auto when derived by the indexer
+        source VARCHAR(10) NOT NULL,
+        -- token the address belongs to, pre_addr for pools and main_addr for Thala pools
+        token_address VARCHAR(66),
+        -- transaction the auto label was derived from
+        txn_version BIGINT,
+        -- a file label does not replace the auto label of the same name, both are kept and
+        -- the file one is returned
+        PRIMARY KEY (address, label, source)
+    );
+
+CREATE INDEX address_labels_category_idx ON address_labels (category);
+
+CREATE INDEX address_labels_cluster_idx ON address_labels (cluster);
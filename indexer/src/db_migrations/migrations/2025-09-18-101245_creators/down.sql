@@ -0,0 +1,8 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS money_configs;
+ALTER TABLE trades
+DROP COLUMN IF EXISTS fee,
+DROP COLUMN IF EXISTS creator_fee;
+DROP TABLE IF EXISTS creators;
+ALTER TABLE tokens
+DROP COLUMN IF EXISTS creator_sold_24h;
\ No newline at end of file
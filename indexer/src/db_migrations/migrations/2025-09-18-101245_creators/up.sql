@@ -0,0 +1,47 @@
+-- Your SQL goes here
+CREATE TABLE
+    creators (
+        creator VARCHAR(66) NOT NULL PRIMARY KEY,
+        tokens_launched INT NOT NULL,
+        tokens_graduated INT NOT NULL,
+        -- graduated over launched tokens, in percent
+        graduation_rate NUMERIC NOT NULL,
+        -- creator share of the trading fees of all its tokens, in octas
+        creator_fees BIGINT NOT NULL,
+        -- holders with a balance left, averaged over the launched tokens
+        avg_holders NUMERIC NOT NULL,
+        -- tokens the creator sold within 24h of each launch
+        early_sold_token_amount NUMERIC NOT NULL,
+        -- share of the supply sold within 24h, averaged over the launched tokens, in percent
+        early_sell_percentage NUMERIC NOT NULL,
+        -- trusted, risky or unproven
+        trust_badge VARCHAR(20) NOT NULL,
+        first_launch_ts BIGINT NOT NULL,
+        last_launch_ts BIGINT NOT NULL,
+        updated_at_version BIGINT NOT NULL
+    );
+
+CREATE INDEX creators_badge_idx ON creators (trust_badge);
+
+-- Tokens the creator sold within 24h of the launch
+ALTER TABLE tokens
+ADD COLUMN creator_sold_24h NUMERIC NOT NULL DEFAULT 0;
+
+-- Fees of the money module's Config at each write of the resource, which creating a token and
+-- update_config do. Trades pay the fees of the latest write before them.
+CREATE TABLE
+    money_configs (
+        contract_address VARCHAR(66) NOT NULL,
+        txn_version BIGINT NOT NULL,
+        -- Trading fee, in basis points of the trade
+        fee_bps BIGINT NOT NULL,
+        -- Creator share of the trading fee, in basis points of the fee
+        creator_fee_bps BIGINT NOT NULL,
+        PRIMARY KEY (contract_address, txn_version)
+    );
+
+-- Fees paid on the trade in octas, at the Config of its version. NULL for trades stored before
+-- the Config was tracked, those are valued with fee_config.
+ALTER TABLE trades
+ADD COLUMN fee BIGINT,
+ADD COLUMN creator_fee BIGINT;
@@ -0,0 +1,9 @@
+-- This file should undo anything in `up.sql`
+DROP TABLE IF EXISTS graduation_signals;
+ALTER TABLE tokens
+DROP COLUMN IF EXISTS curve_supply,
+DROP COLUMN IF EXISTS graduation_aptos_reserves,
+DROP COLUMN IF EXISTS curve_progress,
+DROP COLUMN IF EXISTS buy_velocity,
+DROP COLUMN IF EXISTS graduation_eta,
+DROP COLUMN IF EXISTS curve_updated_version;
\ No newline at end of file
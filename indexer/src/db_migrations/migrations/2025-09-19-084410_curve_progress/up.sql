@@ -0,0 +1,40 @@
+-- Your SQL goes here
+-- Curve of the token, from the reserves of its TokenCreated event
+ALTER TABLE tokens
+ADD COLUMN curve_supply NUMERIC,
+ADD COLUMN graduation_aptos_reserves NUMERIC,
+-- share of curve_supply sold, in percent
+ADD COLUMN curve_progress NUMERIC NOT NULL DEFAULT 0,
+-- net aptos bought per second over curve_config.velocity_window_secs, in octas
+ADD COLUMN buy_velocity NUMERIC NOT NULL DEFAULT 0,
+-- projected graduation at the current velocity, NULL when not buying or graduated
+ADD COLUMN graduation_eta BIGINT,
+ADD COLUMN curve_updated_version BIGINT;
+
+-- Tokens created before the curve was indexed, truncated to whole octas like the indexer does
+-- for new tokens
+UPDATE tokens
+SET
+    curve_supply = virtual_token_reserves - remain_token_reserves,
+    graduation_aptos_reserves = TRUNC(virtual_aptos_reserves * virtual_token_reserves / NULLIF(remain_token_reserves, 0));
+
+UPDATE tokens
+SET
+    curve_progress = 100
+WHERE
+    is_completed;
+
+CREATE TABLE
+    graduation_signals (
+        -- pre_addr of the token, as in trades.token_address
+        token_address VARCHAR(66) NOT NULL,
+        -- one of curve_config.near_graduation_thresholds, in percent
+        threshold INT NOT NULL,
+        curve_progress NUMERIC NOT NULL,
+        graduation_eta BIGINT,
+        txn_version BIGINT NOT NULL,
+        ts BIGINT NOT NULL,
+        PRIMARY KEY (token_address, threshold)
+    );
+
+CREATE INDEX graduation_signals_version_idx ON graduation_signals (txn_version);
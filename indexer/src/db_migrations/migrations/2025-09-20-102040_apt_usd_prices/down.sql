@@ -0,0 +1,7 @@
+-- This file should undo anything in `up.sql`
+DROP INDEX IF EXISTS trades_unpriced_idx;
+ALTER TABLE trades
+DROP COLUMN IF EXISTS apt_usd_price,
+DROP COLUMN IF EXISTS aptos_amount_usd,
+DROP COLUMN IF EXISTS fee_usd;
+DROP TABLE IF EXISTS apt_usd_prices;
\ No newline at end of file
@@ -0,0 +1,22 @@
+-- Your SQL goes here
+-- APT/USD price on a minute grid, filled by the source in price_config
+CREATE TABLE
+    apt_usd_prices (
+        -- start of the minute, in unix seconds
+        minute_ts BIGINT NOT NULL PRIMARY KEY,
+        price NUMERIC NOT NULL,
+        -- time of the observation the price is from, a later one replaces it
+        price_ts BIGINT NOT NULL,
+        -- http, csv or pyth
+        source VARCHAR(10) NOT NULL
+    );
+
+-- USD values at the time of the trade, NULL until a price is known
+ALTER TABLE trades
+ADD COLUMN apt_usd_price NUMERIC,
+ADD COLUMN aptos_amount_usd NUMERIC,
+ADD COLUMN fee_usd NUMERIC;
+
+CREATE INDEX trades_unpriced_idx ON trades (txn_version)
+WHERE
+    apt_usd_price IS NULL;
@@ -0,0 +1,30 @@
+-- Your SQL goes here
+-- Periodic market snapshots of the tokens traded in the 24h before the snapshot, prices
+-- are per whole token and amounts in APT, USD values are NULL without a recent APT/USD price
+CREATE TABLE
+    token_metrics (
+        -- pre_addr of the token, as in trades.token_address
+        token_address VARCHAR(66) NOT NULL,
+        -- multiple of token_metrics_config.snapshot_interval_secs, in chain time
+        snapshot_ts BIGINT NOT NULL,
+        price_apt NUMERIC NOT NULL,
+        price_usd NUMERIC,
+        -- price times the supply sold through the curve, or the whole supply once graduated
+        market_cap_apt NUMERIC NOT NULL,
+        market_cap_usd NUMERIC,
+        -- price times the supply of mooner_money::Config
+        fdv_apt NUMERIC NOT NULL,
+        fdv_usd NUMERIC,
+        -- real aptos in the curve
+        liquidity_apt NUMERIC NOT NULL,
+        liquidity_usd NUMERIC,
+        volume_24h_apt NUMERIC NOT NULL,
+        volume_24h_usd NUMERIC,
+        -- in percent, from the price 24h before the snapshot or at launch for younger tokens
+        price_change_24h NUMERIC NOT NULL,
+        -- latest trade the snapshot is taken at
+        txn_version BIGINT NOT NULL,
+        PRIMARY KEY (token_address, snapshot_ts)
+    );
+
+CREATE INDEX token_metrics_snapshot_idx ON token_metrics (snapshot_ts);
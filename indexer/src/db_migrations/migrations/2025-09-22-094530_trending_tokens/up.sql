@@ -0,0 +1,23 @@
+-- Your SQL goes here
+-- Trending ranking of the tokens traded within trending_config.window_secs, replaced on
+-- every refresh. Activity components are decayed by the age of the trades.
+CREATE TABLE
+    trending_tokens (
+        -- 1 is the top
+        rank INT NOT NULL PRIMARY KEY,
+        -- pre_addr of the token, as in trades.token_address
+        token_address VARCHAR(66) NOT NULL,
+        -- weighted sum of the components, each scaled to the highest token's
+        score NUMERIC NOT NULL,
+        -- in APT
+        volume NUMERIC NOT NULL,
+        unique_buyers NUMERIC NOT NULL,
+        trade_count NUMERIC NOT NULL,
+        -- buyers whose first buy is in the window and still hold the token
+        holder_growth NUMERIC NOT NULL,
+        -- in percent, not decayed
+        curve_progress NUMERIC NOT NULL,
+        refreshed_at_ts BIGINT NOT NULL
+    );
+
+CREATE INDEX trending_tokens_token_idx ON trending_tokens (token_address);
@@ -0,0 +1,21 @@
+-- Your SQL goes here
+-- On-chain position of the author of a chat message in the chat's token, at the message
+-- timestamp. Written once per message after the indexer has processed that time.
+CREATE TABLE
+    chat_author_context (
+        -- chats.id
+        chat_id VARCHAR NOT NULL PRIMARY KEY,
+        -- chats.token_address and chats.address, standardized
+        token_address VARCHAR NOT NULL,
+        author_address VARCHAR NOT NULL,
+        -- chats.timestamp in unix seconds
+        message_ts BIGINT NOT NULL,
+        is_holder BOOLEAN NOT NULL,
+        -- in token base units, from the author's trades up to the message
+        balance NUMERIC NOT NULL,
+        is_creator BOOLEAN NOT NULL,
+        -- sold any of the token in the hour before the message
+        sold_last_hour BOOLEAN NOT NULL
+    );
+
+CREATE INDEX chat_author_context_token_author_idx ON chat_author_context (token_address, author_address);
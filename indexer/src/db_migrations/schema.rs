@@ -14,6 +14,47 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    address_labels (address, label, source) {
+        #[max_length = 66]
+        address -> Varchar,
+        #[max_length = 100]
+        label -> Varchar,
+        #[max_length = 50]
+        category -> Varchar,
+        #[max_length = 100]
+        cluster -> Nullable<Varchar>,
+        #[max_length = 10]
+        source -> Varchar,
+        #[max_length = 66]
+        token_address -> Nullable<Varchar>,
+        txn_version -> Nullable<Int8>,
+    }
+}
+
+diesel::table! {
+    apt_usd_prices (minute_ts) {
+        minute_ts -> Int8,
+        price -> Numeric,
+        price_ts -> Int8,
+        #[max_length = 10]
+        source -> Varchar,
+    }
+}
+
+diesel::table! {
+    chat_author_context (chat_id) {
+        chat_id -> Varchar,
+        token_address -> Varchar,
+        author_address -> Varchar,
+        message_ts -> Int8,
+        is_holder -> Bool,
+        balance -> Numeric,
+        is_creator -> Bool,
+        sold_last_hour -> Bool,
+    }
+}
+
 diesel::table! {
     chats (id) {
         id -> Varchar,
@@ -25,12 +66,138 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    contract_transactions (txn_version) {
+        txn_version -> Int8,
+        #[max_length = 66]
+        contract_address -> Varchar,
+        #[max_length = 66]
+        sender -> Varchar,
+        sequence_number -> Int8,
+        #[max_length = 255]
+        module_name -> Varchar,
+        #[max_length = 255]
+        function_name -> Varchar,
+        #[max_length = 600]
+        function -> Varchar,
+        type_arguments -> Jsonb,
+        arguments -> Jsonb,
+        decoded -> Bool,
+        success -> Bool,
+        vm_status -> Text,
+        gas_used -> Int8,
+        gas_unit_price -> Int8,
+        max_gas_amount -> Int8,
+        ts -> Int8,
+    }
+}
+
+diesel::table! {
+    creator_fundings (txn_version, recipient) {
+        txn_version -> Int8,
+        #[max_length = 66]
+        recipient -> Varchar,
+        #[max_length = 66]
+        creator -> Varchar,
+        amount -> Int8,
+        ts -> Int8,
+    }
+}
+
+diesel::table! {
+    creators (creator) {
+        #[max_length = 66]
+        creator -> Varchar,
+        tokens_launched -> Int4,
+        tokens_graduated -> Int4,
+        graduation_rate -> Numeric,
+        creator_fees -> Int8,
+        avg_holders -> Numeric,
+        early_sold_token_amount -> Numeric,
+        early_sell_percentage -> Numeric,
+        #[max_length = 20]
+        trust_badge -> Varchar,
+        first_launch_ts -> Int8,
+        last_launch_ts -> Int8,
+        updated_at_version -> Int8,
+    }
+}
+
+diesel::table! {
+    failed_transactions (txn_version) {
+        txn_version -> Int8,
+        #[max_length = 66]
+        contract_address -> Varchar,
+        #[max_length = 66]
+        sender -> Varchar,
+        #[max_length = 600]
+        function -> Varchar,
+        type_arguments -> Jsonb,
+        arguments -> Jsonb,
+        gas_used -> Int8,
+        vm_status -> Text,
+        #[max_length = 300]
+        abort_module -> Nullable<Varchar>,
+        abort_code -> Nullable<Int8>,
+        #[max_length = 300]
+        abort_name -> Nullable<Varchar>,
+        ts -> Int8,
+    }
+}
+
+diesel::table! {
+    graduation_signals (token_address, threshold) {
+        #[max_length = 66]
+        token_address -> Varchar,
+        threshold -> Int4,
+        curve_progress -> Numeric,
+        graduation_eta -> Nullable<Int8>,
+        txn_version -> Int8,
+        ts -> Int8,
+    }
+}
+
+diesel::table! {
+    leaderboards (board, time_window, rank) {
+        #[max_length = 50]
+        board -> Varchar,
+        #[max_length = 10]
+        time_window -> Varchar,
+        rank -> Int4,
+        #[max_length = 66]
+        address -> Varchar,
+        value -> Numeric,
+        refreshed_at_ts -> Int8,
+    }
+}
+
 diesel::table! {
     ledger_infos (chain_id) {
         chain_id -> Int8,
     }
 }
 
+diesel::table! {
+    module_abi_changes (module_addr, module_name, upgrade_number, item_kind, item_name) {
+        #[max_length = 300]
+        module_addr -> Varchar,
+        #[max_length = 300]
+        module_name -> Varchar,
+        upgrade_number -> Int8,
+        previous_upgrade_number -> Int8,
+        #[max_length = 16]
+        item_kind -> Varchar,
+        #[max_length = 600]
+        item_name -> Varchar,
+        #[max_length = 16]
+        change_type -> Varchar,
+        old_abi -> Nullable<Jsonb>,
+        new_abi -> Nullable<Jsonb>,
+        is_breaking -> Bool,
+        tx_version -> Int8,
+    }
+}
+
 diesel::table! {
     module_upgrade_history (module_addr, module_name, upgrade_number) {
         #[max_length = 300]
@@ -42,6 +209,37 @@ diesel::table! {
         module_source_code -> Text,
         module_abi -> Json,
         tx_version -> Int8,
+        module_source_text -> Nullable<Text>,
+        partial_reason -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    money_configs (contract_address, txn_version) {
+        #[max_length = 66]
+        contract_address -> Varchar,
+        txn_version -> Int8,
+        fee_bps -> Int8,
+        creator_fee_bps -> Int8,
+    }
+}
+
+diesel::table! {
+    package_dependencies (package_addr, package_name, upgrade_number, dependency_name) {
+        #[max_length = 300]
+        package_addr -> Varchar,
+        #[max_length = 300]
+        package_name -> Varchar,
+        upgrade_number -> Int8,
+        #[max_length = 300]
+        dependency_name -> Varchar,
+        #[max_length = 66]
+        dependency_addr -> Nullable<Varchar>,
+        git -> Nullable<Text>,
+        rev -> Nullable<Text>,
+        subdir -> Nullable<Text>,
+        local_path -> Nullable<Text>,
+        tx_version -> Int8,
     }
 }
 
@@ -56,6 +254,25 @@ diesel::table! {
         package_manifest -> Text,
         source_digest -> Text,
         tx_version -> Int8,
+        manifest_text -> Nullable<Text>,
+        #[max_length = 16]
+        deployment -> Varchar,
+    }
+}
+
+diesel::table! {
+    positions (user_addr, token_address) {
+        #[max_length = 66]
+        user_addr -> Varchar,
+        #[max_length = 66]
+        token_address -> Varchar,
+        balance -> Numeric,
+        cost_basis -> Numeric,
+        realized_pnl -> Numeric,
+        aptos_bought -> Numeric,
+        aptos_sold -> Numeric,
+        last_txn_version -> Int8,
+        volume -> Numeric,
     }
 }
 
@@ -69,6 +286,38 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    raw_contract_events (txn_version, kind, event_index) {
+        txn_version -> Int8,
+        #[max_length = 16]
+        kind -> Varchar,
+        event_index -> Int8,
+        #[max_length = 66]
+        contract_address -> Varchar,
+        #[max_length = 300]
+        module_name -> Varchar,
+        #[max_length = 300]
+        struct_name -> Varchar,
+        type_str -> Text,
+        #[max_length = 66]
+        account_address -> Nullable<Varchar>,
+        data -> Jsonb,
+        decoded -> Bool,
+    }
+}
+
+diesel::table! {
+    realized_pnl (txn_version) {
+        txn_version -> Int8,
+        #[max_length = 66]
+        user_addr -> Varchar,
+        #[max_length = 66]
+        token_address -> Varchar,
+        pnl -> Numeric,
+        ts -> Int8,
+    }
+}
+
 diesel::table! {
     stakings (position_addr) {
         #[max_length = 66]
@@ -82,6 +331,9 @@ diesel::table! {
         txn_version -> Int8,
         is_removed -> Bool,
         claimed -> Nullable<Int8>,
+        #[max_length = 66]
+        contract_address -> Varchar,
+        ts -> Nullable<Int8>,
     }
 }
 
@@ -97,6 +349,18 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    task_progress (task_id, address) {
+        task_id -> Int4,
+        #[max_length = 66]
+        address -> Varchar,
+        progress -> Numeric,
+        hold_started_at -> Nullable<Int8>,
+        completed_at_version -> Nullable<Int8>,
+        last_txn_version -> Int8,
+    }
+}
+
 diesel::table! {
     tasks (id) {
         id -> Int4,
@@ -133,6 +397,65 @@ diesel::table! {
         is_completed -> Bool,
         ts -> Int8,
         txn_version -> Int8,
+        #[max_length = 66]
+        contract_address -> Varchar,
+        block_height -> Nullable<Int8>,
+        creator_sold_24h -> Numeric,
+        curve_supply -> Nullable<Numeric>,
+        graduation_aptos_reserves -> Nullable<Numeric>,
+        curve_progress -> Numeric,
+        buy_velocity -> Numeric,
+        graduation_eta -> Nullable<Int8>,
+        curve_updated_version -> Nullable<Int8>,
+    }
+}
+
+diesel::table! {
+    token_metrics (token_address, snapshot_ts) {
+        #[max_length = 66]
+        token_address -> Varchar,
+        snapshot_ts -> Int8,
+        price_apt -> Numeric,
+        price_usd -> Nullable<Numeric>,
+        market_cap_apt -> Numeric,
+        market_cap_usd -> Nullable<Numeric>,
+        fdv_apt -> Numeric,
+        fdv_usd -> Nullable<Numeric>,
+        liquidity_apt -> Numeric,
+        liquidity_usd -> Nullable<Numeric>,
+        volume_24h_apt -> Numeric,
+        volume_24h_usd -> Nullable<Numeric>,
+        price_change_24h -> Numeric,
+        txn_version -> Int8,
+    }
+}
+
+diesel::table! {
+    token_risk_scores (token_address) {
+        #[max_length = 66]
+        token_address -> Varchar,
+        risk_score -> Int4,
+        sniper_trades -> Int4,
+        bundle_trades -> Int4,
+        round_trip_trades -> Int4,
+        wash_trades -> Int4,
+        flagged_volume -> Int8,
+        total_volume -> Int8,
+        updated_at_version -> Int8,
+    }
+}
+
+diesel::table! {
+    trade_flags (txn_version, flag) {
+        txn_version -> Int8,
+        #[max_length = 20]
+        flag -> Varchar,
+        #[max_length = 66]
+        token_address -> Varchar,
+        #[max_length = 66]
+        user_addr -> Varchar,
+        detail -> Jsonb,
+        ts -> Int8,
     }
 }
 
@@ -149,22 +472,99 @@ diesel::table! {
         virtual_aptos_reserves -> Numeric,
         virtual_token_reserves -> Numeric,
         ts -> Int8,
+        #[max_length = 66]
+        contract_address -> Varchar,
+        in_whitelist_window -> Nullable<Bool>,
+        block_height -> Nullable<Int8>,
+        apt_usd_price -> Nullable<Numeric>,
+        aptos_amount_usd -> Nullable<Numeric>,
+        fee_usd -> Nullable<Numeric>,
+        fee -> Nullable<Int8>,
+        creator_fee -> Nullable<Int8>,
+    }
+}
+
+diesel::table! {
+    trending_tokens (rank) {
+        rank -> Int4,
+        #[max_length = 66]
+        token_address -> Varchar,
+        score -> Numeric,
+        volume -> Numeric,
+        unique_buyers -> Numeric,
+        trade_count -> Numeric,
+        holder_growth -> Numeric,
+        curve_progress -> Numeric,
+        refreshed_at_ts -> Int8,
+    }
+}
+
+diesel::table! {
+    whitelist_windows (token_address) {
+        #[max_length = 66]
+        token_address -> Varchar,
+        #[max_length = 66]
+        pool_addr -> Varchar,
+        #[max_length = 66]
+        contract_address -> Varchar,
+        whitelist_duration -> Int8,
+        starts_at -> Int8,
+        ends_at -> Int8,
+        txn_version -> Int8,
+    }
+}
+
+diesel::table! {
+    xp_ledger (id) {
+        id -> Int8,
+        #[max_length = 66]
+        address -> Varchar,
+        delta -> Int4,
+        #[max_length = 50]
+        source_type -> Varchar,
+        #[max_length = 300]
+        source_ref -> Varchar,
+        txn_version -> Nullable<Int8>,
+        created_at -> Timestamp,
     }
 }
 
 diesel::joinable!(task_claims -> accounts (address));
 diesel::joinable!(task_claims -> tasks (task_id));
+diesel::joinable!(task_progress -> tasks (task_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
     accounts,
+    address_labels,
+    apt_usd_prices,
+    chat_author_context,
     chats,
+    contract_transactions,
+    creator_fundings,
+    creators,
+    failed_transactions,
+    graduation_signals,
+    leaderboards,
     ledger_infos,
+    module_abi_changes,
     module_upgrade_history,
+    money_configs,
+    package_dependencies,
     package_upgrade_history,
+    positions,
     processor_status,
+    raw_contract_events,
+    realized_pnl,
     stakings,
     task_claims,
+    task_progress,
     tasks,
+    token_metrics,
+    token_risk_scores,
     tokens,
+    trade_flags,
     trades,
+    trending_tokens,
+    whitelist_windows,
+    xp_ledger,
 );
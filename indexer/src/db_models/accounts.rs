@@ -16,7 +16,8 @@ pub struct Account {
 pub struct Spin {
     pub claimer: String,
     pub amount: i64,
-    pub win_type: i32
+    pub win_type: i32,
+    pub txn_version: i64
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -27,11 +28,12 @@ pub struct SpinEventOnChain {
 }
 
 impl SpinEventOnChain {
-    pub fn to_db_account(&self) -> Spin {
+    pub fn to_db_account(&self, txn_version: i64) -> Spin {
         Spin {
            claimer: standardize_address(&self.claimer),
            amount: self.amount.parse().unwrap(),
-           win_type: self.win_type
+           win_type: self.win_type,
+           txn_version
         }
     }
 }
@@ -0,0 +1,124 @@
+use anyhow::{Context, Result};
+use aptos_indexer_processor_sdk::utils::convert::standardize_address;
+use diesel::{AsChangeset, Insertable, Queryable};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::address_labels;
+
+pub const LABEL_SOURCE_FILE: &str = "file";
+pub const LABEL_SOURCE_AUTO: &str = "auto";
+
+pub const CATEGORY_FEE_WALLET: &str = "fee_wallet";
+pub const CATEGORY_CONFIG: &str = "config";
+pub const CATEGORY_POOL: &str = "pool";
+pub const CATEGORY_THALA_POOL: &str = "thala_pool";
+pub const CATEGORY_GAME: &str = "game";
+
+#[derive(AsChangeset, Clone, Debug, Deserialize, FieldCount, Insertable, Queryable, Serialize)]
+#[diesel(table_name = address_labels)]
+pub struct AddressLabel {
+    pub address: String,
+    pub label: String,
+    pub category: String,
+    pub cluster: Option<String>,
+    pub source: String,
+    pub token_address: Option<String>,
+    pub txn_version: Option<i64>,
+}
+
+impl AddressLabel {
+    /// Label of a protocol address derived from a transaction.
+    pub fn auto(
+        address: &str,
+        label: String,
+        category: &str,
+        token_address: Option<String>,
+        txn_version: i64,
+    ) -> Self {
+        Self {
+            address: standardize_address(address),
+            label,
+            category: category.to_string(),
+            cluster: None,
+            source: LABEL_SOURCE_AUTO.to_string(),
+            token_address,
+            txn_version: Some(txn_version),
+        }
+    }
+}
+
+/// Row of a label file, `address,label,category[,cluster]` in csv.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LabelFileEntry {
+    pub address: String,
+    pub label: String,
+    pub category: String,
+    #[serde(default)]
+    pub cluster: Option<String>,
+}
+
+impl LabelFileEntry {
+    /// Reads a yaml list of entries, or a csv with a header line when the file ends in `.csv`.
+    /// Empty lines and lines starting with `#` are skipped in csv files, fields may be quoted.
+    pub fn parse(path: &str, content: &str) -> Result<Vec<Self>> {
+        if !path.ends_with(".csv") {
+            return serde_yaml::from_str(content).context("Failed to parse label file");
+        }
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .comment(Some(b'#'))
+            .from_reader(content.as_bytes());
+        let headers = reader.headers().context("Failed to read label file header")?;
+        if !["address", "label", "category"]
+            .iter()
+            .all(|column| headers.iter().any(|header| header == *column))
+        {
+            anyhow::bail!("Label file header must have address, label and category columns");
+        }
+        reader
+            .deserialize::<Self>()
+            .map(|entry| {
+                let entry = entry.context("Failed to parse label file")?;
+                if entry.address.is_empty() || entry.label.is_empty() || entry.category.is_empty()
+                {
+                    anyhow::bail!("Missing address, label or category for {:?}", entry);
+                }
+                Ok(Self {
+                    cluster: entry.cluster.filter(|cluster| !cluster.is_empty()),
+                    ..entry
+                })
+            })
+            .collect()
+    }
+
+    pub fn to_db_address_label(&self) -> AddressLabel {
+        AddressLabel {
+            address: standardize_address(&self.address),
+            label: self.label.clone(),
+            category: self.category.clone(),
+            cluster: self.cluster.clone(),
+            source: LABEL_SOURCE_FILE.to_string(),
+            token_address: None,
+            txn_version: None,
+        }
+    }
+}
+
+/// Fee wallet part of the `mooner_money::Config` resource
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct FeeWalletConfigOnChain {
+    pub fee_wallet: String,
+}
+
+/// `0x1::object::ObjectCore`, for the owner of the pool objects
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ObjectCoreOnChain {
+    pub owner: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GameInitEventOnChain {
+    pub game: String,
+}
@@ -0,0 +1,210 @@
+use std::str::FromStr;
+
+use anyhow::{Context, Result};
+use aptos_indexer_processor_sdk::{
+    aptos_protos::transaction::v1::Event as EventPB, utils::convert::standardize_address,
+};
+use bigdecimal::{BigDecimal, Zero};
+use diesel::{AsChangeset, Insertable, Queryable};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::apt_usd_prices;
+
+pub const PRICE_SOURCE_HTTP: &str = "http";
+pub const PRICE_SOURCE_CSV: &str = "csv";
+pub const PRICE_SOURCE_PYTH: &str = "pyth";
+
+/// Start of the minute of a unix timestamp, in seconds
+pub fn minute_ts(ts: i64) -> i64 {
+    ts - ts.rem_euclid(60)
+}
+
+#[derive(AsChangeset, Clone, Debug, Deserialize, FieldCount, Insertable, Queryable, Serialize)]
+#[diesel(table_name = apt_usd_prices)]
+/// APT/USD price of a minute, from the latest observation in it
+pub struct AptUsdPrice {
+    pub minute_ts: i64,
+    pub price: BigDecimal,
+    pub price_ts: i64,
+    pub source: String,
+}
+
+impl AptUsdPrice {
+    pub fn new(price_ts: i64, price: BigDecimal, source: &str) -> Self {
+        Self {
+            minute_ts: minute_ts(price_ts),
+            price,
+            price_ts,
+            source: source.to_string(),
+        }
+    }
+
+    /// Keeps the latest observation of every minute, in minute order, so a batch never
+    /// upserts the same row twice.
+    pub fn latest_per_minute(mut prices: Vec<Self>) -> Vec<Self> {
+        prices.sort_by(|a, b| a.minute_ts.cmp(&b.minute_ts).then(b.price_ts.cmp(&a.price_ts)));
+        prices.dedup_by_key(|price| price.minute_ts);
+        prices
+    }
+
+    /// Prices of the configured feed in the `PriceFeedUpdate` events of the Pyth contract.
+    pub fn from_pyth_events(pyth_address: &str, price_id: &str, events: &[EventPB]) -> Vec<Self> {
+        let event_type = format!("{}::event::PriceFeedUpdate", standardize_address(pyth_address));
+        events
+            .iter()
+            .filter(|event| {
+                event
+                    .type_str
+                    .split_once("::")
+                    .map(|(address, rest)| {
+                        format!("{}::{}", standardize_address(address), rest) == event_type
+                    })
+                    .unwrap_or(false)
+            })
+            .filter_map(|event| serde_json::from_str::<PriceFeedUpdateOnChain>(&event.data).ok())
+            .filter(|update| {
+                update
+                    .price_feed
+                    .price_identifier
+                    .bytes
+                    .eq_ignore_ascii_case(price_id)
+            })
+            .filter_map(|update| update.price_feed.price.to_db_apt_usd_price())
+            .collect()
+    }
+}
+
+/// Row of a price file, `ts,price` in csv with `ts` in unix seconds.
+#[derive(Clone, Debug)]
+pub struct PriceFileEntry {
+    pub ts: i64,
+    pub price: BigDecimal,
+}
+
+impl PriceFileEntry {
+    /// Reads a csv with a header line, empty lines and lines starting with `#` are skipped.
+    pub fn parse(content: &str) -> Result<Vec<Self>> {
+        let mut lines = content
+            .lines()
+            .enumerate()
+            .map(|(idx, line)| (idx + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+        let header = match lines.next() {
+            Some((_, header)) => header.split(',').map(str::trim).collect::<Vec<_>>(),
+            None => return Ok(vec![]),
+        };
+        let column = |name: &str| header.iter().position(|column| *column == name);
+        let (Some(ts), Some(price)) = (column("ts"), column("price")) else {
+            anyhow::bail!("Price file header must have ts and price columns");
+        };
+        lines
+            .map(|(line_number, line)| {
+                let fields = line.split(',').map(str::trim).collect::<Vec<_>>();
+                let field = |idx: usize| {
+                    fields
+                        .get(idx)
+                        .copied()
+                        .filter(|field| !field.is_empty())
+                        .with_context(|| format!("Missing field on line {}", line_number))
+                };
+                Ok(Self {
+                    ts: field(ts)?
+                        .parse()
+                        .with_context(|| format!("Invalid ts on line {}", line_number))?,
+                    price: BigDecimal::from_str(field(price)?)
+                        .with_context(|| format!("Invalid price on line {}", line_number))?,
+                })
+            })
+            .collect()
+    }
+
+    pub fn to_db_apt_usd_price(&self) -> AptUsdPrice {
+        AptUsdPrice::new(self.ts, self.price.clone(), PRICE_SOURCE_CSV)
+    }
+}
+
+/// Move `i64` of the Pyth contract
+#[derive(Clone, Debug, Deserialize)]
+pub struct PythI64OnChain {
+    pub negative: bool,
+    pub magnitude: String,
+}
+
+impl PythI64OnChain {
+    fn to_string_signed(&self) -> String {
+        if self.negative {
+            format!("-{}", self.magnitude)
+        } else {
+            self.magnitude.clone()
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PythPriceOnChain {
+    pub price: PythI64OnChain,
+    pub expo: PythI64OnChain,
+    pub timestamp: String,
+}
+
+impl PythPriceOnChain {
+    /// `price * 10^expo`, skipped when not positive
+    pub fn to_db_apt_usd_price(&self) -> Option<AptUsdPrice> {
+        let price = BigDecimal::from_str(&format!(
+            "{}e{}",
+            self.price.to_string_signed(),
+            self.expo.to_string_signed()
+        ))
+        .ok()?;
+        if price <= BigDecimal::zero() {
+            return None;
+        }
+        Some(AptUsdPrice::new(
+            self.timestamp.parse().ok()?,
+            price,
+            PRICE_SOURCE_PYTH,
+        ))
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PythPriceIdentifierOnChain {
+    pub bytes: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct PythPriceFeedOnChain {
+    pub price_identifier: PythPriceIdentifierOnChain,
+    pub price: PythPriceOnChain,
+}
+
+/// `pyth::event::PriceFeedUpdate`
+#[derive(Clone, Debug, Deserialize)]
+pub struct PriceFeedUpdateOnChain {
+    pub price_feed: PythPriceFeedOnChain,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pyth_price_feed_update() {
+        let data = r#"{"price_feed":{"ema_price":{"conf":"1","expo":{"magnitude":"8","negative":true},"price":{"magnitude":"512000000","negative":false},"timestamp":"1726822861"},"price":{"conf":"1","expo":{"magnitude":"8","negative":true},"price":{"magnitude":"523456789","negative":false},"timestamp":"1726822861"},"price_identifier":{"bytes":"0x03ae"}},"timestamp":"1726822862"}"#;
+        let event = |type_str: &str| EventPB {
+            type_str: type_str.to_string(),
+            data: data.to_string(),
+            ..Default::default()
+        };
+        let prices = AptUsdPrice::from_pyth_events(
+            "0x7",
+            "0x03AE",
+            &[event("0x7::event::PriceFeedUpdate"), event("0x8::event::PriceFeedUpdate")],
+        );
+        assert_eq!(prices.len(), 1);
+        assert_eq!(prices[0].minute_ts, 1726822860);
+        assert_eq!(prices[0].price_ts, 1726822861);
+        assert_eq!(prices[0].price, BigDecimal::from_str("5.23456789").unwrap());
+    }
+}
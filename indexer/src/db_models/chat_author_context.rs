@@ -0,0 +1,88 @@
+use aptos_indexer_processor_sdk::utils::convert::standardize_address;
+use bigdecimal::BigDecimal;
+use diesel::{Insertable, Queryable};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::chat_author_context;
+
+/// A sell this long before a message marks its author as a recent seller
+pub const SELL_WINDOW_SECS: i64 = 3_600;
+
+#[derive(Clone, Debug, Deserialize, FieldCount, Insertable, Queryable, Serialize)]
+#[diesel(table_name = chat_author_context)]
+/// Position of the author of a chat message in the chat's token, at the message timestamp
+pub struct ChatAuthorContext {
+    pub chat_id: String,
+    pub token_address: String,
+    pub author_address: String,
+    pub message_ts: i64,
+    pub is_holder: bool,
+    pub balance: BigDecimal,
+    pub is_creator: bool,
+    pub sold_last_hour: bool,
+}
+
+/// Chat message written by the backend, addresses as sent by the client
+#[derive(Clone, Debug, Queryable)]
+pub struct ChatMessage {
+    pub id: String,
+    pub address: String,
+    pub token_address: String,
+    pub timestamp: chrono::NaiveDateTime,
+}
+
+/// Trade of a chat author on a chat's token
+#[derive(Clone, Debug, Queryable)]
+pub struct AuthorTrade {
+    pub token_address: String,
+    pub user_addr: String,
+    pub is_buy: bool,
+    pub token_amount: i64,
+    pub ts: i64,
+}
+
+impl ChatMessage {
+    pub fn author_address(&self) -> String {
+        standardize_address(&self.address)
+    }
+
+    pub fn chat_token_address(&self) -> String {
+        standardize_address(&self.token_address)
+    }
+
+    pub fn message_ts(&self) -> i64 {
+        self.timestamp.and_utc().timestamp()
+    }
+}
+
+impl ChatAuthorContext {
+    /// Replays the author's `trades` on the token up to the message, `created_by` is the
+    /// creator of the token if it is indexed.
+    pub fn new(chat: &ChatMessage, created_by: Option<&str>, trades: &[AuthorTrade]) -> Self {
+        let author_address = chat.author_address();
+        let message_ts = chat.message_ts();
+        let mut balance: i128 = 0;
+        let mut sold_last_hour = false;
+        for trade in trades.iter().filter(|trade| trade.ts <= message_ts) {
+            if trade.is_buy {
+                balance += trade.token_amount as i128;
+            } else {
+                balance -= trade.token_amount as i128;
+                sold_last_hour |= trade.ts > message_ts - SELL_WINDOW_SECS;
+            }
+        }
+        let balance = balance.max(0);
+        Self {
+            chat_id: chat.id.clone(),
+            token_address: chat.chat_token_address(),
+            is_creator: created_by
+                .is_some_and(|created_by| standardize_address(created_by) == author_address),
+            author_address,
+            message_ts,
+            is_holder: balance > 0,
+            balance: BigDecimal::from(balance),
+            sold_last_hour,
+        }
+    }
+}
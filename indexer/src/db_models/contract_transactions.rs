@@ -0,0 +1,102 @@
+use aptos_indexer_processor_sdk::{
+    aptos_protos::transaction::v1::{EntryFunctionPayload, TransactionInfo, UserTransactionRequest},
+    utils::convert::standardize_address,
+};
+use diesel::{AsChangeset, Insertable, Queryable};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::indexer_processor_config::ContractEntry,
+    db_models::failed_transactions::{entry_function_arguments, entry_function_id},
+    schema::contract_transactions,
+    utils::abi_decoder::AbiRegistry,
+};
+
+#[derive(AsChangeset, Clone, Debug, Deserialize, FieldCount, Insertable, Queryable, Serialize)]
+#[diesel(table_name = contract_transactions)]
+/// User transaction calling an entry function of the contract, successful or not
+pub struct ContractTransaction {
+    pub txn_version: i64,
+    pub contract_address: String,
+    pub sender: String,
+    pub sequence_number: i64,
+    pub module_name: String,
+    pub function_name: String,
+    pub function: String,
+    pub type_arguments: serde_json::Value,
+    pub arguments: serde_json::Value,
+    pub decoded: bool,
+    pub success: bool,
+    pub vm_status: String,
+    pub gas_used: i64,
+    pub gas_unit_price: i64,
+    pub max_gas_amount: i64,
+    pub ts: i64,
+}
+
+/// Call as extracted from the transaction, its arguments are decoded once the ABIs are loaded.
+#[derive(Clone, Debug)]
+pub struct ContractTransactionOnChain {
+    pub transaction: ContractTransaction,
+}
+
+impl ContractTransactionOnChain {
+    /// `None` when the call is not to the money or spin module of the contract.
+    pub fn new(
+        txn_version: i64,
+        ts: i64,
+        contract: &ContractEntry,
+        request: &UserTransactionRequest,
+        payload: &EntryFunctionPayload,
+        info: &TransactionInfo,
+    ) -> Option<Self> {
+        let function = payload.function.as_ref()?;
+        let module_name = function.module.as_ref()?.name.clone();
+        if module_name != contract.module_names.money && module_name != contract.module_names.spin
+        {
+            return None;
+        }
+        Some(Self {
+            transaction: ContractTransaction {
+                txn_version,
+                contract_address: contract.address.clone(),
+                sender: standardize_address(&request.sender),
+                sequence_number: request.sequence_number as i64,
+                module_name,
+                function_name: function.name.clone(),
+                function: entry_function_id(payload),
+                type_arguments: serde_json::to_value(&payload.type_arguments).unwrap_or_default(),
+                arguments: entry_function_arguments(payload),
+                decoded: false,
+                success: info.success,
+                vm_status: info.vm_status.clone(),
+                gas_used: info.gas_used as i64,
+                gas_unit_price: request.gas_unit_price as i64,
+                max_gas_amount: request.max_gas_amount as i64,
+                ts,
+            },
+        })
+    }
+
+    pub fn to_db_contract_transaction(&self, registry: &AbiRegistry) -> ContractTransaction {
+        let transaction = &self.transaction;
+        let decoded = match (&transaction.type_arguments, &transaction.arguments) {
+            (serde_json::Value::Array(type_arguments), serde_json::Value::Array(arguments)) => {
+                registry.decode_arguments(
+                    &transaction.contract_address,
+                    &transaction.module_name,
+                    &transaction.function_name,
+                    type_arguments,
+                    arguments,
+                )
+            }
+            _ => None,
+        };
+        ContractTransaction {
+            decoded: decoded.is_some(),
+            arguments: decoded.unwrap_or_else(|| transaction.arguments.clone()),
+            ..transaction.clone()
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use bigdecimal::BigDecimal;
+use diesel::{AsChangeset, Insertable, Queryable};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+use crate::{config::indexer_processor_config::CreatorConfig, schema::creators};
+
+pub const BADGE_TRUSTED: &str = "trusted";
+pub const BADGE_RISKY: &str = "risky";
+pub const BADGE_UNPROVEN: &str = "unproven";
+
+/// Sells of the creator this soon after the launch count as early sells
+pub const EARLY_SELL_SECS: i64 = 86_400;
+
+#[derive(AsChangeset, Clone, Debug, Deserialize, FieldCount, Insertable, Queryable, Serialize)]
+#[diesel(table_name = creators)]
+/// Launch history of a token creator, rolled up from its tokens, trades and holders
+pub struct Creator {
+    pub creator: String,
+    pub tokens_launched: i32,
+    pub tokens_graduated: i32,
+    pub graduation_rate: BigDecimal,
+    pub creator_fees: i64,
+    pub avg_holders: BigDecimal,
+    pub early_sold_token_amount: BigDecimal,
+    pub early_sell_percentage: BigDecimal,
+    pub trust_badge: String,
+    pub first_launch_ts: i64,
+    pub last_launch_ts: i64,
+    pub updated_at_version: i64,
+}
+
+impl Creator {
+    /// Risky on heavy early sells, trusted with enough graduated launches and few early
+    /// sells, unproven otherwise.
+    pub fn trust_badge(
+        config: &CreatorConfig,
+        tokens_launched: i32,
+        graduation_rate: &BigDecimal,
+        early_sell_percentage: &BigDecimal,
+    ) -> &'static str {
+        let risky_early_sell = BigDecimal::from(config.risky_early_sell_percentage);
+        let trusted_graduation = BigDecimal::from(config.trusted_min_graduation_rate);
+        let trusted_early_sell = BigDecimal::from(config.trusted_max_early_sell_percentage);
+        if *early_sell_percentage > risky_early_sell {
+            BADGE_RISKY
+        } else if tokens_launched >= config.trusted_min_tokens
+            && *graduation_rate >= trusted_graduation
+            && *early_sell_percentage <= trusted_early_sell
+        {
+            BADGE_TRUSTED
+        } else {
+            BADGE_UNPROVEN
+        }
+    }
+}
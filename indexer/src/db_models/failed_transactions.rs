@@ -0,0 +1,85 @@
+use aptos_indexer_processor_sdk::{
+    aptos_protos::transaction::v1::{EntryFunctionPayload, TransactionInfo, UserTransactionRequest},
+    utils::convert::standardize_address,
+};
+use diesel::{AsChangeset, Insertable, Queryable};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+use crate::{schema::failed_transactions, utils::move_errors::MoveAbort};
+
+#[derive(AsChangeset, Clone, Debug, Deserialize, FieldCount, Insertable, Queryable, Serialize)]
+#[diesel(table_name = failed_transactions)]
+/// User transaction calling the contract that failed
+pub struct FailedTransaction {
+    pub txn_version: i64,
+    pub contract_address: String,
+    pub sender: String,
+    pub function: String,
+    pub type_arguments: serde_json::Value,
+    pub arguments: serde_json::Value,
+    pub gas_used: i64,
+    pub vm_status: String,
+    pub abort_module: Option<String>,
+    pub abort_code: Option<i64>,
+    pub abort_name: Option<String>,
+    pub ts: i64,
+}
+
+/// Entry function id as `address::module::function`, with a standardized address.
+pub fn entry_function_id(payload: &EntryFunctionPayload) -> String {
+    match payload.function.as_ref() {
+        Some(function) => {
+            let (address, module) = function
+                .module
+                .as_ref()
+                .map(|module| (standardize_address(&module.address), module.name.as_str()))
+                .unwrap_or_default();
+            format!("{}::{}::{}", address, module, function.name)
+        }
+        None => payload.entry_function_id_str.clone(),
+    }
+}
+
+/// Arguments of the call, each one is sent as json.
+pub fn entry_function_arguments(payload: &EntryFunctionPayload) -> serde_json::Value {
+    serde_json::Value::Array(
+        payload
+            .arguments
+            .iter()
+            .map(|arg| {
+                serde_json::from_str(arg).unwrap_or_else(|_| serde_json::Value::String(arg.clone()))
+            })
+            .collect(),
+    )
+}
+
+impl FailedTransaction {
+    pub fn new(
+        txn_version: i64,
+        ts: i64,
+        contract_address: &str,
+        contract_addresses: &[String],
+        request: &UserTransactionRequest,
+        payload: &EntryFunctionPayload,
+        info: &TransactionInfo,
+    ) -> Self {
+        let abort = MoveAbort::parse(&info.vm_status, contract_addresses);
+        Self {
+            txn_version,
+            contract_address: contract_address.to_string(),
+            sender: standardize_address(&request.sender),
+            function: entry_function_id(payload),
+            type_arguments: serde_json::to_value(&payload.type_arguments).unwrap_or_default(),
+            arguments: entry_function_arguments(payload),
+            gas_used: info.gas_used as i64,
+            vm_status: info.vm_status.clone(),
+            abort_module: abort
+                .as_ref()
+                .map(|abort| format!("{}::{}", abort.module_address, abort.module_name)),
+            abort_code: abort.as_ref().map(|abort| abort.code as i64),
+            abort_name: abort.and_then(|abort| abort.name),
+            ts,
+        }
+    }
+}
@@ -0,0 +1,18 @@
+use bigdecimal::BigDecimal;
+use diesel::{AsChangeset, Insertable, Queryable};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::graduation_signals;
+
+#[derive(AsChangeset, Clone, Debug, Deserialize, FieldCount, Insertable, Queryable, Serialize)]
+#[diesel(table_name = graduation_signals)]
+/// First trade that took a token's curve progress past one of the near graduation thresholds
+pub struct GraduationSignal {
+    pub token_address: String,
+    pub threshold: i32,
+    pub curve_progress: BigDecimal,
+    pub graduation_eta: Option<i64>,
+    pub txn_version: i64,
+    pub ts: i64,
+}
@@ -0,0 +1,28 @@
+use bigdecimal::BigDecimal;
+use diesel::{Insertable, Queryable};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::leaderboards;
+
+pub const BOARD_TRADER_VOLUME: &str = "trader_volume";
+pub const BOARD_TRADER_PNL: &str = "trader_pnl";
+pub const BOARD_CREATOR_FEES: &str = "creator_fees";
+pub const BOARD_STAKER_AMOUNT: &str = "staker_amount";
+
+/// Rolling windows a leaderboard is ranked over, as (name, length in seconds).
+/// `None` ranks over all time.
+pub const LEADERBOARD_WINDOWS: [(&str, Option<i64>); 3] =
+    [("24h", Some(86_400)), ("7d", Some(604_800)), ("all", None)];
+
+#[derive(Clone, Debug, Deserialize, FieldCount, Insertable, Queryable, Serialize)]
+#[diesel(table_name = leaderboards)]
+/// One ranked row of a leaderboard, rank 1 is the top
+pub struct LeaderboardEntry {
+    pub board: String,
+    pub time_window: String,
+    pub rank: i32,
+    pub address: String,
+    pub value: BigDecimal,
+    pub refreshed_at_ts: i64,
+}
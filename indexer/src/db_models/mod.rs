@@ -5,4 +5,21 @@ pub mod processor_status;
 pub mod tokens;
 pub mod trades;
 pub mod stakings;
-pub mod accounts;
\ No newline at end of file
+pub mod accounts;
+pub mod leaderboards;
+pub mod positions;
+pub mod task_progress;
+pub mod xp_ledger;
+pub mod raw_contract_events;
+pub mod failed_transactions;
+pub mod contract_transactions;
+pub mod whitelist_windows;
+pub mod trade_flags;
+pub mod address_labels;
+pub mod creators;
+pub mod graduation_signals;
+pub mod apt_usd_prices;
+pub mod token_metrics;
+pub mod trending_tokens;
+pub mod chat_author_context;
+pub mod money_configs;
@@ -1,8 +1,12 @@
+use aptos_indexer_processor_sdk::aptos_protos::transaction::v1::MoveModuleBytecode;
 use diesel::{AsChangeset, Insertable};
 use field_count::FieldCount;
 use serde::{Deserialize, Serialize};
 
-use crate::schema::module_upgrade_history;
+use crate::{
+    schema::{module_abi_changes, module_upgrade_history},
+    utils::{abi_diff::AbiChange, package_metadata::decode_gzip_hex},
+};
 
 #[derive(AsChangeset, Clone, Debug, Deserialize, FieldCount, Insertable, Serialize)]
 #[diesel(table_name = module_upgrade_history)]
@@ -15,4 +19,77 @@ pub struct ModuleUpgrade {
     pub module_source_code: String,
     pub module_abi: serde_json::Value,
     pub tx_version: i64,
+    // Decoded module_source_code, none when the deployer hid the source
+    pub module_source_text: Option<String>,
+    // Set when the write set and the PackageRegistry disagree, bytecode or abi may be empty
+    pub partial_reason: Option<String>,
+}
+
+impl ModuleUpgrade {
+    pub fn new(
+        module_addr: &str,
+        module_name: &str,
+        upgrade_number: i64,
+        raw_module: Option<&MoveModuleBytecode>,
+        source: &str,
+        tx_version: i64,
+        partial_reason: Option<&str>,
+    ) -> Self {
+        let abi = raw_module.and_then(|raw_module| raw_module.abi.as_ref());
+        let partial_reason = match (partial_reason, abi) {
+            (Some(reason), _) => Some(reason.to_string()),
+            (None, None) => Some("module abi is missing".to_string()),
+            (None, Some(_)) => None,
+        };
+        Self {
+            module_addr: module_addr.to_string(),
+            module_name: module_name.to_string(),
+            upgrade_number,
+            module_bytecode: raw_module
+                .map(|raw_module| raw_module.bytecode.clone())
+                .unwrap_or_default(),
+            module_source_code: source.to_string(),
+            module_abi: abi
+                .map(|abi| serde_json::json!(abi))
+                .unwrap_or(serde_json::Value::Null),
+            tx_version,
+            module_source_text: decode_gzip_hex(source),
+            partial_reason,
+        }
+    }
+}
+
+#[derive(AsChangeset, Clone, Debug, Deserialize, FieldCount, Insertable, Serialize)]
+#[diesel(table_name = module_abi_changes)]
+/// Difference between a module ABI and the one of its previous upgrade
+pub struct ModuleAbiChange {
+    pub module_addr: String,
+    pub module_name: String,
+    pub upgrade_number: i64,
+    pub previous_upgrade_number: i64,
+    pub item_kind: String,
+    pub item_name: String,
+    pub change_type: String,
+    pub old_abi: Option<serde_json::Value>,
+    pub new_abi: Option<serde_json::Value>,
+    pub is_breaking: bool,
+    pub tx_version: i64,
+}
+
+impl ModuleAbiChange {
+    pub fn new(upgrade: &ModuleUpgrade, previous_upgrade_number: i64, change: AbiChange) -> Self {
+        Self {
+            module_addr: upgrade.module_addr.clone(),
+            module_name: upgrade.module_name.clone(),
+            upgrade_number: upgrade.upgrade_number,
+            previous_upgrade_number,
+            item_kind: change.item_kind.to_string(),
+            item_name: change.item_name,
+            change_type: change.change_type.to_string(),
+            old_abi: change.old_abi,
+            new_abi: change.new_abi,
+            is_breaking: change.is_breaking,
+            tx_version: upgrade.tx_version,
+        }
+    }
 }
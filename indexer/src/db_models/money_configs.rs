@@ -0,0 +1,32 @@
+use diesel::{AsChangeset, Insertable, Queryable};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+use crate::{config::indexer_processor_config::FeeConfig, schema::money_configs};
+
+#[derive(AsChangeset, Clone, Debug, Deserialize, FieldCount, Insertable, Queryable, Serialize)]
+#[diesel(table_name = money_configs)]
+/// Fees of the money module's `Config` as written at a version, trades pay the fees of the
+/// latest write before them
+pub struct MoneyConfig {
+    pub contract_address: String,
+    pub txn_version: i64,
+    pub fee_bps: i64,
+    pub creator_fee_bps: i64,
+}
+
+impl MoneyConfig {
+    pub fn fee_config(&self) -> FeeConfig {
+        FeeConfig {
+            fee_bps: self.fee_bps,
+            creator_fee_bps: self.creator_fee_bps,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+/// Fee part of the `mooner_money::Config` resource
+pub struct FeeConfigOnChain {
+    pub fee: u8,
+    pub creator_fee: String,
+}
@@ -1,8 +1,12 @@
+use aptos_indexer_processor_sdk::utils::convert::standardize_address;
 use diesel::{AsChangeset, Insertable};
 use field_count::FieldCount;
 use serde::{Deserialize, Serialize};
 
-use crate::schema::package_upgrade_history;
+use crate::{
+    schema::{package_dependencies, package_upgrade_history},
+    utils::package_metadata::{decode_gzip_hex, parse_manifest_dependencies},
+};
 
 #[derive(AsChangeset, Clone, Debug, Deserialize, FieldCount, Insertable, Serialize)]
 #[diesel(table_name = package_upgrade_history)]
@@ -15,6 +19,31 @@ pub struct PackageUpgrade {
     pub package_manifest: String,
     pub source_digest: String,
     pub tx_version: i64,
+    // Move.toml decoded from package_manifest
+    pub manifest_text: Option<String>,
+    // DEPLOYMENT_ACCOUNT or DEPLOYMENT_OBJECT
+    pub deployment: String,
+}
+
+/// Package published to an account with `0x1::code`
+pub const DEPLOYMENT_ACCOUNT: &str = "account";
+/// Package published to a code object with `0x1::object_code_deployment`
+pub const DEPLOYMENT_OBJECT: &str = "object";
+
+#[derive(AsChangeset, Clone, Debug, Deserialize, FieldCount, Insertable, Serialize)]
+#[diesel(table_name = package_dependencies)]
+/// Dependency declared by a package upgrade
+pub struct PackageDependency {
+    pub package_addr: String,
+    pub package_name: String,
+    pub upgrade_number: i64,
+    pub dependency_name: String,
+    pub dependency_addr: Option<String>,
+    pub git: Option<String>,
+    pub rev: Option<String>,
+    pub subdir: Option<String>,
+    pub local_path: Option<String>,
+    pub tx_version: i64,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -29,8 +58,16 @@ pub struct UpgradePolicyOnChain {
     pub policy: i64,
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct PackageDepOnChain {
+    pub account: String,
+    pub package_name: String,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct PackageOnChain {
+    #[serde(default)]
+    pub deps: Vec<PackageDepOnChain>,
     pub manifest: String,
     pub modules: Vec<ModuleOnChain>,
     pub name: String,
@@ -39,6 +76,68 @@ pub struct PackageOnChain {
     pub upgrade_policy: UpgradePolicyOnChain,
 }
 
+impl PackageOnChain {
+    pub fn parsed_upgrade_number(&self) -> i64 {
+        self.upgrade_number.parse().unwrap_or_else(|_| {
+            tracing::warn!(
+                "Invalid upgrade_number {} of package {}",
+                self.upgrade_number,
+                self.name
+            );
+            0
+        })
+    }
+
+    /// Dependencies from the on-chain deps, with their source from the manifest when it
+    /// declares them, followed by the ones only the manifest declares.
+    pub fn to_db_package_dependencies(
+        &self,
+        tx_version: i64,
+        package_addr: &str,
+        manifest_text: Option<&str>,
+    ) -> Vec<PackageDependency> {
+        let upgrade_number = self.parsed_upgrade_number();
+        let mut declared = manifest_text
+            .map(parse_manifest_dependencies)
+            .unwrap_or_default();
+        let mut dependencies = vec![];
+        for dep in self.deps.iter() {
+            let source = declared
+                .iter()
+                .position(|d| d.name == dep.package_name)
+                .map(|idx| declared.remove(idx))
+                .unwrap_or_default();
+            dependencies.push(PackageDependency {
+                package_addr: package_addr.to_string(),
+                package_name: self.name.clone(),
+                upgrade_number,
+                dependency_name: dep.package_name.clone(),
+                dependency_addr: Some(standardize_address(&dep.account)),
+                git: source.git,
+                rev: source.rev,
+                subdir: source.subdir,
+                local_path: source.local,
+                tx_version,
+            });
+        }
+        for source in declared {
+            dependencies.push(PackageDependency {
+                package_addr: package_addr.to_string(),
+                package_name: self.name.clone(),
+                upgrade_number,
+                dependency_name: source.name,
+                dependency_addr: source.aptos.as_deref().map(standardize_address),
+                git: source.git,
+                rev: source.rev,
+                subdir: source.subdir,
+                local_path: source.local,
+                tx_version,
+            });
+        }
+        dependencies
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 /// On-chain representation of a write module change
 pub struct PackageUpgradeChangeOnChain {
@@ -50,17 +149,37 @@ impl PackageUpgradeChangeOnChain {
         &self,
         tx_version: i64,
         package_addr: String,
+        deployment: &str,
     ) -> Vec<PackageUpgrade> {
         self.packages
             .iter()
             .map(|package| PackageUpgrade {
                 package_addr: package_addr.clone(),
                 package_name: package.name.clone(),
-                upgrade_number: package.upgrade_number.parse().unwrap(),
+                upgrade_number: package.parsed_upgrade_number(),
                 upgrade_policy: package.upgrade_policy.policy,
                 package_manifest: package.manifest.clone(),
                 source_digest: package.source_digest.clone(),
                 tx_version,
+                manifest_text: decode_gzip_hex(&package.manifest),
+                deployment: deployment.to_string(),
+            })
+            .collect()
+    }
+
+    pub fn to_db_package_dependencies(
+        &self,
+        tx_version: i64,
+        package_addr: &str,
+    ) -> Vec<PackageDependency> {
+        self.packages
+            .iter()
+            .flat_map(|package| {
+                package.to_db_package_dependencies(
+                    tx_version,
+                    package_addr,
+                    decode_gzip_hex(&package.manifest).as_deref(),
+                )
             })
             .collect()
     }
@@ -0,0 +1,108 @@
+use bigdecimal::{BigDecimal, ToPrimitive, Zero};
+use diesel::{AsChangeset, Insertable, Queryable};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::indexer_processor_config::FeeConfig,
+    db_models::trades::Trade,
+    schema::{positions, realized_pnl},
+    utils::{fees::net_aptos_amount, pnl::CostBasisPosition},
+};
+
+#[derive(AsChangeset, Clone, Debug, Deserialize, FieldCount, Insertable, Queryable, Serialize)]
+#[diesel(table_name = positions)]
+/// Average cost basis position of a user in a token, amounts in octas net of fees
+pub struct Position {
+    pub user_addr: String,
+    pub token_address: String,
+    pub balance: BigDecimal,
+    pub cost_basis: BigDecimal,
+    pub realized_pnl: BigDecimal,
+    pub aptos_bought: BigDecimal,
+    pub aptos_sold: BigDecimal,
+    pub last_txn_version: i64,
+    // Gross aptos traded
+    pub volume: BigDecimal,
+}
+
+#[derive(Clone, Debug, Deserialize, FieldCount, Insertable, Queryable, Serialize)]
+#[diesel(table_name = realized_pnl)]
+/// PnL realized by a sell, in octas net of fees
+pub struct RealizedPnl {
+    pub txn_version: i64,
+    pub user_addr: String,
+    pub token_address: String,
+    pub pnl: BigDecimal,
+    pub ts: i64,
+}
+
+fn to_i128(value: &BigDecimal) -> i128 {
+    value.to_i128().unwrap_or(0)
+}
+
+impl Position {
+    pub fn new(user_addr: String, token_address: String) -> Self {
+        Self {
+            user_addr,
+            token_address,
+            balance: BigDecimal::from(0),
+            cost_basis: BigDecimal::from(0),
+            realized_pnl: BigDecimal::from(0),
+            aptos_bought: BigDecimal::from(0),
+            aptos_sold: BigDecimal::from(0),
+            last_txn_version: -1,
+            volume: BigDecimal::from(0),
+        }
+    }
+
+    /// Applies a trade of this user on this token and returns the pnl realized by a sell.
+    /// Trades at or before `last_txn_version` were already applied and are skipped.
+    pub fn apply_trade(&mut self, fee_config: &FeeConfig, trade: &Trade) -> Option<RealizedPnl> {
+        if trade.txn_version <= self.last_txn_version {
+            return None;
+        }
+        // Sells net of the fee stamped at their version, `fee_config` for trades stored before
+        // the Config was tracked
+        let net_aptos = match trade.fee {
+            Some(fee) if !trade.is_buy => trade.aptos_amount - fee,
+            _ => net_aptos_amount(fee_config, trade.is_buy, trade.aptos_amount),
+        };
+        let mut position = CostBasisPosition {
+            balance: to_i128(&self.balance),
+            cost_basis: to_i128(&self.cost_basis),
+            realized_pnl: to_i128(&self.realized_pnl),
+        };
+        let realized = position.apply_trade(trade.is_buy, net_aptos, trade.token_amount);
+        self.balance = BigDecimal::from(position.balance);
+        self.cost_basis = BigDecimal::from(position.cost_basis);
+        self.realized_pnl = BigDecimal::from(position.realized_pnl);
+        if trade.is_buy {
+            self.aptos_bought += BigDecimal::from(net_aptos);
+        } else {
+            self.aptos_sold += BigDecimal::from(net_aptos);
+        }
+        self.volume += BigDecimal::from(trade.aptos_amount);
+        self.last_txn_version = trade.txn_version;
+        (!trade.is_buy).then(|| RealizedPnl {
+            txn_version: trade.txn_version,
+            user_addr: trade.user_addr.clone(),
+            token_address: trade.token_address.clone(),
+            pnl: BigDecimal::from(realized),
+            ts: trade.ts,
+        })
+    }
+
+    /// Unrealized pnl of the held balance, marked at the price of the given pool reserves.
+    pub fn unrealized_pnl(
+        &self,
+        virtual_aptos_reserves: &BigDecimal,
+        virtual_token_reserves: &BigDecimal,
+    ) -> BigDecimal {
+        if virtual_token_reserves.is_zero() {
+            return BigDecimal::from(0);
+        }
+        let market_value = &self.balance * virtual_aptos_reserves / virtual_token_reserves;
+        market_value.with_scale(0) - &self.cost_basis
+    }
+}
@@ -0,0 +1,58 @@
+use diesel::{AsChangeset, Insertable, Queryable};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+use crate::{schema::raw_contract_events, utils::abi_decoder::AbiRegistry};
+
+pub const RAW_KIND_EVENT: &str = "event";
+pub const RAW_KIND_RESOURCE: &str = "resource";
+
+#[derive(AsChangeset, Clone, Debug, Deserialize, FieldCount, Insertable, Queryable, Serialize)]
+#[diesel(table_name = raw_contract_events)]
+/// Any event or resource of an indexed contract, decoded with the latest module ABI
+pub struct RawContractEvent {
+    pub txn_version: i64,
+    pub kind: String,
+    pub event_index: i64,
+    pub contract_address: String,
+    pub module_name: String,
+    pub struct_name: String,
+    pub type_str: String,
+    pub account_address: Option<String>,
+    pub data: serde_json::Value,
+    pub decoded: bool,
+}
+
+/// Event or resource as extracted from the transaction, decoded once the ABIs are loaded.
+#[derive(Clone, Debug)]
+pub struct RawContractEventOnChain {
+    pub txn_version: i64,
+    pub kind: &'static str,
+    pub event_index: i64,
+    pub contract_address: String,
+    pub module_name: String,
+    pub struct_name: String,
+    pub type_str: String,
+    pub account_address: Option<String>,
+    pub data: String,
+}
+
+impl RawContractEventOnChain {
+    pub fn to_db_raw_event(&self, registry: &AbiRegistry) -> RawContractEvent {
+        let raw = serde_json::from_str::<serde_json::Value>(&self.data)
+            .unwrap_or_else(|_| serde_json::Value::String(self.data.clone()));
+        let decoded = registry.decode(&self.type_str, &raw);
+        RawContractEvent {
+            txn_version: self.txn_version,
+            kind: self.kind.to_string(),
+            event_index: self.event_index,
+            contract_address: self.contract_address.clone(),
+            module_name: self.module_name.clone(),
+            struct_name: self.struct_name.clone(),
+            type_str: self.type_str.clone(),
+            account_address: self.account_address.clone(),
+            decoded: decoded.is_some(),
+            data: decoded.unwrap_or(raw),
+        }
+    }
+}
@@ -15,6 +15,9 @@ pub struct Staking {
     pub txn_version: i64,
     pub is_removed: bool,
     pub claimed: i64,
+    pub contract_address: String,
+    // Set by the extractor from the transaction timestamp
+    pub ts: Option<i64>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -27,7 +30,7 @@ pub struct PositionCreatedOnChain {
 }
 
 impl PositionCreatedOnChain {
-    pub fn to_db_staking(&self, txn_version: i64) -> Staking {
+    pub fn to_db_staking(&self, txn_version: i64, contract_address: &str) -> Staking {
         Staking {
             position_addr: standardize_address(&self.position_addr),
             user: standardize_address(&self.user),
@@ -37,6 +40,8 @@ impl PositionCreatedOnChain {
             claimed: 0,
             stake_addr: standardize_address(&self.stake_addr),
             txn_version,
+            contract_address: contract_address.to_string(),
+            ts: None,
         }
     }
 }
@@ -0,0 +1,188 @@
+use aptos_indexer_processor_sdk::utils::convert::standardize_address;
+use bigdecimal::BigDecimal;
+use diesel::{AsChangeset, Insertable, Queryable};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::schema::task_progress;
+
+#[derive(AsChangeset, Clone, Debug, Deserialize, FieldCount, Insertable, Queryable, Serialize)]
+#[diesel(table_name = task_progress)]
+/// Progress of an address towards a task, in the unit of the task requirement
+pub struct TaskProgress {
+    pub task_id: i32,
+    pub address: String,
+    pub progress: BigDecimal,
+    pub hold_started_at: Option<i64>,
+    pub completed_at_version: Option<i64>,
+    pub last_txn_version: i64,
+}
+
+impl TaskProgress {
+    pub fn new(task_id: i32, address: String) -> Self {
+        Self {
+            task_id,
+            address,
+            progress: BigDecimal::from(0),
+            hold_started_at: None,
+            completed_at_version: None,
+            last_txn_version: -1,
+        }
+    }
+}
+
+/// Which side of a trade counts towards a trade requirement.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TradeSide {
+    Buy,
+    Sell,
+    Any,
+}
+
+/// Which amount of a trade counts towards a trade requirement. `Apt` sums the aptos
+/// amount of every trade, `Token` sums the token amount of trades on that token only.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TaskToken {
+    Apt,
+    Token(String),
+}
+
+/// Parsed form of the `tasks.requirement` JSONB column.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TaskRequirement {
+    Trade {
+        side: TradeSide,
+        token: TaskToken,
+        amount: BigDecimal,
+    },
+    CreateToken {
+        count: BigDecimal,
+    },
+    Stake {
+        amount: BigDecimal,
+    },
+    Spin {
+        count: BigDecimal,
+    },
+    Hold {
+        token_address: String,
+        amount: BigDecimal,
+        duration: i64,
+    },
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct TaskRequirementSpec {
+    #[serde(alias = "action_type")]
+    action: String,
+    amount: Option<Value>,
+    count: Option<Value>,
+    token: Option<String>,
+    token_address: Option<String>,
+    duration: Option<Value>,
+}
+
+fn parse_number(value: &Option<Value>) -> Option<BigDecimal> {
+    match value {
+        Some(Value::Number(n)) => n.to_string().parse().ok(),
+        Some(Value::String(s)) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+impl TaskRequirement {
+    /// Parses a requirement. Returns None for requirements that can't be evaluated from
+    /// on-chain data, such as `connect-wallet` or `connect-twitter`.
+    pub fn from_json(requirement: &Value) -> Option<Self> {
+        let spec: TaskRequirementSpec = serde_json::from_value(requirement.clone()).ok()?;
+        let trade_side = match spec.action.as_str() {
+            "buy" => Some(TradeSide::Buy),
+            "sell" => Some(TradeSide::Sell),
+            "volume" => Some(TradeSide::Any),
+            _ => None,
+        };
+        if let Some(side) = trade_side {
+            let token = match (spec.token.as_deref(), spec.token_address.as_ref()) {
+                (Some("APT"), _) | (None, None) => TaskToken::Apt,
+                (_, Some(token_address)) => TaskToken::Token(standardize_address(token_address)),
+                (Some(_), None) => return None,
+            };
+            return Some(TaskRequirement::Trade {
+                side,
+                token,
+                amount: parse_number(&spec.amount)?,
+            });
+        }
+        match spec.action.as_str() {
+            "create_token" | "create" => Some(TaskRequirement::CreateToken {
+                count: parse_number(&spec.count)
+                    .or_else(|| parse_number(&spec.amount))
+                    .unwrap_or_else(|| BigDecimal::from(1)),
+            }),
+            "stake" => Some(TaskRequirement::Stake {
+                amount: parse_number(&spec.amount)?,
+            }),
+            "spin" => Some(TaskRequirement::Spin {
+                count: parse_number(&spec.count)
+                    .or_else(|| parse_number(&spec.amount))
+                    .unwrap_or_else(|| BigDecimal::from(1)),
+            }),
+            "hold" => Some(TaskRequirement::Hold {
+                token_address: standardize_address(spec.token_address.as_ref()?),
+                amount: parse_number(&spec.amount)?,
+                duration: match spec.duration {
+                    Some(Value::Number(n)) => n.as_i64()?,
+                    Some(Value::String(s)) => s.parse().ok()?,
+                    _ => return None,
+                },
+            }),
+            _ => None,
+        }
+    }
+
+    /// The progress value at which the task is complete.
+    pub fn target(&self) -> BigDecimal {
+        match self {
+            TaskRequirement::Trade { amount, .. } => amount.clone(),
+            TaskRequirement::CreateToken { count } => count.clone(),
+            TaskRequirement::Stake { amount } => amount.clone(),
+            TaskRequirement::Spin { count } => count.clone(),
+            TaskRequirement::Hold { duration, .. } => BigDecimal::from(*duration),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_task_requirements() {
+        assert_eq!(
+            TaskRequirement::from_json(&json!({ "action": "buy", "amount": 250, "token": "APT" })),
+            Some(TaskRequirement::Trade {
+                side: TradeSide::Buy,
+                token: TaskToken::Apt,
+                amount: BigDecimal::from(250),
+            })
+        );
+        assert_eq!(
+            TaskRequirement::from_json(
+                &json!({ "action_type": "volume", "amount": "1000", "token": "MOON", "token_address": "0x1" })
+            ),
+            Some(TaskRequirement::Trade {
+                side: TradeSide::Any,
+                token: TaskToken::Token(standardize_address("0x1")),
+                amount: BigDecimal::from(1000),
+            })
+        );
+        assert_eq!(
+            TaskRequirement::from_json(&json!({ "action": "hold", "amount": 5, "token_address": "0x1", "duration": 3600 }))
+                .map(|r| r.target()),
+            Some(BigDecimal::from(3600))
+        );
+        assert_eq!(TaskRequirement::from_json(&json!({ "action_type": "connect-twitter" })), None);
+    }
+}
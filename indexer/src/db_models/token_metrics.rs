@@ -0,0 +1,135 @@
+use std::str::FromStr;
+
+use bigdecimal::{BigDecimal, Zero};
+use diesel::{AsChangeset, Insertable, Queryable};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::token_metrics;
+
+/// Trades of this window make the volume and price change of a snapshot
+pub const METRICS_WINDOW_SECS: i64 = 86_400;
+
+#[derive(AsChangeset, Clone, Debug, Deserialize, FieldCount, Insertable, Queryable, Serialize)]
+#[diesel(table_name = token_metrics)]
+/// Market snapshot of a token, prices are per whole token and amounts in APT
+pub struct TokenMetrics {
+    pub token_address: String,
+    pub snapshot_ts: i64,
+    pub price_apt: BigDecimal,
+    pub price_usd: Option<BigDecimal>,
+    pub market_cap_apt: BigDecimal,
+    pub market_cap_usd: Option<BigDecimal>,
+    pub fdv_apt: BigDecimal,
+    pub fdv_usd: Option<BigDecimal>,
+    pub liquidity_apt: BigDecimal,
+    pub liquidity_usd: Option<BigDecimal>,
+    pub volume_24h_apt: BigDecimal,
+    pub volume_24h_usd: Option<BigDecimal>,
+    pub price_change_24h: BigDecimal,
+    pub txn_version: i64,
+}
+
+/// Token columns the snapshots are computed from, the reserves are the ones the pool was
+/// created with so the token reserves are the supply of `mooner_money::Config`
+#[derive(Clone, Debug, Queryable)]
+pub struct MetricsToken {
+    pub pre_addr: String,
+    pub decimals: i16,
+    pub virtual_aptos_reserves: BigDecimal,
+    pub virtual_token_reserves: BigDecimal,
+    pub is_completed: bool,
+    pub txn_version: i64,
+}
+
+impl MetricsToken {
+    /// Reserves of the pool before its first trade
+    pub fn launch_reserves(&self) -> PoolReserves {
+        PoolReserves {
+            token_address: self.pre_addr.clone(),
+            txn_version: self.txn_version,
+            virtual_aptos_reserves: self.virtual_aptos_reserves.clone(),
+            virtual_token_reserves: self.virtual_token_reserves.clone(),
+        }
+    }
+}
+
+/// Virtual reserves of a pool after a trade, in octas and token base units
+#[derive(Clone, Debug, Queryable)]
+pub struct PoolReserves {
+    pub token_address: String,
+    pub txn_version: i64,
+    pub virtual_aptos_reserves: BigDecimal,
+    pub virtual_token_reserves: BigDecimal,
+}
+
+impl TokenMetrics {
+    /// Snapshot of a token at its latest reserves, `reserves_24h_ago` defaults to the
+    /// launch reserves. `volume_24h` is in octas, `apt_usd_price` prices the USD values.
+    pub fn snapshot(
+        token: &MetricsToken,
+        snapshot_ts: i64,
+        reserves: &PoolReserves,
+        reserves_24h_ago: Option<&PoolReserves>,
+        volume_24h: &BigDecimal,
+        volume_24h_usd: Option<BigDecimal>,
+        apt_usd_price: Option<&BigDecimal>,
+    ) -> Option<Self> {
+        let octas = BigDecimal::from(100_000_000);
+        let unit = BigDecimal::from_str(&format!("1e{}", token.decimals)).ok()?;
+        let price = |aptos_reserves: &BigDecimal, token_reserves: &BigDecimal| {
+            if token_reserves.is_zero() {
+                None
+            } else {
+                Some(aptos_reserves * &unit / (token_reserves * &octas))
+            }
+        };
+        let price_apt = price(
+            &reserves.virtual_aptos_reserves,
+            &reserves.virtual_token_reserves,
+        )?;
+        let price_24h_ago = match reserves_24h_ago {
+            Some(reserves) => price(
+                &reserves.virtual_aptos_reserves,
+                &reserves.virtual_token_reserves,
+            ),
+            None => price(&token.virtual_aptos_reserves, &token.virtual_token_reserves),
+        }?;
+
+        let supply = &token.virtual_token_reserves / &unit;
+        let circulating_supply = if token.is_completed {
+            supply.clone()
+        } else {
+            ((&token.virtual_token_reserves - &reserves.virtual_token_reserves) / &unit)
+                .max(BigDecimal::zero())
+        };
+        let liquidity_apt = ((&reserves.virtual_aptos_reserves - &token.virtual_aptos_reserves)
+            / &octas)
+            .max(BigDecimal::zero());
+        let price_change_24h = if price_24h_ago.is_zero() {
+            BigDecimal::zero()
+        } else {
+            (&price_apt - &price_24h_ago) * BigDecimal::from(100) / &price_24h_ago
+        };
+
+        let market_cap_apt = (&price_apt * circulating_supply).round(8);
+        let fdv_apt = (&price_apt * supply).round(8);
+        let usd = |apt: &BigDecimal| apt_usd_price.map(|price| (apt * price).round(8));
+        Some(Self {
+            token_address: token.pre_addr.clone(),
+            snapshot_ts,
+            price_usd: apt_usd_price.map(|price| (&price_apt * price).round(18)),
+            price_apt: price_apt.round(18),
+            market_cap_usd: usd(&market_cap_apt),
+            market_cap_apt,
+            fdv_usd: usd(&fdv_apt),
+            fdv_apt,
+            liquidity_usd: usd(&liquidity_apt),
+            liquidity_apt: liquidity_apt.round(8),
+            volume_24h_apt: (volume_24h / &octas).round(8),
+            volume_24h_usd,
+            price_change_24h: price_change_24h.round(4),
+            txn_version: reserves.txn_version,
+        })
+    }
+}
@@ -1,13 +1,13 @@
 use aptos_indexer_processor_sdk::utils::convert::{standardize_address};
-use diesel::{AsChangeset, Insertable};
+use diesel::{AsChangeset, Insertable, Queryable};
 use field_count::FieldCount;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use serde::de::Deserializer;
-use bigdecimal::BigDecimal;
+use bigdecimal::{BigDecimal, Zero};
 use crate::schema::tokens;
 
-#[derive(AsChangeset, Clone, Debug, Deserialize, FieldCount, Insertable, Serialize)]
+#[derive(AsChangeset, Clone, Debug, Deserialize, FieldCount, Insertable, Queryable, Serialize)]
 #[diesel(table_name = tokens)]
 pub struct Token {
     pub pool_addr: String,
@@ -27,7 +27,19 @@ pub struct Token {
     pub created_by: String,
     pub is_completed: bool,
     pub ts: i64,
-    pub txn_version: i64
+    pub txn_version: i64,
+    pub contract_address: String,
+    pub block_height: Option<i64>,
+    /// Tokens the creator sold within 24h of the launch, added to as its sells are stored
+    pub creator_sold_24h: BigDecimal,
+    /// Tokens sold through the curve when the token graduates
+    pub curve_supply: Option<BigDecimal>,
+    /// Virtual aptos reserves of the pool once the curve supply is sold
+    pub graduation_aptos_reserves: Option<BigDecimal>,
+    pub curve_progress: BigDecimal,
+    pub buy_velocity: BigDecimal,
+    pub graduation_eta: Option<i64>,
+    pub curve_updated_version: Option<i64>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -55,8 +67,8 @@ pub struct TokenCreatedOnChain {
 }
 
 impl TokenCreatedOnChain {
-       pub fn to_db_token(&self, txn_version: i64) -> Token {
-        Token {
+       pub fn to_db_token(&self, txn_version: i64, contract_address: &str) -> Token {
+        let mut token = Token {
             pool_addr: standardize_address(&self.pool_addr),
             name: self.name.clone(),
             symbol: self.symbol.clone(),
@@ -74,8 +86,37 @@ impl TokenCreatedOnChain {
             created_by: standardize_address(&self.created_by),
             is_completed: self.is_completed,
             ts: self.ts.parse().unwrap(),
-            txn_version
-        }
+            txn_version,
+            contract_address: contract_address.to_string(),
+            block_height: None,
+            creator_sold_24h: BigDecimal::from(0),
+            curve_supply: None,
+            graduation_aptos_reserves: None,
+            curve_progress: BigDecimal::from(0),
+            buy_velocity: BigDecimal::from(0),
+            graduation_eta: None,
+            curve_updated_version: None,
+        };
+        let (curve_supply, graduation_aptos_reserves) = token.curve();
+        token.curve_supply = Some(curve_supply);
+        token.graduation_aptos_reserves = graduation_aptos_reserves;
+        token
+    }
+}
+
+impl Token {
+    /// Supply sold through the curve and the virtual aptos reserves once it is sold, from the
+    /// reserves of the `TokenCreated` event. Pools start with `virtual_token_reserves` and
+    /// graduate when only the locked `remain_token_reserves` are left, buys keep the product
+    /// of the virtual reserves. Matches the backfill of the `curve_progress` migration.
+    pub fn curve(&self) -> (BigDecimal, Option<BigDecimal>) {
+        let curve_supply = &self.virtual_token_reserves - &self.remain_token_reserves;
+        let graduation_aptos_reserves = (!self.remain_token_reserves.is_zero()).then(|| {
+            (&self.virtual_aptos_reserves * &self.virtual_token_reserves
+                / &self.remain_token_reserves)
+                .with_scale(0)
+        });
+        (curve_supply, graduation_aptos_reserves)
     }
 }
 
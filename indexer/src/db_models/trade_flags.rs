@@ -0,0 +1,222 @@
+use aptos_indexer_processor_sdk::{
+    aptos_protos::transaction::v1::EntryFunctionPayload, utils::convert::standardize_address,
+};
+use diesel::{AsChangeset, Insertable, Queryable};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    db_models::failed_transactions::{entry_function_arguments, entry_function_id},
+    schema::{creator_fundings, token_risk_scores, trade_flags},
+    utils::abi_decoder::MoveTypeAbi,
+};
+
+/// Buy in the block or transaction that created the token, by someone else than the creator
+pub const FLAG_SNIPER: &str = "sniper";
+/// Buy by a wallet the creator funded
+pub const FLAG_BUNDLE: &str = "bundle";
+/// Sell shortly after a buy of the same user
+pub const FLAG_ROUND_TRIP: &str = "round_trip";
+/// Trade against a recent opposite trade of the creator or a wallet it funded
+pub const FLAG_WASH: &str = "wash";
+
+#[derive(AsChangeset, Clone, Debug, Deserialize, FieldCount, Insertable, Queryable, Serialize)]
+#[diesel(table_name = trade_flags)]
+pub struct TradeFlag {
+    pub txn_version: i64,
+    pub flag: String,
+    pub token_address: String,
+    pub user_addr: String,
+    pub detail: Value,
+    pub ts: i64,
+}
+
+#[derive(AsChangeset, Clone, Debug, Deserialize, FieldCount, Insertable, Queryable, Serialize)]
+#[diesel(table_name = token_risk_scores)]
+pub struct TokenRiskScore {
+    pub token_address: String,
+    pub risk_score: i32,
+    pub sniper_trades: i32,
+    pub bundle_trades: i32,
+    pub round_trip_trades: i32,
+    pub wash_trades: i32,
+    pub flagged_volume: i64,
+    pub total_volume: i64,
+    pub updated_at_version: i64,
+}
+
+#[derive(AsChangeset, Clone, Debug, Deserialize, FieldCount, Insertable, Queryable, Serialize)]
+#[diesel(table_name = creator_fundings)]
+pub struct CreatorFunding {
+    pub txn_version: i64,
+    pub recipient: String,
+    pub creator: String,
+    pub amount: i64,
+    pub ts: i64,
+}
+
+/// APT sent by a user transaction with one of the `0x1` transfer entry functions
+#[derive(Clone, Debug)]
+pub struct AptTransfer {
+    pub txn_version: i64,
+    pub sender: String,
+    pub recipient: String,
+    pub amount: i64,
+    pub ts: i64,
+}
+
+impl AptTransfer {
+    pub fn from_payload(
+        txn_version: i64,
+        ts: i64,
+        sender: &str,
+        payload: &EntryFunctionPayload,
+    ) -> Vec<Self> {
+        let function = entry_function_id(payload);
+        let Some(function) = function.strip_prefix(&format!("{}::", standardize_address("0x1")))
+        else {
+            return vec![];
+        };
+        let arguments = match entry_function_arguments(payload) {
+            Value::Array(arguments) => arguments,
+            _ => return vec![],
+        };
+        let is_apt_coin = || {
+            payload.type_arguments.iter().all(|type_argument| {
+                serde_json::to_value(type_argument)
+                    .map(|value| MoveTypeAbi::from_abi_json(&value) == apt_coin_type())
+                    .unwrap_or(false)
+            })
+        };
+        let transfers = match (function, arguments.as_slice()) {
+            ("aptos_account::transfer", [to, amount]) => vec![(to, amount)],
+            ("aptos_account::transfer_coins" | "coin::transfer", [to, amount])
+                if is_apt_coin() =>
+            {
+                vec![(to, amount)]
+            }
+            (
+                "aptos_account::transfer_fungible_assets" | "primary_fungible_store::transfer",
+                [metadata, to, amount],
+            ) if is_apt_metadata(metadata) => vec![(to, amount)],
+            ("aptos_account::batch_transfer", [Value::Array(to), Value::Array(amounts)]) => {
+                to.iter().zip(amounts).collect()
+            }
+            (
+                "aptos_account::batch_transfer_coins",
+                [Value::Array(to), Value::Array(amounts)],
+            ) if is_apt_coin() => to.iter().zip(amounts).collect(),
+            _ => vec![],
+        };
+        transfers
+            .into_iter()
+            .filter_map(|(to, amount)| {
+                Some(Self {
+                    txn_version,
+                    sender: standardize_address(sender),
+                    recipient: standardize_address(to.as_str()?),
+                    amount: match amount {
+                        Value::String(amount) => amount.parse().ok()?,
+                        amount => amount.as_i64()?,
+                    },
+                    ts,
+                })
+            })
+            .collect()
+    }
+
+    pub fn to_db_creator_funding(&self) -> CreatorFunding {
+        CreatorFunding {
+            txn_version: self.txn_version,
+            recipient: self.recipient.clone(),
+            creator: self.sender.clone(),
+            amount: self.amount,
+            ts: self.ts,
+        }
+    }
+}
+
+fn apt_coin_type() -> MoveTypeAbi {
+    MoveTypeAbi::Struct {
+        address: standardize_address("0x1"),
+        module: "aptos_coin".to_string(),
+        name: "AptosCoin".to_string(),
+        generic_type_params: vec![],
+    }
+}
+
+/// APT's fungible asset metadata is at `0xa`, objects may be sent as `{ "inner": address }`
+fn is_apt_metadata(metadata: &Value) -> bool {
+    let address = match metadata {
+        Value::String(address) => Some(address.as_str()),
+        metadata => metadata.get("inner").and_then(Value::as_str),
+    };
+    address.is_some_and(|address| standardize_address(address) == standardize_address("0xa"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use aptos_indexer_processor_sdk::aptos_protos::transaction::v1::{
+        EntryFunctionId, MoveModuleId,
+    };
+
+    fn payload(module: &str, name: &str, arguments: &[&str]) -> EntryFunctionPayload {
+        EntryFunctionPayload {
+            function: Some(EntryFunctionId {
+                module: Some(MoveModuleId {
+                    address: "0x1".to_string(),
+                    name: module.to_string(),
+                }),
+                name: name.to_string(),
+            }),
+            arguments: arguments.iter().map(|arg| arg.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_apt_transfers() {
+        let transfers = AptTransfer::from_payload(
+            10,
+            20,
+            "0x3",
+            &payload("aptos_account", "transfer", &["\"0x2\"", "\"100\""]),
+        );
+        assert_eq!(transfers.len(), 1);
+        assert_eq!(transfers[0].sender, standardize_address("0x3"));
+        assert_eq!(transfers[0].recipient, standardize_address("0x2"));
+        assert_eq!(transfers[0].amount, 100);
+
+        let transfers = AptTransfer::from_payload(
+            10,
+            20,
+            "0x3",
+            &payload(
+                "aptos_account",
+                "batch_transfer",
+                &["[\"0x4\",\"0x5\"]", "[\"1\",\"2\"]"],
+            ),
+        );
+        assert_eq!(
+            transfers
+                .iter()
+                .map(|transfer| transfer.amount)
+                .collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+
+        let transfers = AptTransfer::from_payload(
+            10,
+            20,
+            "0x3",
+            &payload(
+                "primary_fungible_store",
+                "transfer",
+                &["{\"inner\":\"0xb\"}", "\"0x2\"", "\"100\""],
+            ),
+        );
+        assert!(transfers.is_empty());
+    }
+}
@@ -1,11 +1,11 @@
 use aptos_indexer_processor_sdk::utils::convert::{standardize_address};
-use diesel::{AsChangeset, Insertable};
+use diesel::{AsChangeset, Insertable, Queryable};
 use field_count::FieldCount;
 use serde::{Deserialize, Serialize};
 use bigdecimal::BigDecimal;
 use crate::schema::trades;
 
-#[derive(AsChangeset, Clone, Debug, Deserialize, FieldCount, Insertable, Serialize)]
+#[derive(AsChangeset, Clone, Debug, Deserialize, FieldCount, Insertable, Queryable, Serialize)]
 #[diesel(table_name = trades)]
 pub struct Trade {
     pub txn_version: i64,
@@ -17,6 +17,17 @@ pub struct Trade {
     pub virtual_aptos_reserves: BigDecimal,
     pub virtual_token_reserves: BigDecimal,
     pub ts: i64,
+    pub contract_address: String,
+    // Set once the whitelist window of the token is known
+    pub in_whitelist_window: Option<bool>,
+    pub block_height: Option<i64>,
+    // USD values at the APT/USD price of the trade's minute, set once the price is known
+    pub apt_usd_price: Option<BigDecimal>,
+    pub aptos_amount_usd: Option<BigDecimal>,
+    pub fee_usd: Option<BigDecimal>,
+    // Fees in octas at the on-chain config of the trade's version, set before it is stored
+    pub fee: Option<i64>,
+    pub creator_fee: Option<i64>,
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -32,7 +43,7 @@ pub struct TradeCreatedOnChain {
 }
 
 impl TradeCreatedOnChain {
-       pub fn to_db_trade(&self, txn_version: i64) -> Trade {
+       pub fn to_db_trade(&self, txn_version: i64, contract_address: &str) -> Trade {
         Trade {
             txn_version,
             is_buy: self.is_buy,
@@ -43,6 +54,14 @@ impl TradeCreatedOnChain {
             virtual_aptos_reserves: self.virtual_aptos_reserves.parse().unwrap(),
             virtual_token_reserves: self.virtual_token_reserves.parse().unwrap(),
             ts: self.ts.parse().unwrap(),
+            contract_address: contract_address.to_string(),
+            in_whitelist_window: None,
+            block_height: None,
+            apt_usd_price: None,
+            aptos_amount_usd: None,
+            fee_usd: None,
+            fee: None,
+            creator_fee: None,
         }
     }
 }
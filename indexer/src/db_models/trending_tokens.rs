@@ -0,0 +1,133 @@
+use ahash::AHashMap;
+use bigdecimal::{BigDecimal, FromPrimitive};
+use diesel::{Insertable, Queryable};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+use crate::{config::indexer_processor_config::TrendingWeights, schema::trending_tokens};
+
+#[derive(Clone, Debug, Deserialize, FieldCount, Insertable, Queryable, Serialize)]
+#[diesel(table_name = trending_tokens)]
+/// One ranked row of the trending tokens, rank 1 is the top
+pub struct TrendingToken {
+    pub rank: i32,
+    pub token_address: String,
+    pub score: BigDecimal,
+    pub volume: BigDecimal,
+    pub unique_buyers: BigDecimal,
+    pub trade_count: BigDecimal,
+    pub holder_growth: BigDecimal,
+    pub curve_progress: BigDecimal,
+    pub refreshed_at_ts: i64,
+}
+
+/// Weight of a trade `age_secs` old, halved every `half_life_secs`
+pub fn decay(age_secs: i64, half_life_secs: i64) -> f64 {
+    0.5f64.powf(age_secs.max(0) as f64 / half_life_secs as f64)
+}
+
+/// Activity of a token over the trending window, every component but the curve progress
+/// is decayed by the age of the trades it comes from
+#[derive(Clone, Debug, Default)]
+pub struct TrendingActivity {
+    // In APT
+    pub volume: f64,
+    pub unique_buyers: f64,
+    pub trade_count: f64,
+    pub holder_growth: f64,
+    // In percent
+    pub curve_progress: f64,
+}
+
+impl TrendingActivity {
+    fn score(&self, max: &Self, weights: &TrendingWeights) -> f64 {
+        let scaled = |value: f64, max: f64| if max > 0.0 { value / max } else { 0.0 };
+        weights.volume * scaled(self.volume, max.volume)
+            + weights.unique_buyers * scaled(self.unique_buyers, max.unique_buyers)
+            + weights.trade_count * scaled(self.trade_count, max.trade_count)
+            + weights.holder_growth * scaled(self.holder_growth, max.holder_growth)
+            + weights.curve_progress * scaled(self.curve_progress, max.curve_progress)
+    }
+}
+
+impl TrendingToken {
+    /// Scores every token against the highest value of each component and keeps the `size`
+    /// best, ties are broken by address.
+    pub fn rank(
+        weights: &TrendingWeights,
+        activities: AHashMap<String, TrendingActivity>,
+        size: i64,
+        refreshed_at_ts: i64,
+    ) -> Vec<Self> {
+        let max = activities
+            .values()
+            .fold(TrendingActivity::default(), |max, activity| {
+                TrendingActivity {
+                    volume: max.volume.max(activity.volume),
+                    unique_buyers: max.unique_buyers.max(activity.unique_buyers),
+                    trade_count: max.trade_count.max(activity.trade_count),
+                    holder_growth: max.holder_growth.max(activity.holder_growth),
+                    curve_progress: max.curve_progress.max(activity.curve_progress),
+                }
+            });
+        let mut scored = activities
+            .into_iter()
+            .map(|(token_address, activity)| {
+                (activity.score(&max, weights), token_address, activity)
+            })
+            .collect::<Vec<(f64, String, TrendingActivity)>>();
+        scored.sort_by(|(a, a_addr, _), (b, b_addr, _)| {
+            b.total_cmp(a).then_with(|| a_addr.cmp(b_addr))
+        });
+        let decimal =
+            |value: f64, scale: i64| BigDecimal::from_f64(value).unwrap_or_default().round(scale);
+        scored
+            .into_iter()
+            .take(size as usize)
+            .enumerate()
+            .map(|(idx, (score, token_address, activity))| Self {
+                rank: idx as i32 + 1,
+                token_address,
+                score: decimal(score, 6),
+                volume: decimal(activity.volume, 8),
+                unique_buyers: decimal(activity.unique_buyers, 4),
+                trade_count: decimal(activity.trade_count, 4),
+                holder_growth: decimal(activity.holder_growth, 4),
+                curve_progress: decimal(activity.curve_progress, 4),
+                refreshed_at_ts,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rank_trending_tokens() {
+        assert_eq!(decay(3_600, 3_600), 0.5);
+        let activity = |volume: f64, unique_buyers: f64, curve_progress: f64| TrendingActivity {
+            volume,
+            unique_buyers,
+            trade_count: unique_buyers,
+            holder_growth: 0.0,
+            curve_progress,
+        };
+        let activities = AHashMap::from_iter([
+            ("0xa".to_string(), activity(10.0, 2.0, 50.0)),
+            ("0xb".to_string(), activity(40.0, 4.0, 10.0)),
+            ("0xc".to_string(), activity(1.0, 1.0, 5.0)),
+        ]);
+        let ranked = TrendingToken::rank(&TrendingWeights::default(), activities, 2, 100);
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].token_address, "0xb");
+        assert_eq!(ranked[0].rank, 1);
+        // 0.35 + 0.25 + 0.15 + 0.1 * 10 / 50
+        assert_eq!(
+            ranked[0].score,
+            BigDecimal::from_f64(0.77).unwrap().round(6)
+        );
+        assert_eq!(ranked[1].token_address, "0xa");
+    }
+}
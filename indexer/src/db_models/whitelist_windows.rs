@@ -0,0 +1,42 @@
+use diesel::{AsChangeset, Insertable, Queryable};
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+use crate::{db_models::tokens::Token, schema::whitelist_windows};
+
+#[derive(AsChangeset, Clone, Debug, Deserialize, FieldCount, Insertable, Queryable, Serialize)]
+#[diesel(table_name = whitelist_windows)]
+/// Time after the creation of a token during which only whitelisted wallets can buy it
+pub struct WhitelistWindow {
+    pub token_address: String,
+    pub pool_addr: String,
+    pub contract_address: String,
+    pub whitelist_duration: i64,
+    pub starts_at: i64,
+    pub ends_at: i64,
+    pub txn_version: i64,
+}
+
+impl WhitelistWindow {
+    pub fn new(token: &Token, whitelist_duration: i64) -> Self {
+        Self {
+            token_address: token.pre_addr.clone(),
+            pool_addr: token.pool_addr.clone(),
+            contract_address: token.contract_address.clone(),
+            whitelist_duration,
+            starts_at: token.ts,
+            ends_at: token.ts + whitelist_duration,
+            txn_version: token.txn_version,
+        }
+    }
+
+    pub fn contains(&self, ts: i64) -> bool {
+        self.starts_at <= ts && ts < self.ends_at
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+/// Whitelist part of the `mooner_money::Config` resource
+pub struct WhitelistConfigOnChain {
+    pub whitelist_duration: String,
+}
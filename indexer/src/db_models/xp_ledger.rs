@@ -0,0 +1,29 @@
+use diesel::Insertable;
+use field_count::FieldCount;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::xp_ledger;
+
+/// XP awarded for a trade, `source_ref` is the trade's txn_version
+pub const XP_SOURCE_TRADE: &str = "trade";
+/// XP won on a spin, `source_ref` is the spin's txn_version
+pub const XP_SOURCE_SPIN: &str = "spin";
+/// XP credited by the backend for a task claim, `source_ref` is the task_claims id
+pub const XP_SOURCE_TASK_CLAIM: &str = "task_claim";
+/// XP spent by the backend on a spin, `source_ref` is a uuid
+pub const XP_SOURCE_SPIN_COST: &str = "spin_cost";
+/// XP earned by an account before the ledger existed, `source_ref` is the address
+pub const XP_SOURCE_OPENING_BALANCE: &str = "opening_balance";
+/// XP spent by an account before the ledger existed, `source_ref` is the address
+pub const XP_SOURCE_OPENING_SPENT: &str = "opening_spent";
+
+#[derive(Clone, Debug, Deserialize, FieldCount, Insertable, Serialize)]
+#[diesel(table_name = xp_ledger)]
+/// Append-only XP change of an address, unique per (source_type, source_ref)
+pub struct XpLedgerEntry {
+    pub address: String,
+    pub delta: i32,
+    pub source_type: String,
+    pub source_ref: String,
+    pub txn_version: Option<i64>,
+}
@@ -0,0 +1,311 @@
+//! In-process feed of the contract events stored by the processor. The `Storer` publishes
+//! every batch once it is committed, and the health server streams it to clients over
+//! WebSocket and SSE. Recent events are kept in memory so clients can resume after a
+//! reconnect from the (txn_version, event_index) of the last event they got. Resuming from
+//! an event that already left the history fails with [`CursorTooOld`], such clients have to
+//! read the gap back from the database, e.g. through the read api. A client too slow to keep
+//! up gets [`SubscriberLagged`] and is disconnected, so it resumes from its cursor.
+
+use aptos_indexer_processor_sdk::utils::convert::standardize_address;
+use futures_util::{stream, Stream, StreamExt};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    future::ready,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::broadcast;
+
+use crate::{db_models::graduation_signals::GraduationSignal, steps::extractor::ContractEvent};
+
+/// Number of recent events kept for clients resuming from a cursor.
+pub const FEED_HISTORY_SIZE: usize = 10_000;
+
+/// Event index of the first event derived by the processor in a transaction, such events
+/// are ordered after every on-chain event of it.
+pub const DERIVED_EVENT_INDEX: i64 = 1 << 32;
+
+pub static EVENT_FEED: Lazy<EventFeed> = Lazy::new(|| EventFeed::new(FEED_HISTORY_SIZE));
+
+#[derive(Clone, Debug, Serialize)]
+pub struct FeedEvent {
+    pub txn_version: i64,
+    /// Index of the event in its transaction, from [`DERIVED_EVENT_INDEX`] on for events
+    /// derived by the processor
+    pub event_index: i64,
+    pub event_type: String,
+    /// Token addresses the event is about, pre and main address for created tokens
+    pub tokens: Vec<String>,
+    /// Users the event is about
+    pub users: Vec<String>,
+    pub data: serde_json::Value,
+}
+
+impl FeedEvent {
+    pub fn from_contract_event(event: &ContractEvent, txn_version: i64, event_index: i64) -> Self {
+        let (event_type, tokens, users, data) = match event {
+            ContractEvent::TokenCreatedEvent(token) => (
+                "token_created",
+                vec![token.pre_addr.clone(), token.main_addr.clone()],
+                vec![token.created_by.clone()],
+                serde_json::to_value(token),
+            ),
+            ContractEvent::PoolCompletedEvent(pool) => (
+                "pool_completed",
+                vec![pool.main_addr.clone()],
+                vec![],
+                serde_json::to_value(pool),
+            ),
+            ContractEvent::TradeCreatedEvent(trade) => (
+                "trade",
+                vec![trade.token_address.clone()],
+                vec![trade.user_addr.clone()],
+                serde_json::to_value(trade),
+            ),
+            ContractEvent::PositionCreated(staking) => (
+                "position_created",
+                vec![],
+                vec![staking.user.clone()],
+                serde_json::to_value(staking),
+            ),
+            ContractEvent::PositionRemoved(staking) => (
+                "position_removed",
+                vec![],
+                vec![],
+                serde_json::to_value(staking),
+            ),
+            ContractEvent::PositionRewardClaimed(reward) => (
+                "reward_claimed",
+                vec![],
+                vec![],
+                serde_json::to_value(reward),
+            ),
+            ContractEvent::SpinEvent(spin) => (
+                "spin",
+                vec![],
+                vec![spin.claimer.clone()],
+                serde_json::to_value(spin),
+            ),
+        };
+        Self {
+            txn_version,
+            event_index,
+            event_type: event_type.to_string(),
+            tokens,
+            users,
+            data: data.unwrap_or_default(),
+        }
+    }
+
+    /// Near graduation signal, `index` orders the signals of a transaction.
+    pub fn from_graduation_signal(signal: &GraduationSignal, index: i64) -> Self {
+        Self {
+            txn_version: signal.txn_version,
+            event_index: DERIVED_EVENT_INDEX + index,
+            event_type: "near_graduation".to_string(),
+            tokens: vec![signal.token_address.clone()],
+            users: vec![],
+            data: serde_json::to_value(signal).unwrap_or_default(),
+        }
+    }
+
+    pub fn cursor(&self) -> (i64, i64) {
+        (self.txn_version, self.event_index)
+    }
+}
+
+/// Subscription filter, every field that is set must match.
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct FeedFilter {
+    pub token: Option<String>,
+    pub user: Option<String>,
+    /// Comma separated list of event types
+    pub event_type: Option<String>,
+    /// Only events after this version are sent, including the ones still in history. With
+    /// `from_event_index`, only events after that event of the version.
+    pub from_version: Option<i64>,
+    pub from_event_index: Option<i64>,
+}
+
+impl FeedFilter {
+    /// (txn_version, event_index) the client resumes after.
+    pub fn cursor(&self) -> Option<(i64, i64)> {
+        let from_version = self.from_version?;
+        Some((from_version, self.from_event_index.unwrap_or(i64::MAX)))
+    }
+
+    pub fn matches(&self, event: &FeedEvent) -> bool {
+        if let Some(token) = self.token.as_ref() {
+            let token = standardize_address(token);
+            if !event.tokens.contains(&token) {
+                return false;
+            }
+        }
+        if let Some(user) = self.user.as_ref() {
+            let user = standardize_address(user);
+            if !event.users.contains(&user) {
+                return false;
+            }
+        }
+        if let Some(event_types) = self.event_type.as_ref() {
+            if !event_types.split(',').any(|t| t.trim() == event.event_type) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// The cursor a client resumes after is older than the history, some of the events after
+/// it were already dropped.
+#[derive(Clone, Copy, Debug)]
+pub struct CursorTooOld {
+    /// Cursor of the oldest event still in history
+    pub oldest: Option<(i64, i64)>,
+}
+
+impl std::fmt::Display for CursorTooOld {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.oldest {
+            Some((txn_version, event_index)) => write!(
+                f,
+                "cursor is older than the event feed history, which starts at version {} event {}",
+                txn_version, event_index
+            ),
+            None => write!(f, "cursor is older than the event feed history"),
+        }
+    }
+}
+
+impl std::error::Error for CursorTooOld {}
+
+/// The subscriber fell too far behind the feed and missed events. Its stream ends after this,
+/// it has to resume from the last event it got.
+#[derive(Clone, Copy, Debug)]
+pub struct SubscriberLagged {
+    pub skipped: u64,
+}
+
+impl std::fmt::Display for SubscriberLagged {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "fell behind the event feed and missed {} events, resume from the last event received",
+            self.skipped
+        )
+    }
+}
+
+impl std::error::Error for SubscriberLagged {}
+
+struct FeedHistory {
+    events: VecDeque<Arc<FeedEvent>>,
+    // Cursor of the latest event dropped from the history
+    dropped_until: Option<(i64, i64)>,
+}
+
+pub struct EventFeed {
+    sender: broadcast::Sender<Arc<FeedEvent>>,
+    history: Mutex<FeedHistory>,
+    history_size: usize,
+}
+
+impl EventFeed {
+    pub fn new(history_size: usize) -> Self {
+        let (sender, _) = broadcast::channel(history_size.max(1));
+        Self {
+            sender,
+            history: Mutex::new(FeedHistory {
+                events: VecDeque::with_capacity(history_size),
+                dropped_until: None,
+            }),
+            history_size,
+        }
+    }
+
+    /// Publishes events in (txn_version, event_index) order, after the ones published
+    /// before them.
+    pub fn publish(&self, events: Vec<FeedEvent>) {
+        let mut history = self.history.lock().unwrap();
+        for event in events {
+            let event = Arc::new(event);
+            if history.events.len() >= self.history_size {
+                if let Some(dropped) = history.events.pop_front() {
+                    history.dropped_until = Some(dropped.cursor());
+                }
+            }
+            history.events.push_back(event.clone());
+            // Fails only when nobody is subscribed
+            let _ = self.sender.send(event);
+        }
+    }
+
+    /// Publishes the events of a stored batch with the signals derived from it.
+    pub fn publish_batch(&self, mut events: Vec<FeedEvent>, signals: &[GraduationSignal]) {
+        let mut signals = signals.iter().collect::<Vec<&GraduationSignal>>();
+        signals.sort_by_key(|signal| (signal.txn_version, &signal.token_address, signal.threshold));
+        let mut previous_version = None;
+        let mut index = 0;
+        for signal in signals {
+            if previous_version != Some(signal.txn_version) {
+                previous_version = Some(signal.txn_version);
+                index = 0;
+            }
+            events.push(FeedEvent::from_graduation_signal(signal, index));
+            index += 1;
+        }
+        events.sort_by_key(FeedEvent::cursor);
+        self.publish(events);
+    }
+
+    /// Streams the events matching the filter, starting with the ones in history after
+    /// its cursor. A client that falls too far behind gets [`SubscriberLagged`] and its
+    /// stream ends, it is expected to resume from the last event it saw.
+    pub fn subscribe(
+        &self,
+        filter: FeedFilter,
+    ) -> Result<impl Stream<Item = Result<Arc<FeedEvent>, SubscriberLagged>> + Send, CursorTooOld>
+    {
+        // Take the history and subscribe under the lock so no event is missed or repeated
+        let history = self.history.lock().unwrap();
+        let backlog = match filter.cursor() {
+            Some(cursor) => {
+                if history
+                    .dropped_until
+                    .is_some_and(|dropped_until| cursor < dropped_until)
+                {
+                    return Err(CursorTooOld {
+                        oldest: history.events.front().map(|event| event.cursor()),
+                    });
+                }
+                history
+                    .events
+                    .iter()
+                    .filter(|event| event.cursor() > cursor)
+                    .cloned()
+                    .collect::<Vec<Arc<FeedEvent>>>()
+            }
+            None => vec![],
+        };
+        let receiver = self.sender.subscribe();
+        drop(history);
+
+        let live = stream::unfold(Some(receiver), |receiver| async move {
+            let mut receiver = receiver?;
+            match receiver.recv().await {
+                Ok(event) => Some((Ok(event), Some(receiver))),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!("Event feed subscriber lagged, skipped {} events", skipped);
+                    Some((Err(SubscriberLagged { skipped }), None))
+                }
+                Err(broadcast::error::RecvError::Closed) => None,
+            }
+        });
+        Ok(stream::iter(backlog.into_iter().map(Ok))
+            .chain(live)
+            .filter(move |event| {
+                ready(event.as_ref().map_or(true, |event| filter.matches(event)))
+            }))
+    }
+}
@@ -0,0 +1,1047 @@
+//! GraphQL api over the indexed tables, served by the health server at `/graphql` when
+//! `graphql_config.enabled` is set. Lists take `limit`, `cursor` and `order` arguments and
+//! are paginated on txn_version the same way as the read api in [`crate::query_api`].
+//! Queries over `graphql_config.max_complexity` or `max_depth` are rejected, list fields
+//! count as `limit` times their selection.
+
+use aptos_indexer_processor_sdk::utils::convert::standardize_address;
+use async_graphql::{
+    http::GraphiQLSource, ComplexObject, Context, EmptyMutation, EmptySubscription, Enum,
+    InputObject, Json, Object, Schema, SimpleObject,
+};
+use async_graphql_poem::GraphQL;
+use bigdecimal::BigDecimal;
+use diesel::{
+    BoolExpressionMethods, ExpressionMethods, OptionalExtension, PgTextExpressionMethods,
+    QueryDsl, Queryable,
+};
+use diesel_async::RunQueryDsl;
+use poem::{get, handler, web::Html, Route};
+
+use crate::{
+    config::indexer_processor_config::GraphqlConfig,
+    db_models::contract_transactions::ContractTransaction,
+    schema::{
+        accounts, address_labels, contract_transactions, creators, module_upgrade_history,
+        positions, stakings, token_metrics, token_risk_scores, tokens, trade_flags, trades,
+        trending_tokens,
+    },
+    utils::{
+        database_connection::get_db_connection,
+        database_utils::{ArcDbPool, DbPoolConnection},
+        whitelist_report::{load_whitelist_report, WhitelistReport},
+    },
+};
+
+pub type IndexerSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+pub fn build_schema(pool: ArcDbPool, config: GraphqlConfig) -> IndexerSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .limit_complexity(config.max_complexity)
+        .limit_depth(config.max_depth)
+        .data(pool)
+        .data(config)
+        .finish()
+}
+
+pub fn add_routes(route: Route, schema: IndexerSchema) -> Route {
+    route.at("/graphql", get(graphiql).post(GraphQL::new(schema)))
+}
+
+#[handler]
+async fn graphiql() -> Html<String> {
+    Html(GraphiQLSource::build().endpoint("/graphql").finish())
+}
+
+async fn conn<'a>(ctx: &Context<'a>) -> async_graphql::Result<DbPoolConnection<'a>> {
+    let pool = ctx.data::<ArcDbPool>()?;
+    Ok(get_db_connection(pool).await?)
+}
+
+fn page_size(ctx: &Context<'_>, limit: Option<i64>) -> async_graphql::Result<i64> {
+    let config = ctx.data::<GraphqlConfig>()?;
+    Ok(limit
+        .unwrap_or(config.default_page_size)
+        .clamp(1, config.max_page_size))
+}
+
+/// Complexity of a list field, the limit is not clamped here so oversized limits are
+/// rejected rather than silently cut.
+fn list_complexity(limit: Option<i64>, child_complexity: usize) -> usize {
+    (limit.unwrap_or(GraphqlConfig::default_page_size()).max(1) as usize)
+        .saturating_mul(child_complexity)
+}
+
+#[derive(Clone, Copy, Debug, Default, Enum, Eq, PartialEq)]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// Applies the cursor and order of a list argument to a boxed query on a version column.
+macro_rules! paginate {
+    ($query:expr, $column:expr, $cursor:expr, $order:expr) => {{
+        let query = $query;
+        match ($order.unwrap_or_default(), $cursor) {
+            (SortOrder::Desc, Some(cursor)) => query.filter($column.lt(cursor)).order($column.desc()),
+            (SortOrder::Desc, None) => query.order($column.desc()),
+            (SortOrder::Asc, Some(cursor)) => query.filter($column.gt(cursor)).order($column.asc()),
+            (SortOrder::Asc, None) => query.order($column.asc()),
+        }
+    }};
+}
+
+/// Column the top tokens are ranked by, highest first
+#[derive(Clone, Copy, Debug, Default, Enum, Eq, PartialEq)]
+pub enum TokenMetricsSort {
+    #[default]
+    MarketCap,
+    Fdv,
+    Liquidity,
+    #[graphql(name = "VOLUME_24H")]
+    Volume24h,
+    #[graphql(name = "PRICE_CHANGE_24H")]
+    PriceChange24h,
+}
+
+#[derive(Clone, Debug, Default, InputObject)]
+pub struct TokenFilter {
+    pub created_by: Option<String>,
+    pub is_completed: Option<bool>,
+    /// Case insensitive match on the name or symbol
+    pub search: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, InputObject)]
+pub struct TradeFilter {
+    pub token_address: Option<String>,
+    pub user_addr: Option<String>,
+    pub is_buy: Option<bool>,
+    pub from_ts: Option<i64>,
+    pub to_ts: Option<i64>,
+}
+
+#[derive(Clone, Debug, Default, InputObject)]
+pub struct StakingFilter {
+    pub user: Option<String>,
+    pub is_removed: Option<bool>,
+}
+
+#[derive(Clone, Debug, Default, InputObject)]
+pub struct TransactionFilter {
+    pub sender: Option<String>,
+    pub function_name: Option<String>,
+    pub success: Option<bool>,
+}
+
+#[derive(Clone, Debug, Queryable, SimpleObject)]
+#[graphql(complex, name = "Token")]
+pub struct TokenObject {
+    pub pool_addr: String,
+    pub name: String,
+    pub symbol: String,
+    pub image: String,
+    pub description: String,
+    pub website: Option<String>,
+    pub twitter: Option<String>,
+    pub telegram: Option<String>,
+    pub decimals: i16,
+    pub pre_addr: String,
+    pub main_addr: String,
+    pub virtual_aptos_reserves: BigDecimal,
+    pub virtual_token_reserves: BigDecimal,
+    pub remain_token_reserves: BigDecimal,
+    pub created_by: String,
+    pub is_completed: bool,
+    pub ts: i64,
+    pub txn_version: i64,
+    pub contract_address: String,
+    pub block_height: Option<i64>,
+    /// Tokens the creator sold within 24h of the launch
+    pub creator_sold_24h: BigDecimal,
+    /// Tokens sold through the curve when the token graduates
+    pub curve_supply: Option<BigDecimal>,
+    /// Virtual aptos reserves of the pool once the curve supply is sold
+    pub graduation_aptos_reserves: Option<BigDecimal>,
+    /// Share of the curve supply sold, in percent
+    pub curve_progress: BigDecimal,
+    /// Net aptos bought per second over the velocity window, in octas
+    pub buy_velocity: BigDecimal,
+    /// Projected graduation timestamp at the current velocity, null when not buying
+    pub graduation_eta: Option<i64>,
+    pub curve_updated_version: Option<i64>,
+}
+
+#[derive(Clone, Debug, Queryable, SimpleObject)]
+#[graphql(complex, name = "Trade")]
+pub struct TradeObject {
+    pub txn_version: i64,
+    pub is_buy: bool,
+    pub user_addr: String,
+    pub aptos_amount: i64,
+    pub token_amount: i64,
+    pub token_address: String,
+    pub virtual_aptos_reserves: BigDecimal,
+    pub virtual_token_reserves: BigDecimal,
+    pub ts: i64,
+    pub contract_address: String,
+    /// Whether the trade happened in the whitelist window of the token, null when unknown
+    pub in_whitelist_window: Option<bool>,
+    pub block_height: Option<i64>,
+    /// APT/USD price of the trade's minute, null until the price is known
+    pub apt_usd_price: Option<BigDecimal>,
+    pub aptos_amount_usd: Option<BigDecimal>,
+    /// Trading fee of the trade, in USD
+    pub fee_usd: Option<BigDecimal>,
+    /// Trading fee and creator share of it, in octas at the on-chain config of the trade
+    pub fee: Option<i64>,
+    pub creator_fee: Option<i64>,
+}
+
+#[derive(Clone, Debug, Queryable, SimpleObject)]
+#[graphql(complex, name = "Staking")]
+pub struct StakingObject {
+    pub position_addr: String,
+    pub stake_addr: String,
+    pub user: String,
+    pub amount: i64,
+    pub unlock_ts: i64,
+    pub txn_version: i64,
+    pub is_removed: bool,
+    pub claimed: Option<i64>,
+    pub contract_address: String,
+    /// Null for positions created before the timestamp was tracked
+    pub ts: Option<i64>,
+}
+
+#[derive(Clone, Debug, Queryable, SimpleObject)]
+#[graphql(complex, name = "Account")]
+pub struct AccountObject {
+    pub address: String,
+    pub xp: i32,
+    pub xp_earned: i32,
+    pub x_id: Option<String>,
+    pub x_username: Option<String>,
+    pub x_display_picture: Option<String>,
+    pub x_name: Option<String>,
+    pub x_verified: bool,
+    pub x_description: Option<String>,
+}
+
+#[derive(Clone, Debug, Queryable, SimpleObject)]
+#[graphql(complex, name = "Position")]
+pub struct PositionObject {
+    pub user_addr: String,
+    pub token_address: String,
+    pub balance: BigDecimal,
+    pub cost_basis: BigDecimal,
+    pub realized_pnl: BigDecimal,
+    pub aptos_bought: BigDecimal,
+    pub aptos_sold: BigDecimal,
+    pub last_txn_version: i64,
+    pub volume: BigDecimal,
+}
+
+#[derive(Clone, Debug, SimpleObject)]
+#[graphql(name = "ModuleUpgrade")]
+pub struct ModuleUpgradeObject {
+    pub module_addr: String,
+    pub module_name: String,
+    pub upgrade_number: i64,
+    pub module_source_code: String,
+    /// Decoded Move source, null when the deployer hid it
+    pub module_source_text: Option<String>,
+    pub module_abi: Json<serde_json::Value>,
+    pub tx_version: i64,
+}
+
+#[derive(Clone, Debug, SimpleObject)]
+#[graphql(name = "ContractTransaction")]
+pub struct ContractTransactionObject {
+    pub txn_version: i64,
+    pub contract_address: String,
+    pub sender: String,
+    pub sequence_number: i64,
+    pub module_name: String,
+    pub function_name: String,
+    pub function: String,
+    pub type_arguments: Json<serde_json::Value>,
+    /// Decoded with the function params when `decoded`, as sent otherwise
+    pub arguments: Json<serde_json::Value>,
+    pub decoded: bool,
+    pub success: bool,
+    pub vm_status: String,
+    pub gas_used: i64,
+    pub gas_unit_price: i64,
+    pub max_gas_amount: i64,
+    pub ts: i64,
+}
+
+impl From<ContractTransaction> for ContractTransactionObject {
+    fn from(transaction: ContractTransaction) -> Self {
+        Self {
+            txn_version: transaction.txn_version,
+            contract_address: transaction.contract_address,
+            sender: transaction.sender,
+            sequence_number: transaction.sequence_number,
+            module_name: transaction.module_name,
+            function_name: transaction.function_name,
+            function: transaction.function,
+            type_arguments: Json(transaction.type_arguments),
+            arguments: Json(transaction.arguments),
+            decoded: transaction.decoded,
+            success: transaction.success,
+            vm_status: transaction.vm_status,
+            gas_used: transaction.gas_used,
+            gas_unit_price: transaction.gas_unit_price,
+            max_gas_amount: transaction.max_gas_amount,
+            ts: transaction.ts,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Queryable, SimpleObject)]
+#[graphql(name = "TokenRiskScore")]
+pub struct TokenRiskScoreObject {
+    pub token_address: String,
+    /// 0 to 100, share of the traded volume that was flagged
+    pub risk_score: i32,
+    pub sniper_trades: i32,
+    pub bundle_trades: i32,
+    pub round_trip_trades: i32,
+    pub wash_trades: i32,
+    pub flagged_volume: i64,
+    pub total_volume: i64,
+    pub updated_at_version: i64,
+}
+
+#[derive(Clone, Debug, Queryable, SimpleObject)]
+#[graphql(complex, name = "Creator")]
+pub struct CreatorObject {
+    pub creator: String,
+    pub tokens_launched: i32,
+    pub tokens_graduated: i32,
+    /// Graduated over launched tokens, in percent
+    pub graduation_rate: BigDecimal,
+    /// Creator share of the trading fees of its tokens, in octas
+    pub creator_fees: i64,
+    pub avg_holders: BigDecimal,
+    /// Tokens sold within 24h of each launch
+    pub early_sold_token_amount: BigDecimal,
+    /// Share of the supply sold within 24h, averaged over the launches, in percent
+    pub early_sell_percentage: BigDecimal,
+    /// trusted, risky or unproven
+    pub trust_badge: String,
+    pub first_launch_ts: i64,
+    pub last_launch_ts: i64,
+    pub updated_at_version: i64,
+}
+
+#[derive(Clone, Debug, Queryable, SimpleObject)]
+#[graphql(name = "AddressLabel")]
+pub struct AddressLabelObject {
+    pub address: String,
+    pub label: String,
+    /// fee_wallet, team, bot, cex, pool, config, game, thala_pool, ...
+    pub category: String,
+    /// Addresses sharing a cluster belong to the same party
+    pub cluster: Option<String>,
+    /// file when imported from the label file, auto when derived by the indexer
+    pub source: String,
+    pub token_address: Option<String>,
+    pub txn_version: Option<i64>,
+}
+
+#[derive(Clone, Debug, Queryable, SimpleObject)]
+#[graphql(complex, name = "TokenMetrics")]
+pub struct TokenMetricsObject {
+    pub token_address: String,
+    pub snapshot_ts: i64,
+    /// Price of a whole token, in APT
+    pub price_apt: BigDecimal,
+    pub price_usd: Option<BigDecimal>,
+    /// Price times the supply sold through the curve, or the whole supply once graduated
+    pub market_cap_apt: BigDecimal,
+    pub market_cap_usd: Option<BigDecimal>,
+    /// Price times the whole supply
+    pub fdv_apt: BigDecimal,
+    pub fdv_usd: Option<BigDecimal>,
+    /// Real aptos in the curve
+    pub liquidity_apt: BigDecimal,
+    pub liquidity_usd: Option<BigDecimal>,
+    pub volume_24h_apt: BigDecimal,
+    /// Null unless every trade of the window is valued in USD
+    pub volume_24h_usd: Option<BigDecimal>,
+    /// In percent, from 24h before the snapshot or from the launch
+    pub price_change_24h: BigDecimal,
+    pub txn_version: i64,
+}
+
+#[derive(Clone, Debug, Queryable, SimpleObject)]
+#[graphql(complex, name = "TrendingToken")]
+pub struct TrendingTokenObject {
+    /// 1 is the top
+    pub rank: i32,
+    pub token_address: String,
+    /// Weighted sum of the components, each scaled to the highest token's
+    pub score: BigDecimal,
+    /// Decayed APT volume
+    pub volume: BigDecimal,
+    /// Buyers, each counted at the decayed weight of their latest buy
+    pub unique_buyers: BigDecimal,
+    /// Decayed number of trades
+    pub trade_count: BigDecimal,
+    /// Buyers whose first buy is in the window and still hold the token, decayed
+    pub holder_growth: BigDecimal,
+    /// In percent, not decayed
+    pub curve_progress: BigDecimal,
+    pub refreshed_at_ts: i64,
+}
+
+#[derive(Clone, Debug, SimpleObject)]
+pub struct HolderStats {
+    /// Number of positions with a balance left
+    pub holders: i64,
+    /// Sum of the balances from trades
+    pub total_balance: BigDecimal,
+}
+
+async fn get_token(
+    conn: &mut DbPoolConnection<'_>,
+    address: &str,
+) -> async_graphql::Result<Option<TokenObject>> {
+    Ok(tokens::table
+        .filter(
+            tokens::pre_addr
+                .eq(address)
+                .or(tokens::main_addr.eq(address))
+                .or(tokens::pool_addr.eq(address)),
+        )
+        .first::<TokenObject>(conn)
+        .await
+        .optional()?)
+}
+
+async fn get_account(
+    conn: &mut DbPoolConnection<'_>,
+    address: &str,
+) -> async_graphql::Result<Option<AccountObject>> {
+    Ok(accounts::table
+        .find(address)
+        .first::<AccountObject>(conn)
+        .await
+        .optional()?)
+}
+
+async fn get_creator(
+    conn: &mut DbPoolConnection<'_>,
+    address: &str,
+) -> async_graphql::Result<Option<CreatorObject>> {
+    Ok(creators::table
+        .find(address)
+        .first::<CreatorObject>(conn)
+        .await
+        .optional()?)
+}
+
+async fn get_labels(
+    conn: &mut DbPoolConnection<'_>,
+    address: &str,
+) -> async_graphql::Result<Vec<AddressLabelObject>> {
+    // A file label hides the auto label of the same name
+    Ok(address_labels::table
+        .filter(address_labels::address.eq(address))
+        .distinct_on(address_labels::label)
+        .order((address_labels::label, address_labels::source.desc()))
+        .load::<AddressLabelObject>(conn)
+        .await?)
+}
+
+async fn list_trades(
+    ctx: &Context<'_>,
+    filter: TradeFilter,
+    limit: Option<i64>,
+    cursor: Option<i64>,
+    order: Option<SortOrder>,
+) -> async_graphql::Result<Vec<TradeObject>> {
+    let limit = page_size(ctx, limit)?;
+    let mut query = trades::table.into_boxed();
+    if let Some(token_address) = filter.token_address {
+        query = query.filter(trades::token_address.eq(standardize_address(&token_address)));
+    }
+    if let Some(user_addr) = filter.user_addr {
+        query = query.filter(trades::user_addr.eq(standardize_address(&user_addr)));
+    }
+    if let Some(is_buy) = filter.is_buy {
+        query = query.filter(trades::is_buy.eq(is_buy));
+    }
+    if let Some(from_ts) = filter.from_ts {
+        query = query.filter(trades::ts.ge(from_ts));
+    }
+    if let Some(to_ts) = filter.to_ts {
+        query = query.filter(trades::ts.lt(to_ts));
+    }
+    Ok(paginate!(query, trades::txn_version, cursor, order)
+        .limit(limit)
+        .load::<TradeObject>(&mut conn(ctx).await?)
+        .await?)
+}
+
+async fn get_transaction(
+    conn: &mut DbPoolConnection<'_>,
+    txn_version: i64,
+) -> async_graphql::Result<Option<ContractTransactionObject>> {
+    Ok(contract_transactions::table
+        .find(txn_version)
+        .first::<ContractTransaction>(conn)
+        .await
+        .optional()?
+        .map(ContractTransactionObject::from))
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Looks a token up by its pre, main or pool address.
+    async fn token(
+        &self,
+        ctx: &Context<'_>,
+        address: String,
+    ) -> async_graphql::Result<Option<TokenObject>> {
+        get_token(&mut conn(ctx).await?, &standardize_address(&address)).await
+    }
+
+    #[graphql(complexity = "list_complexity(limit, child_complexity)")]
+    async fn tokens(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] filter: TokenFilter,
+        limit: Option<i64>,
+        cursor: Option<i64>,
+        order: Option<SortOrder>,
+    ) -> async_graphql::Result<Vec<TokenObject>> {
+        let limit = page_size(ctx, limit)?;
+        let mut query = tokens::table.into_boxed();
+        if let Some(created_by) = filter.created_by {
+            query = query.filter(tokens::created_by.eq(standardize_address(&created_by)));
+        }
+        if let Some(is_completed) = filter.is_completed {
+            query = query.filter(tokens::is_completed.eq(is_completed));
+        }
+        if let Some(search) = filter.search {
+            let pattern = format!("%{}%", search.replace('%', "\\%").replace('_', "\\_"));
+            query = query.filter(
+                tokens::name
+                    .ilike(pattern.clone())
+                    .or(tokens::symbol.ilike(pattern)),
+            );
+        }
+        Ok(paginate!(query, tokens::txn_version, cursor, order)
+            .limit(limit)
+            .load::<TokenObject>(&mut conn(ctx).await?)
+            .await?)
+    }
+
+    #[graphql(complexity = "list_complexity(limit, child_complexity)")]
+    async fn trades(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] filter: TradeFilter,
+        limit: Option<i64>,
+        cursor: Option<i64>,
+        order: Option<SortOrder>,
+    ) -> async_graphql::Result<Vec<TradeObject>> {
+        list_trades(ctx, filter, limit, cursor, order).await
+    }
+
+    #[graphql(complexity = "list_complexity(limit, child_complexity)")]
+    async fn stakings(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] filter: StakingFilter,
+        limit: Option<i64>,
+        cursor: Option<i64>,
+        order: Option<SortOrder>,
+    ) -> async_graphql::Result<Vec<StakingObject>> {
+        let limit = page_size(ctx, limit)?;
+        let mut query = stakings::table.into_boxed();
+        if let Some(user) = filter.user {
+            query = query.filter(stakings::user.eq(standardize_address(&user)));
+        }
+        if let Some(is_removed) = filter.is_removed {
+            query = query.filter(stakings::is_removed.eq(is_removed));
+        }
+        Ok(paginate!(query, stakings::txn_version, cursor, order)
+            .limit(limit)
+            .load::<StakingObject>(&mut conn(ctx).await?)
+            .await?)
+    }
+
+    async fn account(
+        &self,
+        ctx: &Context<'_>,
+        address: String,
+    ) -> async_graphql::Result<Option<AccountObject>> {
+        get_account(&mut conn(ctx).await?, &standardize_address(&address)).await
+    }
+
+    async fn creator(
+        &self,
+        ctx: &Context<'_>,
+        address: String,
+    ) -> async_graphql::Result<Option<CreatorObject>> {
+        get_creator(&mut conn(ctx).await?, &standardize_address(&address)).await
+    }
+
+    /// Creators with a trust badge, most launches first.
+    #[graphql(complexity = "list_complexity(limit, child_complexity)")]
+    async fn creators(
+        &self,
+        ctx: &Context<'_>,
+        trust_badge: Option<String>,
+        limit: Option<i64>,
+    ) -> async_graphql::Result<Vec<CreatorObject>> {
+        let limit = page_size(ctx, limit)?;
+        let mut query = creators::table.into_boxed();
+        if let Some(trust_badge) = trust_badge {
+            query = query.filter(creators::trust_badge.eq(trust_badge));
+        }
+        Ok(query
+            .order((creators::tokens_launched.desc(), creators::creator))
+            .limit(limit)
+            .load::<CreatorObject>(&mut conn(ctx).await?)
+            .await?)
+    }
+
+    /// Top tokens of the latest market snapshot, by market cap unless sorted otherwise.
+    #[graphql(complexity = "list_complexity(limit, child_complexity)")]
+    async fn top_tokens(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] sort_by: TokenMetricsSort,
+        limit: Option<i64>,
+    ) -> async_graphql::Result<Vec<TokenMetricsObject>> {
+        let limit = page_size(ctx, limit)?;
+        let conn = &mut conn(ctx).await?;
+        let Some(snapshot_ts) = token_metrics::table
+            .select(diesel::dsl::max(token_metrics::snapshot_ts))
+            .first::<Option<i64>>(conn)
+            .await?
+        else {
+            return Ok(vec![]);
+        };
+        let query = token_metrics::table
+            .filter(token_metrics::snapshot_ts.eq(snapshot_ts))
+            .into_boxed();
+        let query = match sort_by {
+            TokenMetricsSort::MarketCap => query.order(token_metrics::market_cap_apt.desc()),
+            TokenMetricsSort::Fdv => query.order(token_metrics::fdv_apt.desc()),
+            TokenMetricsSort::Liquidity => query.order(token_metrics::liquidity_apt.desc()),
+            TokenMetricsSort::Volume24h => query.order(token_metrics::volume_24h_apt.desc()),
+            TokenMetricsSort::PriceChange24h => {
+                query.order(token_metrics::price_change_24h.desc())
+            }
+        };
+        Ok(query
+            .then_order_by(token_metrics::token_address)
+            .limit(limit)
+            .load::<TokenMetricsObject>(conn)
+            .await?)
+    }
+
+    /// Trending tokens of the latest refresh, from rank 1.
+    #[graphql(complexity = "list_complexity(limit, child_complexity)")]
+    async fn trending_tokens(
+        &self,
+        ctx: &Context<'_>,
+        limit: Option<i64>,
+    ) -> async_graphql::Result<Vec<TrendingTokenObject>> {
+        let limit = page_size(ctx, limit)?;
+        Ok(trending_tokens::table
+            .order(trending_tokens::rank)
+            .limit(limit)
+            .load::<TrendingTokenObject>(&mut conn(ctx).await?)
+            .await?)
+    }
+
+    async fn labels(
+        &self,
+        ctx: &Context<'_>,
+        address: String,
+    ) -> async_graphql::Result<Vec<AddressLabelObject>> {
+        get_labels(&mut conn(ctx).await?, &standardize_address(&address)).await
+    }
+
+    /// Labeled addresses of a category or cluster, in address order.
+    #[graphql(complexity = "list_complexity(limit, child_complexity)")]
+    async fn labeled_addresses(
+        &self,
+        ctx: &Context<'_>,
+        category: Option<String>,
+        cluster: Option<String>,
+        limit: Option<i64>,
+    ) -> async_graphql::Result<Vec<AddressLabelObject>> {
+        let limit = page_size(ctx, limit)?;
+        // A file label hides the auto label of the same name
+        let mut query = address_labels::table
+            .distinct_on((address_labels::address, address_labels::label))
+            .into_boxed();
+        if let Some(category) = category {
+            query = query.filter(address_labels::category.eq(category));
+        }
+        if let Some(cluster) = cluster {
+            query = query.filter(address_labels::cluster.eq(cluster));
+        }
+        Ok(query
+            .order((
+                address_labels::address,
+                address_labels::label,
+                address_labels::source.desc(),
+            ))
+            .limit(limit)
+            .load::<AddressLabelObject>(&mut conn(ctx).await?)
+            .await?)
+    }
+
+    /// Entry function call of the contract at a transaction version, events and trades of
+    /// the transaction share its txn_version.
+    async fn transaction(
+        &self,
+        ctx: &Context<'_>,
+        txn_version: i64,
+    ) -> async_graphql::Result<Option<ContractTransactionObject>> {
+        get_transaction(&mut conn(ctx).await?, txn_version).await
+    }
+
+    #[graphql(complexity = "list_complexity(limit, child_complexity)")]
+    async fn transactions(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default)] filter: TransactionFilter,
+        limit: Option<i64>,
+        cursor: Option<i64>,
+        order: Option<SortOrder>,
+    ) -> async_graphql::Result<Vec<ContractTransactionObject>> {
+        let limit = page_size(ctx, limit)?;
+        let mut query = contract_transactions::table.into_boxed();
+        if let Some(sender) = filter.sender {
+            query = query.filter(contract_transactions::sender.eq(standardize_address(&sender)));
+        }
+        if let Some(function_name) = filter.function_name {
+            query = query.filter(contract_transactions::function_name.eq(function_name));
+        }
+        if let Some(success) = filter.success {
+            query = query.filter(contract_transactions::success.eq(success));
+        }
+        Ok(paginate!(query, contract_transactions::txn_version, cursor, order)
+            .limit(limit)
+            .load::<ContractTransaction>(&mut conn(ctx).await?)
+            .await?
+            .into_iter()
+            .map(ContractTransactionObject::from)
+            .collect())
+    }
+
+    /// Upgrades of the contract modules, paginated on the upgrade's tx_version.
+    #[graphql(complexity = "list_complexity(limit, child_complexity)")]
+    async fn module_upgrades(
+        &self,
+        ctx: &Context<'_>,
+        module_name: Option<String>,
+        limit: Option<i64>,
+        cursor: Option<i64>,
+        order: Option<SortOrder>,
+    ) -> async_graphql::Result<Vec<ModuleUpgradeObject>> {
+        let limit = page_size(ctx, limit)?;
+        let mut query = module_upgrade_history::table
+            .select((
+                module_upgrade_history::module_addr,
+                module_upgrade_history::module_name,
+                module_upgrade_history::upgrade_number,
+                module_upgrade_history::module_source_code,
+                module_upgrade_history::module_source_text,
+                module_upgrade_history::module_abi,
+                module_upgrade_history::tx_version,
+            ))
+            .into_boxed();
+        if let Some(module_name) = module_name {
+            query = query.filter(module_upgrade_history::module_name.eq(module_name));
+        }
+        Ok(paginate!(query, module_upgrade_history::tx_version, cursor, order)
+            .limit(limit)
+            .load::<(String, String, i64, String, Option<String>, serde_json::Value, i64)>(
+                &mut conn(ctx).await?,
+            )
+            .await?
+            .into_iter()
+            .map(
+                |(
+                    module_addr,
+                    module_name,
+                    upgrade_number,
+                    module_source_code,
+                    module_source_text,
+                    module_abi,
+                    tx_version,
+                )| {
+                    ModuleUpgradeObject {
+                        module_addr,
+                        module_name,
+                        upgrade_number,
+                        module_source_code,
+                        module_source_text,
+                        module_abi: Json(module_abi),
+                        tx_version,
+                    }
+                },
+            )
+            .collect())
+    }
+}
+
+#[ComplexObject]
+impl TokenObject {
+    #[graphql(complexity = "list_complexity(limit, child_complexity)")]
+    async fn trades(
+        &self,
+        ctx: &Context<'_>,
+        limit: Option<i64>,
+        cursor: Option<i64>,
+        order: Option<SortOrder>,
+    ) -> async_graphql::Result<Vec<TradeObject>> {
+        let filter = TradeFilter {
+            token_address: Some(self.pre_addr.clone()),
+            ..Default::default()
+        };
+        list_trades(ctx, filter, limit, cursor, order).await
+    }
+
+    /// Market snapshots of the token, paginated on the snapshot timestamp.
+    #[graphql(complexity = "list_complexity(limit, child_complexity)")]
+    async fn metrics(
+        &self,
+        ctx: &Context<'_>,
+        limit: Option<i64>,
+        cursor: Option<i64>,
+        order: Option<SortOrder>,
+    ) -> async_graphql::Result<Vec<TokenMetricsObject>> {
+        let limit = page_size(ctx, limit)?;
+        let query = token_metrics::table
+            .filter(token_metrics::token_address.eq(&self.pre_addr))
+            .into_boxed();
+        Ok(paginate!(query, token_metrics::snapshot_ts, cursor, order)
+            .limit(limit)
+            .load::<TokenMetricsObject>(&mut conn(ctx).await?)
+            .await?)
+    }
+
+    async fn last_trade(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<TradeObject>> {
+        let filter = TradeFilter {
+            token_address: Some(self.pre_addr.clone()),
+            ..Default::default()
+        };
+        Ok(list_trades(ctx, filter, Some(1), None, None)
+            .await?
+            .into_iter()
+            .next())
+    }
+
+    async fn holder_stats(&self, ctx: &Context<'_>) -> async_graphql::Result<HolderStats> {
+        let (holders, total_balance) = positions::table
+            .filter(positions::token_address.eq(&self.pre_addr))
+            .filter(positions::balance.gt(BigDecimal::from(0)))
+            .select((diesel::dsl::count_star(), diesel::dsl::sum(positions::balance)))
+            .first::<(i64, Option<BigDecimal>)>(&mut conn(ctx).await?)
+            .await?;
+        Ok(HolderStats {
+            holders,
+            total_balance: total_balance.unwrap_or_else(|| BigDecimal::from(0)),
+        })
+    }
+
+    async fn creator(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<AccountObject>> {
+        get_account(&mut conn(ctx).await?, &self.created_by).await
+    }
+
+    async fn creator_profile(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Option<CreatorObject>> {
+        get_creator(&mut conn(ctx).await?, &self.created_by).await
+    }
+
+    async fn creator_labels(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Vec<AddressLabelObject>> {
+        get_labels(&mut conn(ctx).await?, &self.created_by).await
+    }
+
+    async fn risk_score(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Option<TokenRiskScoreObject>> {
+        Ok(token_risk_scores::table
+            .find(&self.pre_addr)
+            .first::<TokenRiskScoreObject>(&mut conn(ctx).await?)
+            .await
+            .optional()?)
+    }
+
+    /// Supply taken by the wallets that bought in the whitelist window, null when the
+    /// window is unknown
+    async fn whitelist_report(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Option<Json<WhitelistReport>>> {
+        let conn = &mut conn(ctx).await?;
+        Ok(load_whitelist_report(conn, &self.pre_addr).await?.map(Json))
+    }
+}
+
+#[ComplexObject]
+impl TokenMetricsObject {
+    async fn token(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<TokenObject>> {
+        get_token(&mut conn(ctx).await?, &self.token_address).await
+    }
+}
+
+#[ComplexObject]
+impl TrendingTokenObject {
+    async fn token(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<TokenObject>> {
+        get_token(&mut conn(ctx).await?, &self.token_address).await
+    }
+}
+
+#[ComplexObject]
+impl TradeObject {
+    async fn token(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<TokenObject>> {
+        get_token(&mut conn(ctx).await?, &self.token_address).await
+    }
+
+    async fn user_labels(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Vec<AddressLabelObject>> {
+        get_labels(&mut conn(ctx).await?, &self.user_addr).await
+    }
+
+    /// Heuristics the trade matched: sniper, bundle, round_trip or wash
+    async fn flags(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<String>> {
+        Ok(trade_flags::table
+            .filter(trade_flags::txn_version.eq(self.txn_version))
+            .select(trade_flags::flag)
+            .load::<String>(&mut conn(ctx).await?)
+            .await?)
+    }
+
+    /// Call that made the trade, null when it didn't come from an entry function of ours
+    async fn transaction(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Option<ContractTransactionObject>> {
+        get_transaction(&mut conn(ctx).await?, self.txn_version).await
+    }
+}
+
+#[ComplexObject]
+impl AccountObject {
+    /// Launch history, null when the account never created a token
+    async fn creator_profile(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Option<CreatorObject>> {
+        get_creator(&mut conn(ctx).await?, &self.address).await
+    }
+
+    async fn labels(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Vec<AddressLabelObject>> {
+        get_labels(&mut conn(ctx).await?, &self.address).await
+    }
+
+    #[graphql(complexity = "list_complexity(limit, child_complexity)")]
+    async fn tokens_created(
+        &self,
+        ctx: &Context<'_>,
+        limit: Option<i64>,
+        cursor: Option<i64>,
+        order: Option<SortOrder>,
+    ) -> async_graphql::Result<Vec<TokenObject>> {
+        let limit = page_size(ctx, limit)?;
+        let query = tokens::table
+            .filter(tokens::created_by.eq(&self.address))
+            .into_boxed();
+        Ok(paginate!(query, tokens::txn_version, cursor, order)
+            .limit(limit)
+            .load::<TokenObject>(&mut conn(ctx).await?)
+            .await?)
+    }
+
+    /// Positions of the account, paginated on the version of their last trade.
+    #[graphql(complexity = "list_complexity(limit, child_complexity)")]
+    async fn positions(
+        &self,
+        ctx: &Context<'_>,
+        limit: Option<i64>,
+        cursor: Option<i64>,
+        order: Option<SortOrder>,
+    ) -> async_graphql::Result<Vec<PositionObject>> {
+        let limit = page_size(ctx, limit)?;
+        let query = positions::table
+            .filter(positions::user_addr.eq(&self.address))
+            .into_boxed();
+        Ok(paginate!(query, positions::last_txn_version, cursor, order)
+            .limit(limit)
+            .load::<PositionObject>(&mut conn(ctx).await?)
+            .await?)
+    }
+
+    #[graphql(complexity = "list_complexity(limit, child_complexity)")]
+    async fn trades(
+        &self,
+        ctx: &Context<'_>,
+        limit: Option<i64>,
+        cursor: Option<i64>,
+        order: Option<SortOrder>,
+    ) -> async_graphql::Result<Vec<TradeObject>> {
+        let filter = TradeFilter {
+            user_addr: Some(self.address.clone()),
+            ..Default::default()
+        };
+        list_trades(ctx, filter, limit, cursor, order).await
+    }
+}
+
+#[ComplexObject]
+impl StakingObject {
+    async fn user_labels(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Vec<AddressLabelObject>> {
+        get_labels(&mut conn(ctx).await?, &self.user).await
+    }
+}
+
+#[ComplexObject]
+impl PositionObject {
+    async fn user_labels(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Vec<AddressLabelObject>> {
+        get_labels(&mut conn(ctx).await?, &self.user_addr).await
+    }
+}
+
+#[ComplexObject]
+impl CreatorObject {
+    async fn labels(
+        &self,
+        ctx: &Context<'_>,
+    ) -> async_graphql::Result<Vec<AddressLabelObject>> {
+        get_labels(&mut conn(ctx).await?, &self.creator).await
+    }
+}
@@ -1,13 +1,37 @@
 //! This contains the health server, a basic server that for now always returns 200.
 //! This is necessary to run the processor in Cloud Run, which expects to be able to
 //! query a HTTP server to check for liveness.
+//!
+//! It also streams the event feed, see [`crate::event_feed`], over `/events/ws` and
+//! `/events/sse`. Both accept the `token`, `user`, `event_type`, `from_version` and
+//! `from_event_index` query parameters. When enabled, the read api in [`crate::query_api`]
+//! and the graphql api in [`crate::graphql_api`] are served too.
 
 use anyhow::{Context, Result};
+use futures_util::{SinkExt, StreamExt};
 use poem::{
-    get, handler, http::Method, listener::TcpListener, middleware::Cors, EndpointExt, Route, Server,
+    get, handler,
+    http::{HeaderMap, Method, StatusCode},
+    listener::TcpListener,
+    middleware::Cors,
+    web::{
+        sse::{Event, SSE},
+        websocket::{Message, WebSocket},
+        Query,
+    },
+    EndpointExt, IntoResponse, Route, Server,
 };
 use serde::{Deserialize, Serialize};
-use std::net::{Ipv4Addr, SocketAddrV4};
+use std::{
+    net::{Ipv4Addr, SocketAddrV4},
+    time::Duration,
+};
+
+use crate::{
+    event_feed::{FeedFilter, EVENT_FEED},
+    graphql_api::{self, IndexerSchema},
+    query_api::{self, QueryApiState},
+};
 
 /// This configures the health server.
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -24,10 +48,23 @@ impl Default for HealthServerConfig {
     }
 }
 
-pub async fn run(config: HealthServerConfig) -> Result<()> {
+pub async fn run(
+    config: HealthServerConfig,
+    query_api: Option<QueryApiState>,
+    graphql_schema: Option<IndexerSchema>,
+) -> Result<()> {
     tracing::info!("Health server starting at {}", config.listen_address);
     let cors = Cors::new().allow_methods(vec![Method::GET, Method::POST]);
-    let route = Route::new().nest("/", get(root)).with(cors);
+    let mut route = Route::new()
+        .at("/events/ws", get(events_ws))
+        .at("/events/sse", get(events_sse));
+    if let Some(query_api) = query_api {
+        route = query_api::add_routes(route, query_api);
+    }
+    if let Some(graphql_schema) = graphql_schema {
+        route = graphql_api::add_routes(route, graphql_schema);
+    }
+    let route = route.nest("/", get(root)).with(cors);
     Server::new(TcpListener::bind(config.listen_address))
         .name("health-server")
         .run(route)
@@ -39,3 +76,77 @@ pub async fn run(config: HealthServerConfig) -> Result<()> {
 async fn root() -> String {
     "Hello from the root!!".to_string()
 }
+
+#[handler]
+async fn events_ws(Query(filter): Query<FeedFilter>, ws: WebSocket) -> impl IntoResponse {
+    ws.on_upgrade(move |socket| async move {
+        let (mut sink, mut incoming) = socket.split();
+        let mut events = match EVENT_FEED.subscribe(filter) {
+            Ok(events) => Box::pin(events),
+            Err(e) => {
+                let error = serde_json::json!({ "error": e.to_string() }).to_string();
+                let _ = sink.send(Message::Text(error)).await;
+                let _ = sink.close().await;
+                return;
+            }
+        };
+        loop {
+            tokio::select! {
+                event = events.next() => {
+                    let text = match event {
+                        Some(Ok(event)) => {
+                            serde_json::to_string(event.as_ref()).unwrap_or_default()
+                        }
+                        Some(Err(e)) => {
+                            let error = serde_json::json!({ "error": e.to_string() }).to_string();
+                            let _ = sink.send(Message::Text(error)).await;
+                            let _ = sink.close().await;
+                            break;
+                        }
+                        None => break,
+                    };
+                    if sink.send(Message::Text(text)).await.is_err() {
+                        break;
+                    }
+                }
+                message = incoming.next() => {
+                    match message {
+                        Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                        _ => {}
+                    }
+                }
+            }
+        }
+    })
+}
+
+#[handler]
+async fn events_sse(
+    Query(mut filter): Query<FeedFilter>,
+    headers: &HeaderMap,
+) -> poem::Result<SSE> {
+    // EventSource sends the id of the last event it got when reconnecting, `version:index`
+    if filter.from_version.is_none() {
+        let last_event_id = headers
+            .get("Last-Event-ID")
+            .and_then(|id| id.to_str().ok())
+            .and_then(|id| id.split_once(':'))
+            .and_then(|(version, index)| Some((version.parse().ok()?, index.parse().ok()?)));
+        if let Some((from_version, from_event_index)) = last_event_id {
+            filter.from_version = Some(from_version);
+            filter.from_event_index = Some(from_event_index);
+        }
+    }
+    let events = EVENT_FEED
+        .subscribe(filter)
+        .map_err(|e| poem::Error::from_string(e.to_string(), StatusCode::GONE))?;
+    // A lagged client gets a lagged event, EventSource then reconnects from its last id
+    Ok(SSE::new(events.map(|event| match event {
+        Ok(event) => Event::message(serde_json::to_string(event.as_ref()).unwrap_or_default())
+            .event_type(event.event_type.clone())
+            .id(format!("{}:{}", event.txn_version, event.event_index)),
+        Err(e) => Event::message(serde_json::json!({ "error": e.to_string() }).to_string())
+            .event_type("lagged"),
+    }))
+    .keep_alive(Duration::from_secs(15)))
+}
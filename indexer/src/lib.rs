@@ -1,6 +1,9 @@
 pub mod config;
 pub mod db_models;
+pub mod event_feed;
+pub mod graphql_api;
 pub mod health_check_server;
+pub mod query_api;
 pub mod steps;
 pub mod utils;
 pub mod api_client;
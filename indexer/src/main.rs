@@ -4,19 +4,49 @@ use clap::Parser;
 use indexer::{
     config::indexer_processor_config::IndexerProcessorConfig,
     health_check_server::{self, HealthServerConfig},
+    graphql_api::build_schema,
+    query_api::QueryApiState,
+    utils::database_connection::new_db_pool,
 };
 
 #[cfg(unix)]
 #[global_allocator]
 static ALLOC: jemallocator::Jemalloc = jemallocator::Jemalloc;
 
-async fn run_health_server() -> Result<()> {
-    health_check_server::run(HealthServerConfig::default()).await
+async fn run_health_server(args: &ServerArgs) -> Result<()> {
+    let config = IndexerProcessorConfig::load(&args.config_path)?;
+    // The indexer validates the rest of the config, the apis only read the database
+    if config.api_config.enabled || config.graphql_config.enabled {
+        config.validate_db()?;
+    }
+    let query_api = if config.api_config.enabled {
+        let pool = new_db_pool(
+            &config.db_config.postgres_connection_string,
+            config.api_config.db_pool_size,
+        )
+        .await;
+        Some(QueryApiState {
+            pool,
+            config: config.api_config,
+        })
+    } else {
+        None
+    };
+    let graphql_schema = if config.graphql_config.enabled {
+        let pool = new_db_pool(
+            &config.db_config.postgres_connection_string,
+            config.graphql_config.db_pool_size,
+        )
+        .await;
+        Some(build_schema(pool, config.graphql_config))
+    } else {
+        None
+    };
+    health_check_server::run(HealthServerConfig::default(), query_api, graphql_schema).await
 }
 
-async fn run_indexer() -> Result<()> {
-    ServerArgs::parse()
-        .run::<IndexerProcessorConfig>(tokio::runtime::Handle::current())
+async fn run_indexer(args: &ServerArgs) -> Result<()> {
+    args.run::<IndexerProcessorConfig>(tokio::runtime::Handle::current())
         .await
 }
 
@@ -32,7 +62,8 @@ fn main() -> Result<()> {
         .build()
         .unwrap()
         .block_on(async {
-            tokio::try_join!(run_health_server(), run_indexer())?;
+            let args = ServerArgs::parse();
+            tokio::try_join!(run_health_server(&args), run_indexer(&args))?;
             Ok(())
         })
 }
@@ -0,0 +1,662 @@
+//! Read only api over the indexed tables, served by the health server when
+//! `api_config.enabled` is set. Lists are ordered from the newest txn_version down and
+//! paginated with `?cursor=<next_cursor>&limit=<n>`. Lists of rows that can share a version
+//! (holders, positions, stakings) use `<version>:<key>` cursors, the key breaking ties.
+
+use ahash::AHashMap;
+use aptos_indexer_processor_sdk::utils::convert::standardize_address;
+use bigdecimal::BigDecimal;
+use diesel::{BoolExpressionMethods, ExpressionMethods, QueryDsl, Queryable};
+use diesel_async::RunQueryDsl;
+use poem::{
+    error::{InternalServerError, NotFoundError},
+    get, handler,
+    http::StatusCode,
+    web::{Data, Json, Path, Query},
+    EndpointExt, Route,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::indexer_processor_config::ApiConfig,
+    db_models::{
+        address_labels::AddressLabel, creators::Creator, positions::Position,
+        token_metrics::TokenMetrics, tokens::Token, trades::Trade,
+        trending_tokens::TrendingToken,
+    },
+    schema::{
+        address_labels, creators, positions, stakings, token_metrics, tokens, trades,
+        trending_tokens,
+    },
+    utils::{
+        address_labels::{labels_of, load_address_labels},
+        database_connection::get_db_connection,
+        database_utils::{ArcDbPool, DbPoolConnection},
+        whitelist_report::{load_whitelist_report, WhitelistReport},
+    },
+};
+
+#[derive(Clone)]
+pub struct QueryApiState {
+    pub pool: ArcDbPool,
+    pub config: ApiConfig,
+}
+
+impl QueryApiState {
+    async fn conn(&self) -> poem::Result<DbPoolConnection<'_>> {
+        get_db_connection(&self.pool)
+            .await
+            .map_err(InternalServerError)
+    }
+
+    fn limit(&self, page: &PageQuery) -> i64 {
+        page.limit
+            .unwrap_or(self.config.default_page_size)
+            .clamp(1, self.config.max_page_size)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PageQuery {
+    /// `next_cursor` of the previous page, only versions below it are returned
+    pub cursor: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KeyedPageQuery {
+    /// `next_cursor` of the previous page, only rows below it are returned
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+impl KeyedPageQuery {
+    fn page(&self) -> PageQuery {
+        PageQuery {
+            cursor: None,
+            limit: self.limit,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Page<T, C = i64> {
+    pub data: Vec<T>,
+    /// Set when there may be more rows
+    pub next_cursor: Option<C>,
+}
+
+impl<T, C> Page<T, C> {
+    fn new(data: Vec<T>, limit: i64, cursor: impl Fn(&T) -> C) -> Self {
+        let next_cursor = if data.len() as i64 == limit {
+            data.last().map(cursor)
+        } else {
+            None
+        };
+        Self { data, next_cursor }
+    }
+}
+
+/// Position of a row in a list ordered by a version several rows can share, then by a key
+/// unique among them.
+#[derive(Debug)]
+pub struct KeyCursor {
+    pub version: i64,
+    pub key: String,
+}
+
+impl KeyCursor {
+    fn parse(cursor: &str) -> poem::Result<Self> {
+        cursor
+            .split_once(':')
+            .and_then(|(version, key)| {
+                Some(Self {
+                    version: version.parse().ok()?,
+                    key: key.to_string(),
+                })
+            })
+            .ok_or_else(|| {
+                poem::Error::from_string(
+                    format!("Invalid cursor {}, expected <version>:<key>", cursor),
+                    StatusCode::BAD_REQUEST,
+                )
+            })
+    }
+
+    fn format(version: i64, key: &str) -> String {
+        format!("{}:{}", version, key)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StakingQuery {
+    pub user: Option<String>,
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LabelQuery {
+    pub category: Option<String>,
+    pub cluster: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenMetricsSort {
+    #[default]
+    MarketCap,
+    Fdv,
+    Liquidity,
+    #[serde(rename = "volume_24h")]
+    Volume24h,
+    #[serde(rename = "price_change_24h")]
+    PriceChange24h,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TopTokensQuery {
+    pub sort: Option<TokenMetricsSort>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Queryable, Serialize)]
+pub struct StakingRow {
+    pub position_addr: String,
+    pub stake_addr: String,
+    pub user: String,
+    pub amount: i64,
+    pub unlock_ts: i64,
+    pub txn_version: i64,
+    pub is_removed: bool,
+    pub claimed: Option<i64>,
+    pub contract_address: String,
+    pub ts: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StakingResponse {
+    #[serde(flatten)]
+    pub staking: StakingRow,
+    pub user_labels: Vec<AddressLabel>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreatorResponse {
+    #[serde(flatten)]
+    pub creator: Creator,
+    pub labels: Vec<AddressLabel>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TokenResponse {
+    #[serde(flatten)]
+    pub token: Token,
+    pub creator_labels: Vec<AddressLabel>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TradeResponse {
+    #[serde(flatten)]
+    pub trade: Trade,
+    pub user_labels: Vec<AddressLabel>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HolderResponse {
+    #[serde(flatten)]
+    pub position: Position,
+    pub user_labels: Vec<AddressLabel>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PositionResponse {
+    #[serde(flatten)]
+    pub position: Position,
+    /// Marked at the reserves of the latest trade on the token
+    pub unrealized_pnl: Option<BigDecimal>,
+    pub user_labels: Vec<AddressLabel>,
+}
+
+pub fn add_routes(route: Route, state: QueryApiState) -> Route {
+    route
+        .at("/tokens", get(list_tokens).data(state.clone()))
+        .at("/tokens/:addr", get(get_token).data(state.clone()))
+        .at("/tokens/:addr/trades", get(list_token_trades).data(state.clone()))
+        .at("/tokens/:addr/holders", get(list_token_holders).data(state.clone()))
+        .at("/tokens/:addr/whitelist", get(get_whitelist_report).data(state.clone()))
+        .at("/tokens/:addr/metrics", get(list_token_metrics).data(state.clone()))
+        .at("/metrics/top", get(list_top_tokens).data(state.clone()))
+        .at("/trending", get(list_trending_tokens).data(state.clone()))
+        .at("/accounts/:addr/positions", get(list_account_positions).data(state.clone()))
+        .at("/creators/:addr", get(get_creator).data(state.clone()))
+        .at("/stakings", get(list_stakings).data(state.clone()))
+        .at("/labels", get(list_labels).data(state.clone()))
+        .at("/labels/:addr", get(get_address_labels).data(state))
+}
+
+#[handler]
+async fn list_tokens(
+    Data(state): Data<&QueryApiState>,
+    Query(page): Query<PageQuery>,
+) -> poem::Result<Json<Page<TokenResponse>>> {
+    let limit = state.limit(&page);
+    let conn = &mut state.conn().await?;
+    let mut query = tokens::table.into_boxed();
+    if let Some(cursor) = page.cursor {
+        query = query.filter(tokens::txn_version.lt(cursor));
+    }
+    let rows = query
+        .order(tokens::txn_version.desc())
+        .limit(limit)
+        .load::<Token>(conn)
+        .await
+        .map_err(InternalServerError)?;
+    let creators = rows
+        .iter()
+        .map(|token| token.created_by.clone())
+        .collect::<Vec<String>>();
+    let labels = load_address_labels(conn, &creators)
+        .await
+        .map_err(InternalServerError)?;
+    let data = rows
+        .into_iter()
+        .map(|token| TokenResponse {
+            creator_labels: labels_of(&labels, &token.created_by),
+            token,
+        })
+        .collect();
+    Ok(Json(Page::new(data, limit, |t| t.token.txn_version)))
+}
+
+/// Looks a token up by its pre, main or pool address.
+#[handler]
+async fn get_token(
+    Data(state): Data<&QueryApiState>,
+    Path(addr): Path<String>,
+) -> poem::Result<Json<TokenResponse>> {
+    let addr = standardize_address(&addr);
+    let conn = &mut state.conn().await?;
+    let token = tokens::table
+        .filter(
+            tokens::pre_addr
+                .eq(&addr)
+                .or(tokens::main_addr.eq(&addr))
+                .or(tokens::pool_addr.eq(&addr)),
+        )
+        .first::<Token>(conn)
+        .await
+        .map_err(|e| match e {
+            diesel::result::Error::NotFound => NotFoundError.into(),
+            e => InternalServerError(e),
+        })?;
+    let creator_labels = load_address_labels(conn, std::slice::from_ref(&token.created_by))
+        .await
+        .map_err(InternalServerError)?;
+    Ok(Json(TokenResponse {
+        token,
+        creator_labels,
+    }))
+}
+
+#[handler]
+async fn list_token_trades(
+    Data(state): Data<&QueryApiState>,
+    Path(addr): Path<String>,
+    Query(page): Query<PageQuery>,
+) -> poem::Result<Json<Page<TradeResponse>>> {
+    let limit = state.limit(&page);
+    let conn = &mut state.conn().await?;
+    let mut query = trades::table
+        .filter(trades::token_address.eq(standardize_address(&addr)))
+        .into_boxed();
+    if let Some(cursor) = page.cursor {
+        query = query.filter(trades::txn_version.lt(cursor));
+    }
+    let rows = query
+        .order(trades::txn_version.desc())
+        .limit(limit)
+        .load::<Trade>(conn)
+        .await
+        .map_err(InternalServerError)?;
+    let users = rows
+        .iter()
+        .map(|trade| trade.user_addr.clone())
+        .collect::<Vec<String>>();
+    let labels = load_address_labels(conn, &users)
+        .await
+        .map_err(InternalServerError)?;
+    let data = rows
+        .into_iter()
+        .map(|trade| TradeResponse {
+            user_labels: labels_of(&labels, &trade.user_addr),
+            trade,
+        })
+        .collect();
+    Ok(Json(Page::new(data, limit, |t| t.trade.txn_version)))
+}
+
+/// Positions with a balance left in the token, paginated on the version of their last trade.
+#[handler]
+async fn list_token_holders(
+    Data(state): Data<&QueryApiState>,
+    Path(addr): Path<String>,
+    Query(page): Query<KeyedPageQuery>,
+) -> poem::Result<Json<Page<HolderResponse, String>>> {
+    let limit = state.limit(&page.page());
+    let conn = &mut state.conn().await?;
+    let mut query = positions::table
+        .filter(positions::token_address.eq(standardize_address(&addr)))
+        .filter(positions::balance.gt(BigDecimal::from(0)))
+        .into_boxed();
+    if let Some(cursor) = page.cursor.as_deref() {
+        let cursor = KeyCursor::parse(cursor)?;
+        query = query.filter(
+            positions::last_txn_version.lt(cursor.version).or(positions::last_txn_version
+                .eq(cursor.version)
+                .and(positions::user_addr.lt(cursor.key))),
+        );
+    }
+    let rows = query
+        .order((positions::last_txn_version.desc(), positions::user_addr.desc()))
+        .limit(limit)
+        .load::<Position>(conn)
+        .await
+        .map_err(InternalServerError)?;
+    let users = rows
+        .iter()
+        .map(|position| position.user_addr.clone())
+        .collect::<Vec<String>>();
+    let labels = load_address_labels(conn, &users)
+        .await
+        .map_err(InternalServerError)?;
+    let data = rows
+        .into_iter()
+        .map(|position| HolderResponse {
+            user_labels: labels_of(&labels, &position.user_addr),
+            position,
+        })
+        .collect();
+    Ok(Json(Page::new(data, limit, |h| {
+        KeyCursor::format(h.position.last_txn_version, &h.position.user_addr)
+    })))
+}
+
+/// Supply taken in the whitelist window of a token by its pre address.
+#[handler]
+async fn get_whitelist_report(
+    Data(state): Data<&QueryApiState>,
+    Path(addr): Path<String>,
+) -> poem::Result<Json<WhitelistReport>> {
+    let conn = &mut state.conn().await?;
+    let report = load_whitelist_report(conn, &standardize_address(&addr))
+        .await
+        .map_err(InternalServerError)?
+        .ok_or(NotFoundError)?;
+    Ok(Json(report))
+}
+
+/// Market snapshots of a token by its pre address, paginated on the snapshot timestamp.
+#[handler]
+async fn list_token_metrics(
+    Data(state): Data<&QueryApiState>,
+    Path(addr): Path<String>,
+    Query(page): Query<PageQuery>,
+) -> poem::Result<Json<Page<TokenMetrics>>> {
+    let limit = state.limit(&page);
+    let mut query = token_metrics::table
+        .filter(token_metrics::token_address.eq(standardize_address(&addr)))
+        .into_boxed();
+    if let Some(cursor) = page.cursor {
+        query = query.filter(token_metrics::snapshot_ts.lt(cursor));
+    }
+    let data = query
+        .order(token_metrics::snapshot_ts.desc())
+        .limit(limit)
+        .load::<TokenMetrics>(&mut state.conn().await?)
+        .await
+        .map_err(InternalServerError)?;
+    Ok(Json(Page::new(data, limit, |metrics| metrics.snapshot_ts)))
+}
+
+/// Top tokens of the latest snapshot, by market cap unless sorted otherwise.
+#[handler]
+async fn list_top_tokens(
+    Data(state): Data<&QueryApiState>,
+    Query(query_params): Query<TopTokensQuery>,
+) -> poem::Result<Json<Vec<TokenMetrics>>> {
+    let limit = state.limit(&PageQuery {
+        cursor: None,
+        limit: query_params.limit,
+    });
+    let conn = &mut state.conn().await?;
+    let Some(snapshot_ts) = token_metrics::table
+        .select(diesel::dsl::max(token_metrics::snapshot_ts))
+        .first::<Option<i64>>(conn)
+        .await
+        .map_err(InternalServerError)?
+    else {
+        return Ok(Json(vec![]));
+    };
+    let query = token_metrics::table
+        .filter(token_metrics::snapshot_ts.eq(snapshot_ts))
+        .into_boxed();
+    let query = match query_params.sort.unwrap_or_default() {
+        TokenMetricsSort::MarketCap => query.order(token_metrics::market_cap_apt.desc()),
+        TokenMetricsSort::Fdv => query.order(token_metrics::fdv_apt.desc()),
+        TokenMetricsSort::Liquidity => query.order(token_metrics::liquidity_apt.desc()),
+        TokenMetricsSort::Volume24h => query.order(token_metrics::volume_24h_apt.desc()),
+        TokenMetricsSort::PriceChange24h => query.order(token_metrics::price_change_24h.desc()),
+    };
+    let data = query
+        .then_order_by(token_metrics::token_address)
+        .limit(limit)
+        .load::<TokenMetrics>(conn)
+        .await
+        .map_err(InternalServerError)?;
+    Ok(Json(data))
+}
+
+/// Trending tokens of the latest refresh, from rank 1.
+#[handler]
+async fn list_trending_tokens(
+    Data(state): Data<&QueryApiState>,
+    Query(page): Query<PageQuery>,
+) -> poem::Result<Json<Page<TrendingToken>>> {
+    let limit = state.limit(&page);
+    let mut query = trending_tokens::table.into_boxed();
+    if let Some(cursor) = page.cursor {
+        query = query.filter(trending_tokens::rank.gt(cursor as i32));
+    }
+    let data = query
+        .order(trending_tokens::rank)
+        .limit(limit)
+        .load::<TrendingToken>(&mut state.conn().await?)
+        .await
+        .map_err(InternalServerError)?;
+    Ok(Json(Page::new(data, limit, |token| token.rank as i64)))
+}
+
+#[handler]
+async fn list_account_positions(
+    Data(state): Data<&QueryApiState>,
+    Path(addr): Path<String>,
+    Query(page): Query<KeyedPageQuery>,
+) -> poem::Result<Json<Page<PositionResponse, String>>> {
+    let limit = state.limit(&page.page());
+    let addr = standardize_address(&addr);
+    let conn = &mut state.conn().await?;
+    let mut query = positions::table
+        .filter(positions::user_addr.eq(&addr))
+        .into_boxed();
+    if let Some(cursor) = page.cursor.as_deref() {
+        let cursor = KeyCursor::parse(cursor)?;
+        query = query.filter(
+            positions::last_txn_version.lt(cursor.version).or(positions::last_txn_version
+                .eq(cursor.version)
+                .and(positions::token_address.lt(cursor.key))),
+        );
+    }
+    let rows = query
+        .order((positions::last_txn_version.desc(), positions::token_address.desc()))
+        .limit(limit)
+        .load::<Position>(conn)
+        .await
+        .map_err(InternalServerError)?;
+    let user_labels = load_address_labels(conn, &[addr])
+        .await
+        .map_err(InternalServerError)?;
+    let token_addresses = rows
+        .iter()
+        .map(|position| position.token_address.clone())
+        .collect::<Vec<String>>();
+    // Reserves of the latest trade on each token
+    let reserves = trades::table
+        .filter(trades::token_address.eq_any(&token_addresses))
+        .distinct_on(trades::token_address)
+        .order((trades::token_address, trades::txn_version.desc()))
+        .select((
+            trades::token_address,
+            trades::virtual_aptos_reserves,
+            trades::virtual_token_reserves,
+        ))
+        .load::<(String, BigDecimal, BigDecimal)>(conn)
+        .await
+        .map_err(InternalServerError)?
+        .into_iter()
+        .map(|(token_address, aptos_reserves, token_reserves)| {
+            (token_address, (aptos_reserves, token_reserves))
+        })
+        .collect::<AHashMap<String, (BigDecimal, BigDecimal)>>();
+
+    let data = rows
+        .into_iter()
+        .map(|position| PositionResponse {
+            unrealized_pnl: reserves
+                .get(&position.token_address)
+                .map(|(aptos_reserves, token_reserves)| {
+                    position.unrealized_pnl(aptos_reserves, token_reserves)
+                }),
+            position,
+            user_labels: user_labels.clone(),
+        })
+        .collect();
+    Ok(Json(Page::new(data, limit, |p| {
+        KeyCursor::format(p.position.last_txn_version, &p.position.token_address)
+    })))
+}
+
+/// Launch history and trust badge of a token creator.
+#[handler]
+async fn get_creator(
+    Data(state): Data<&QueryApiState>,
+    Path(addr): Path<String>,
+) -> poem::Result<Json<CreatorResponse>> {
+    let conn = &mut state.conn().await?;
+    let creator = creators::table
+        .find(standardize_address(&addr))
+        .first::<Creator>(conn)
+        .await
+        .map_err(|e| match e {
+            diesel::result::Error::NotFound => NotFoundError.into(),
+            e => InternalServerError(e),
+        })?;
+    let labels = load_address_labels(conn, std::slice::from_ref(&creator.creator))
+        .await
+        .map_err(InternalServerError)?;
+    Ok(Json(CreatorResponse { creator, labels }))
+}
+
+#[handler]
+async fn list_stakings(
+    Data(state): Data<&QueryApiState>,
+    Query(query_params): Query<StakingQuery>,
+) -> poem::Result<Json<Page<StakingResponse, String>>> {
+    let limit = state.limit(&PageQuery {
+        cursor: None,
+        limit: query_params.limit,
+    });
+    let conn = &mut state.conn().await?;
+    let mut query = stakings::table.into_boxed();
+    if let Some(user) = query_params.user.as_ref() {
+        query = query.filter(stakings::user.eq(standardize_address(user)));
+    }
+    if let Some(cursor) = query_params.cursor.as_deref() {
+        let cursor = KeyCursor::parse(cursor)?;
+        query = query.filter(
+            stakings::txn_version.lt(cursor.version).or(stakings::txn_version
+                .eq(cursor.version)
+                .and(stakings::position_addr.lt(cursor.key))),
+        );
+    }
+    let rows = query
+        .order((stakings::txn_version.desc(), stakings::position_addr.desc()))
+        .limit(limit)
+        .load::<StakingRow>(conn)
+        .await
+        .map_err(InternalServerError)?;
+    let users = rows
+        .iter()
+        .map(|staking| staking.user.clone())
+        .collect::<Vec<String>>();
+    let labels = load_address_labels(conn, &users)
+        .await
+        .map_err(InternalServerError)?;
+    let data = rows
+        .into_iter()
+        .map(|staking| StakingResponse {
+            user_labels: labels_of(&labels, &staking.user),
+            staking,
+        })
+        .collect();
+    Ok(Json(Page::new(data, limit, |s| {
+        KeyCursor::format(s.staking.txn_version, &s.staking.position_addr)
+    })))
+}
+
+/// Labeled addresses of a category or cluster, in address order.
+#[handler]
+async fn list_labels(
+    Data(state): Data<&QueryApiState>,
+    Query(query_params): Query<LabelQuery>,
+) -> poem::Result<Json<Vec<AddressLabel>>> {
+    let limit = state.limit(&PageQuery {
+        cursor: None,
+        limit: query_params.limit,
+    });
+    // A file label hides the auto label of the same name
+    let mut query = address_labels::table
+        .distinct_on((address_labels::address, address_labels::label))
+        .into_boxed();
+    if let Some(category) = query_params.category {
+        query = query.filter(address_labels::category.eq(category));
+    }
+    if let Some(cluster) = query_params.cluster {
+        query = query.filter(address_labels::cluster.eq(cluster));
+    }
+    let data = query
+        .order((
+            address_labels::address,
+            address_labels::label,
+            address_labels::source.desc(),
+        ))
+        .limit(limit)
+        .load::<AddressLabel>(&mut state.conn().await?)
+        .await
+        .map_err(InternalServerError)?;
+    Ok(Json(data))
+}
+
+/// Labels of an address, empty when it isn't known.
+#[handler]
+async fn get_address_labels(
+    Data(state): Data<&QueryApiState>,
+    Path(addr): Path<String>,
+) -> poem::Result<Json<Vec<AddressLabel>>> {
+    let conn = &mut state.conn().await?;
+    let labels = load_address_labels(conn, &[standardize_address(&addr)])
+        .await
+        .map_err(InternalServerError)?;
+    Ok(Json(labels))
+}
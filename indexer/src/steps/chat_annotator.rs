@@ -0,0 +1,228 @@
+use ahash::AHashMap;
+use anyhow::Result;
+use aptos_indexer_processor_sdk::{
+    traits::{NamedStep, PollableAsyncRunType, PollableAsyncStep, Processable},
+    types::transaction_context::TransactionContext,
+    utils::errors::ProcessorError,
+};
+use async_trait::async_trait;
+use diesel::{insert_into, ExpressionMethods, QueryDsl, QueryResult};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use std::marker::PhantomData;
+
+use crate::{
+    config::indexer_processor_config::ChatContextConfig,
+    db_models::chat_author_context::{AuthorTrade, ChatAuthorContext, ChatMessage},
+    schema::{chat_author_context, chats, tokens, trades},
+    utils::{database_connection::get_db_connection, database_utils::ArcDbPool},
+};
+
+/// Chat messages annotated per query
+const CHATS_PER_QUERY: i64 = 1000;
+
+/// ChatAnnotator is a pass-through step that periodically records the on-chain position of
+/// the authors of new chat messages into `chat_author_context`. A message is annotated once
+/// the indexer has processed its timestamp, so its author's trades up to it are stored.
+pub struct ChatAnnotator<T>
+where
+    Self: Sized + Send + 'static,
+    T: Send + 'static,
+{
+    pool: ArcDbPool,
+    config: ChatContextConfig,
+    // Chain timestamp of the latest batch seen, later messages wait for the next batches
+    latest_ts: Option<i64>,
+    _marker: PhantomData<T>,
+}
+
+impl<T> ChatAnnotator<T>
+where
+    Self: Sized + Send + 'static,
+    T: Send + 'static,
+{
+    pub fn new(pool: ArcDbPool, config: ChatContextConfig) -> Self {
+        Self {
+            pool,
+            config,
+            latest_ts: None,
+            _marker: PhantomData,
+        }
+    }
+
+    async fn annotate_chats(&mut self) -> Result<(), ProcessorError> {
+        let conn = &mut get_db_connection(&self.pool).await?;
+        let now = match self.latest_ts {
+            Some(ts) => ts,
+            None => match trades::table
+                .select(diesel::dsl::max(trades::ts))
+                .first::<Option<i64>>(conn)
+                .await
+                .map_err(db_error)?
+            {
+                Some(ts) => ts,
+                None => return Ok(()),
+            },
+        };
+        let Some(processed_until) = chrono::DateTime::from_timestamp(now, 0) else {
+            return Ok(());
+        };
+        let processed_until = processed_until.naive_utc();
+
+        let mut count = 0;
+        loop {
+            let messages = chats::table
+                .filter(chats::timestamp.le(processed_until))
+                .filter(
+                    chats::id
+                        .ne_all(chat_author_context::table.select(chat_author_context::chat_id)),
+                )
+                .order(chats::timestamp)
+                .limit(CHATS_PER_QUERY)
+                .select((
+                    chats::id,
+                    chats::address,
+                    chats::token_address,
+                    chats::timestamp,
+                ))
+                .load::<ChatMessage>(conn)
+                .await
+                .map_err(db_error)?;
+            let contexts = get_author_contexts(conn, &messages)
+                .await
+                .map_err(db_error)?;
+            count += insert_into(chat_author_context::table)
+                .values(contexts)
+                .on_conflict(chat_author_context::chat_id)
+                .do_nothing()
+                .execute(conn)
+                .await
+                .map_err(db_error)?;
+            if (messages.len() as i64) < CHATS_PER_QUERY {
+                break;
+            }
+        }
+        if count > 0 {
+            tracing::info!("Annotated {} chat messages", count);
+        }
+        Ok(())
+    }
+}
+
+fn db_error(e: diesel::result::Error) -> ProcessorError {
+    tracing::warn!("Error running query: {:?}", e);
+    ProcessorError::ProcessError {
+        message: e.to_string(),
+    }
+}
+
+/// Context of the authors of `messages`, from their trades on the chats' tokens.
+async fn get_author_contexts(
+    conn: &mut AsyncPgConnection,
+    messages: &[ChatMessage],
+) -> QueryResult<Vec<ChatAuthorContext>> {
+    let Some(last_ts) = messages.iter().map(ChatMessage::message_ts).max() else {
+        return Ok(vec![]);
+    };
+    let authors = messages
+        .iter()
+        .map(ChatMessage::author_address)
+        .collect::<Vec<String>>();
+    let token_addresses = messages
+        .iter()
+        .map(ChatMessage::chat_token_address)
+        .collect::<Vec<String>>();
+    let creators = tokens::table
+        .filter(tokens::pre_addr.eq_any(&token_addresses))
+        .select((tokens::pre_addr, tokens::created_by))
+        .load::<(String, String)>(conn)
+        .await?
+        .into_iter()
+        .collect::<AHashMap<String, String>>();
+    let mut author_trades: AHashMap<(String, String), Vec<AuthorTrade>> = AHashMap::new();
+    for trade in trades::table
+        .filter(trades::token_address.eq_any(&token_addresses))
+        .filter(trades::user_addr.eq_any(&authors))
+        .filter(trades::ts.le(last_ts))
+        .select((
+            trades::token_address,
+            trades::user_addr,
+            trades::is_buy,
+            trades::token_amount,
+            trades::ts,
+        ))
+        .load::<AuthorTrade>(conn)
+        .await?
+    {
+        author_trades
+            .entry((trade.token_address.clone(), trade.user_addr.clone()))
+            .or_default()
+            .push(trade);
+    }
+
+    Ok(messages
+        .iter()
+        .map(|message| {
+            let token_address = message.chat_token_address();
+            let trades = author_trades
+                .get(&(token_address.clone(), message.author_address()))
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            ChatAuthorContext::new(
+                message,
+                creators.get(&token_address).map(String::as_str),
+                trades,
+            )
+        })
+        .collect())
+}
+
+#[async_trait]
+impl<T> Processable for ChatAnnotator<T>
+where
+    Self: Sized + Send + 'static,
+    T: Send + 'static,
+{
+    type Input = T;
+    type Output = T;
+    type RunType = PollableAsyncRunType;
+
+    async fn process(
+        &mut self,
+        current_batch: TransactionContext<T>,
+    ) -> Result<Option<TransactionContext<T>>, ProcessorError> {
+        if let Some(ts) = current_batch.metadata.end_transaction_timestamp.as_ref() {
+            self.latest_ts = Some(self.latest_ts.unwrap_or(0).max(ts.seconds));
+        }
+        // Pass through
+        Ok(Some(current_batch))
+    }
+}
+
+#[async_trait]
+impl<T: Send + 'static> PollableAsyncStep for ChatAnnotator<T>
+where
+    Self: Sized + Send + Sync + 'static,
+    T: Send + 'static,
+{
+    fn poll_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.config.poll_interval_secs)
+    }
+
+    async fn poll(&mut self) -> Result<Option<Vec<TransactionContext<T>>>, ProcessorError> {
+        if self.config.enabled {
+            self.annotate_chats().await?;
+        }
+        // Nothing should be returned
+        Ok(None)
+    }
+}
+
+impl<T> NamedStep for ChatAnnotator<T>
+where
+    Self: Sized + Send + 'static,
+    T: Send + 'static,
+{
+    fn name(&self) -> String {
+        format!("ChatAnnotator: {}", std::any::type_name::<T>())
+    }
+}
@@ -2,8 +2,9 @@ use ahash::AHashMap;
 use anyhow::Result;
 use aptos_indexer_processor_sdk::{
     aptos_protos::transaction::v1::{
-        transaction::TxnData, write_set_change::Change, Event as EventPB, MoveModuleBytecode,
-        Transaction, WriteSetChange,
+        transaction::TxnData, transaction_payload::Payload, write_set_change::Change,
+        EntryFunctionPayload, Event as EventPB, MoveModuleBytecode, Transaction,
+        UserTransactionRequest, WriteSetChange,
     },
     traits::{async_step::AsyncRunType, AsyncStep, NamedStep, Processable},
     types::transaction_context::TransactionContext,
@@ -12,8 +13,24 @@ use aptos_indexer_processor_sdk::{
 use async_trait::async_trait;
 use rayon::prelude::*;
 
+use crate::event_feed::FeedEvent;
+use crate::config::indexer_processor_config::{
+    AddressLabelsConfig, ContractEntry, PriceConfig, PriceSource, RawEventsConfig,
+    TradeFlagsConfig,
+};
 use crate::db_models::{
-    accounts::{Spin, SpinEventOnChain}, module_upgrade::ModuleUpgrade, package_upgrade::{PackageUpgrade, PackageUpgradeChangeOnChain}, stakings::{PositionCreatedOnChain, RewardClaimed, RewardClaimedOnChain, Staking, StakingRemoved, StakingRemovedOnChain}, tokens::{PoolCompleted, PoolCompletedOnChain, Token, TokenCreatedOnChain}, trades::{Trade, TradeCreatedOnChain}
+    address_labels::{
+        AddressLabel, FeeWalletConfigOnChain, GameInitEventOnChain, ObjectCoreOnChain,
+        CATEGORY_CONFIG, CATEGORY_FEE_WALLET, CATEGORY_GAME, CATEGORY_POOL, CATEGORY_THALA_POOL,
+    },
+    apt_usd_prices::AptUsdPrice,
+    contract_transactions::ContractTransactionOnChain,
+    failed_transactions::FailedTransaction,
+    money_configs::{FeeConfigOnChain, MoneyConfig},
+    trade_flags::AptTransfer,
+    whitelist_windows::{WhitelistConfigOnChain, WhitelistWindow},
+    raw_contract_events::{RawContractEventOnChain, RAW_KIND_EVENT, RAW_KIND_RESOURCE},
+    accounts::{Spin, SpinEventOnChain}, module_upgrade::ModuleUpgrade, package_upgrade::{PackageDependency, PackageUpgrade, PackageUpgradeChangeOnChain, DEPLOYMENT_ACCOUNT, DEPLOYMENT_OBJECT}, stakings::{PositionCreatedOnChain, RewardClaimed, RewardClaimedOnChain, Staking, StakingRemoved, StakingRemovedOnChain}, tokens::{PoolCompleted, PoolCompletedOnChain, Token, TokenCreatedOnChain}, trades::{Trade, TradeCreatedOnChain}
 };
 
 /// Extractor is a step that extracts events and their metadata from transactions.
@@ -21,12 +38,28 @@ pub struct Extractor
 where
     Self: Sized + Send + 'static,
 {
-    contract_address: String,
+    contracts: Vec<ContractEntry>,
+    raw_events_config: RawEventsConfig,
+    trade_flags_config: TradeFlagsConfig,
+    address_labels_config: AddressLabelsConfig,
+    price_config: PriceConfig,
 }
 
 impl Extractor {
-    pub fn new(contract_address: String) -> Self {
-        Self { contract_address }
+    pub fn new(
+        contracts: Vec<ContractEntry>,
+        raw_events_config: RawEventsConfig,
+        trade_flags_config: TradeFlagsConfig,
+        address_labels_config: AddressLabelsConfig,
+        price_config: PriceConfig,
+    ) -> Self {
+        Self {
+            contracts,
+            raw_events_config,
+            trade_flags_config,
+            address_labels_config,
+            price_config,
+        }
     }
 }
 
@@ -48,25 +81,25 @@ impl Processable for Extractor {
         &mut self,
         item: TransactionContext<Vec<Transaction>>,
     ) -> Result<Option<TransactionContext<TransactionContextData>>, ProcessorError> {
-        let results: Vec<(Vec<ContractEvent>, Vec<ContractUpgradeChange>)> = item
+        let contract_addresses = self
+            .contracts
+            .iter()
+            .map(|contract| contract.address.clone())
+            .collect::<Vec<String>>();
+        let results: Vec<TransactionContextData> = item
             .data
             .par_iter()
             .map(|txn| {
+                let mut data = TransactionContextData::default();
                 let txn_version = txn.version as i64;
                 let txn_info = match txn.info.as_ref() {
-                    Some(info) => {
-                        if info.success {
-                            info
-                        } else {
-                            return (vec![], vec![]);
-                        }
-                    }
+                    Some(info) => info,
                     None => {
                         tracing::warn!(
                             transaction_version = txn_version,
                             "Transaction info doesn't exist"
                         );
-                        return (vec![], vec![]);
+                        return data;
                     }
                 };
                 let txn_data = match txn.txn_data.as_ref() {
@@ -76,57 +109,204 @@ impl Processable for Extractor {
                             transaction_version = txn_version,
                             "Transaction data doesn't exist"
                         );
-                        return (vec![], vec![]);
+                        return data;
                     }
                 };
+                let ts = txn.timestamp.as_ref().map(|ts| ts.seconds).unwrap_or_default();
+                if let Some((contract, request, payload)) =
+                    contract_entry_function(&self.contracts, txn_data, txn_version)
+                {
+                    data.contract_transactions.extend(ContractTransactionOnChain::new(
+                        txn_version,
+                        ts,
+                        contract,
+                        request,
+                        payload,
+                        txn_info,
+                    ));
+                    if !txn_info.success {
+                        data.failed_transactions.push(FailedTransaction::new(
+                            txn_version,
+                            ts,
+                            &contract.address,
+                            &contract_addresses,
+                            request,
+                            payload,
+                            txn_info,
+                        ));
+                    }
+                }
+                if !txn_info.success {
+                    return data;
+                }
+                // Any transaction may fund the wallets of a creator
+                if self.trade_flags_config.enabled {
+                    if let Some((request, payload)) = user_entry_function(txn_data) {
+                        data.apt_transfers =
+                            AptTransfer::from_payload(txn_version, ts, &request.sender, payload);
+                    }
+                }
                 let raw_events = match txn_data {
                     TxnData::BlockMetadata(tx_inner) => &tx_inner.events,
                     TxnData::Genesis(tx_inner) => &tx_inner.events,
                     TxnData::User(tx_inner) => &tx_inner.events,
                     _ => &vec![],
                 };
-                // let sender = match txn_data {
-                //     TxnData::User(tx_inner) => {
-                //         tx_inner.request.as_ref().map(|req| req.sender.clone())
-                //     }
-                //     _ => None,
-                // };
-                let txn_events = ContractEvent::from_events(
-                    self.contract_address.as_str(),
-                    raw_events,
+                // Any transaction may update the price feed
+                if self.price_config.source == PriceSource::Pyth {
+                    data.apt_usd_prices = AptUsdPrice::from_pyth_events(
+                        &self.price_config.pyth_address,
+                        &self.price_config.pyth_price_id,
+                        raw_events,
+                    );
+                }
+                let mut events =
+                    ContractEvent::from_events(&self.contracts, raw_events, txn_version);
+                for (_, event) in events.iter_mut() {
+                    match event {
+                        ContractEvent::TokenCreatedEvent(token) => {
+                            token.block_height = Some(txn.block_height as i64);
+                        }
+                        ContractEvent::TradeCreatedEvent(trade) => {
+                            trade.block_height = Some(txn.block_height as i64);
+                        }
+                        ContractEvent::PositionCreated(staking) => {
+                            staking.ts = Some(ts);
+                        }
+                        _ => {}
+                    }
+                }
+                data.feed_events = events
+                    .iter()
+                    .map(|(event_index, event)| {
+                        FeedEvent::from_contract_event(event, txn_version, *event_index)
+                    })
+                    .collect();
+                data.events = events.into_iter().map(|(_, event)| event).collect();
+                data.money_configs = MoneyConfig::from_transaction(
+                    &self.contracts,
+                    txn_info.changes.as_slice(),
                     txn_version,
                 );
-                let txn_changes = ContractUpgradeChange::from_changes(
-                    self.contract_address.as_str(),
-                    txn_version,
+                data.whitelist_windows = WhitelistWindow::from_transaction(
+                    &self.contracts,
+                    &data.events,
                     txn_info.changes.as_slice(),
+                    txn_version,
                 );
-
-                (txn_events, txn_changes)
+                if self.address_labels_config.auto_labels {
+                    data.address_labels = AddressLabel::from_transaction(
+                        &self.contracts,
+                        &data.events,
+                        raw_events,
+                        txn_info.changes.as_slice(),
+                        txn_version,
+                    );
+                }
+                data.changes = self
+                    .contracts
+                    .iter()
+                    .filter(|contract| contract.is_active(txn_version))
+                    .flat_map(|contract| {
+                        ContractUpgradeChange::from_changes(
+                            contract.address.as_str(),
+                            txn_version,
+                            txn_info.changes.as_slice(),
+                            raw_events,
+                        )
+                    })
+                    .collect();
+                if self.raw_events_config.enabled {
+                    data.raw_events = RawContractEventOnChain::from_transaction(
+                        &self.contracts,
+                        self.raw_events_config.include_resources,
+                        raw_events,
+                        txn_info.changes.as_slice(),
+                        txn_version,
+                    );
+                }
+                data
             })
-            .collect::<Vec<(Vec<ContractEvent>, Vec<ContractUpgradeChange>)>>();
-
-        let (events, changes): (Vec<ContractEvent>, Vec<ContractUpgradeChange>) =
-            results.into_iter().fold(
-                (Vec::new(), Vec::new()),
-                |(mut events_acc, mut changes_acc), (events, changes)| {
-                    events_acc.extend(events);
-                    changes_acc.extend(changes);
-                    (events_acc, changes_acc)
-                },
-            );
+            .collect();
+
+        let data = results.into_iter().fold(
+            TransactionContextData::default(),
+            |mut acc, data| {
+                acc.extend(data);
+                acc
+            },
+        );
 
         Ok(Some(TransactionContext {
-            data: TransactionContextData { events, changes },
+            data,
             metadata: item.metadata,
         }))
     }
 }
 
-#[derive(Debug, Clone)]
+/// Request and entry function payload of a user transaction.
+fn user_entry_function(
+    txn_data: &TxnData,
+) -> Option<(&UserTransactionRequest, &EntryFunctionPayload)> {
+    let TxnData::User(user_txn) = txn_data else {
+        return None;
+    };
+    let request = user_txn.request.as_ref()?;
+    match request
+        .payload
+        .as_ref()
+        .and_then(|payload| payload.payload.as_ref())
+    {
+        Some(Payload::EntryFunctionPayload(payload)) => Some((request, payload)),
+        _ => None,
+    }
+}
+
+/// The contract and entry function payload of a user transaction calling an indexed
+/// contract.
+fn contract_entry_function<'a>(
+    contracts: &'a [ContractEntry],
+    txn_data: &'a TxnData,
+    txn_version: i64,
+) -> Option<(&'a ContractEntry, &'a UserTransactionRequest, &'a EntryFunctionPayload)> {
+    let (request, payload) = user_entry_function(txn_data)?;
+    let address = standardize_address(&payload.function.as_ref()?.module.as_ref()?.address);
+    let contract = contracts
+        .iter()
+        .find(|contract| contract.address == address && contract.is_active(txn_version))?;
+    Some((contract, request, payload))
+}
+
+#[derive(Debug, Clone, Default)]
 pub struct TransactionContextData {
     pub events: Vec<ContractEvent>,
+    // The events as published on the event feed once stored
+    pub feed_events: Vec<FeedEvent>,
     pub changes: Vec<ContractUpgradeChange>,
+    pub raw_events: Vec<RawContractEventOnChain>,
+    pub failed_transactions: Vec<FailedTransaction>,
+    pub contract_transactions: Vec<ContractTransactionOnChain>,
+    pub whitelist_windows: Vec<WhitelistWindow>,
+    pub apt_transfers: Vec<AptTransfer>,
+    pub address_labels: Vec<AddressLabel>,
+    pub apt_usd_prices: Vec<AptUsdPrice>,
+    pub money_configs: Vec<MoneyConfig>,
+}
+
+impl TransactionContextData {
+    pub fn extend(&mut self, other: Self) {
+        self.events.extend(other.events);
+        self.feed_events.extend(other.feed_events);
+        self.changes.extend(other.changes);
+        self.raw_events.extend(other.raw_events);
+        self.failed_transactions.extend(other.failed_transactions);
+        self.contract_transactions.extend(other.contract_transactions);
+        self.whitelist_windows.extend(other.whitelist_windows);
+        self.apt_transfers.extend(other.apt_transfers);
+        self.address_labels.extend(other.address_labels);
+        self.apt_usd_prices.extend(other.apt_usd_prices);
+        self.money_configs.extend(other.money_configs);
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -142,80 +322,92 @@ pub enum ContractEvent {
 
 impl ContractEvent {
     fn from_event(
-        contract_address: &str,
+        contracts: &[ContractEntry],
         _event_idx: usize,
         event: &EventPB,
         txn_version: i64,
     ) -> Option<Self> {
         // use standardize_address to pad the address in event type before processing
         let parts = event.type_str.split("::").collect::<Vec<_>>();
-        let t = standardize_address(parts[0]) + "::" + parts[1] + "::" + parts[2];
-        let should_include = t.starts_with(contract_address);
+        if parts.len() < 3 {
+            return None;
+        }
+        let address = standardize_address(parts[0]);
+        let t = address.clone() + "::" + parts[1] + "::" + parts[2];
+        let contract = contracts.iter().find(|contract| {
+            contract.address == address
+                && contract.indexes_module(parts[1])
+                && contract.is_active(txn_version)
+        });
         println!("{}", event.type_str);
-        if should_include {
-            if t.starts_with(format!("{}::mooner_money::TokenCreated", contract_address).as_str()) {
-                println!("mooner_money::TokenCreated {}", event.data.as_str());
+        if let Some(contract) = contract {
+            let contract_address = contract.address.as_str();
+            let modules = &contract.module_names;
+            let event_type =
+                |module: &str, name: &str| format!("{}::{}::{}", contract_address, module, name);
+            if t.starts_with(event_type(&modules.money, "TokenCreated").as_str()) {
+                println!("{}::TokenCreated {}", modules.money, event.data.as_str());
                 let token_created_on_chain: TokenCreatedOnChain =
                     serde_json::from_str(event.data.as_str()).unwrap_or_else(|_| {
-                        panic!("Failed to parse mooner_money::TokenCreated, {}", event.data.as_str())
+                        panic!("Failed to parse {}::TokenCreated, {}", modules.money, event.data.as_str())
                     });
                 Some(ContractEvent::TokenCreatedEvent(
-                    token_created_on_chain.to_db_token(txn_version),
+                    token_created_on_chain.to_db_token(txn_version, contract_address),
                 ))
-            } else if t.starts_with(format!("{}::mooner_money::PoolCompleted", contract_address).as_str()) {
-                println!("mooner_money::PoolCompleted {}", event.data.as_str());
+            } else if t.starts_with(event_type(&modules.money, "PoolCompleted").as_str()) {
+                println!("{}::PoolCompleted {}", modules.money, event.data.as_str());
                 let pool_completed_on_chain: PoolCompletedOnChain =
                     serde_json::from_str(event.data.as_str()).unwrap_or_else(|_| {
-                        panic!("Failed to parse mooner_money::PoolCompleted, {}", event.data.as_str())
+                        panic!("Failed to parse {}::PoolCompleted, {}", modules.money, event.data.as_str())
                     });
                 Some(ContractEvent::PoolCompletedEvent(
                     pool_completed_on_chain.to_db_pool_completed(),
                 ))
-            } else if t.starts_with(format!("{}::mooner_money::TokenTraded", contract_address).as_str()) {
-                println!("mooner_money::TokenTraded {}", event.data.as_str());
+            } else if t.starts_with(event_type(&modules.money, "TokenTraded").as_str()) {
+                println!("{}::TokenTraded {}", modules.money, event.data.as_str());
                 let trade_created_on_chain: TradeCreatedOnChain =
                     serde_json::from_str(event.data.as_str()).unwrap_or_else(|_| {
-                        panic!("Failed to parse mooner_money::TokenTraded, {}", event.data.as_str())
+                        panic!("Failed to parse {}::TokenTraded, {}", modules.money, event.data.as_str())
                     });
                 Some(ContractEvent::TradeCreatedEvent(
-                    trade_created_on_chain.to_db_trade(txn_version),
+                    trade_created_on_chain.to_db_trade(txn_version, contract_address),
                 ))
-            } else if t.starts_with(format!("{}::staking::PositionCreated", contract_address).as_str()) {
-                println!("staking::PositionCreated {}", event.data.as_str());
+            } else if t.starts_with(event_type(&modules.staking, "PositionCreated").as_str()) {
+                println!("{}::PositionCreated {}", modules.staking, event.data.as_str());
                 let position_created_on_chain: PositionCreatedOnChain =
                     serde_json::from_str(event.data.as_str()).unwrap_or_else(|_| {
-                        panic!("Failed to parse staking::PositionCreated, {}", event.data.as_str())
+                        panic!("Failed to parse {}::PositionCreated, {}", modules.staking, event.data.as_str())
                     });
                 Some(ContractEvent::PositionCreated(
-                    position_created_on_chain.to_db_staking(txn_version),
+                    position_created_on_chain.to_db_staking(txn_version, contract_address),
                 ))
-            } else if t.starts_with(format!("{}::staking::RewardsClaimed", contract_address).as_str()) {
-                println!("staking::RewardsClaimed {}", event.data.as_str());
+            } else if t.starts_with(event_type(&modules.staking, "RewardsClaimed").as_str()) {
+                println!("{}::RewardsClaimed {}", modules.staking, event.data.as_str());
                 let reward_claimed_on_chain: RewardClaimedOnChain =
                     serde_json::from_str(event.data.as_str()).unwrap_or_else(|_| {
-                        panic!("Failed to parse staking::RewardsClaimed, {}", event.data.as_str())
+                        panic!("Failed to parse {}::RewardsClaimed, {}", modules.staking, event.data.as_str())
                     });
                 Some(ContractEvent::PositionRewardClaimed(
                     reward_claimed_on_chain.to_db_reward_claimed(),
                 ))
-            } else if t.starts_with(format!("{}::staking::PositionRemoved", contract_address).as_str()) {
-                println!("staking::PositionRemoved {}", event.data.as_str());
+            } else if t.starts_with(event_type(&modules.staking, "PositionRemoved").as_str()) {
+                println!("{}::PositionRemoved {}", modules.staking, event.data.as_str());
                 let staking_removed_on_chain: StakingRemovedOnChain =
                     serde_json::from_str(event.data.as_str()).unwrap_or_else(|_| {
-                        panic!("Failed to parse staking::PositionRemoved, {}", event.data.as_str())
+                        panic!("Failed to parse {}::PositionRemoved, {}", modules.staking, event.data.as_str())
                     });
                 Some(ContractEvent::PositionRemoved(
                     staking_removed_on_chain.to_db_position_removed(),
                 ))
-            } else if t.starts_with(format!("{}::mooner_spin::SpinEvent", contract_address).as_str()) {
+            } else if t.starts_with(event_type(&modules.spin, "SpinEvent").as_str()) {
                 println!("reached here");
-                println!("mooner_spin::SpinEvent {}", event.data.as_str());
+                println!("{}::SpinEvent {}", modules.spin, event.data.as_str());
                 let spin_event_on_chain: SpinEventOnChain =
                     serde_json::from_str(event.data.as_str()).unwrap_or_else(|_| {
-                        panic!("Failed to parse mooner_spin::SpinEvent, {}", event.data.as_str())
+                        panic!("Failed to parse {}::SpinEvent, {}", modules.spin, event.data.as_str())
                     });
                 Some(ContractEvent::SpinEvent(
-                    spin_event_on_chain.to_db_account(),
+                    spin_event_on_chain.to_db_account(txn_version),
                 ))
             } else {
                 None
@@ -225,16 +417,17 @@ impl ContractEvent {
         }
     }
 
+    /// Contract events of a transaction with their index in its events.
     pub fn from_events(
-        contract_address: &str,
+        contracts: &[ContractEntry],
         events: &[EventPB],
         tx_version: i64,
-    ) -> Vec<Self> {
+    ) -> Vec<(i64, Self)> {
         events
             .iter()
             .enumerate()
             .filter_map(|(idx, event)| {
-                Self::from_event(contract_address, idx, event, tx_version)
+                Self::from_event(contracts, idx, event, tx_version).map(|event| (idx as i64, event))
             })
             .collect()
     }
@@ -244,103 +437,146 @@ impl ContractEvent {
 pub enum ContractUpgradeChange {
     ModuleUpgradeChange(ModuleUpgrade),
     PackageUpgradeChange(PackageUpgrade),
+    PackageDependencyChange(PackageDependency),
 }
 
+/// Events of `0x1::object_code_deployment`, emitted when a package is published to or
+/// upgraded in a code object instead of an account.
+const OBJECT_CODE_EVENTS: [&str; 2] = [
+    "0x1::object_code_deployment::Publish",
+    "0x1::object_code_deployment::Upgrade",
+];
+
 impl ContractUpgradeChange {
+    /// Module and package upgrades of the contract. The write set and the `PackageRegistry`
+    /// don't always agree, modules missing from either side are kept as partial rows with
+    /// the reason in `partial_reason` instead of failing the batch.
     pub fn from_changes(
         contract_address: &str,
         txn_version: i64,
         changes: &[WriteSetChange],
+        events: &[EventPB],
     ) -> Vec<Self> {
-        let mut raw_module_changes: AHashMap<(String, String), MoveModuleBytecode> =
-            AHashMap::new();
+        let mut raw_module_changes: AHashMap<String, MoveModuleBytecode> = AHashMap::new();
+        let mut unnamed_module_changes: Vec<(usize, MoveModuleBytecode)> = vec![];
         let mut raw_package_changes: Vec<PackageUpgradeChangeOnChain> = vec![];
 
-        changes
-            .iter()
-            .for_each(|change| match change.change.as_ref() {
-                Some(change) => match change {
-                    Change::WriteModule(write_module_change) => {
-                        if standardize_address(write_module_change.address.as_str())
-                            == contract_address
-                        {
-                            raw_module_changes.insert(
-                                (
-                                    standardize_address(write_module_change.address.as_str()),
-                                    write_module_change
-                                        .data
-                                        .clone()
-                                        .unwrap_or_else(|| {
-                                            panic!("MoveModuleBytecode data is missing",)
-                                        })
-                                        .abi
-                                        .clone()
-                                        .unwrap_or_else(|| {
-                                            panic!("MoveModuleBytecode abi is missing",)
-                                        })
-                                        .name,
-                                ),
-                                write_module_change.data.clone().unwrap(),
-                            );
+        for (idx, change) in changes.iter().enumerate() {
+            match change.change.as_ref() {
+                Some(Change::WriteModule(write_module_change))
+                    if standardize_address(write_module_change.address.as_str())
+                        == contract_address =>
+                {
+                    let Some(data) = write_module_change.data.as_ref() else {
+                        tracing::warn!(
+                            transaction_version = txn_version,
+                            "MoveModuleBytecode data is missing"
+                        );
+                        continue;
+                    };
+                    match data.abi.as_ref() {
+                        Some(abi) => {
+                            raw_module_changes.insert(abi.name.clone(), data.clone());
                         }
+                        None => unnamed_module_changes.push((idx, data.clone())),
                     }
-                    Change::WriteResource(write_resource_change) => {
-                        if standardize_address(write_resource_change.address.as_str())
-                            == contract_address
-                            && write_resource_change.type_str == "0x1::code::PackageRegistry"
-                        {
-                            let package_upgrade: PackageUpgradeChangeOnChain =
-                                serde_json::from_str(write_resource_change.data.as_str())
-                                    .unwrap_or_else(|_| {
-                                        panic!(
-                                            "Failed to parse PackageUpgradeChangeOnChain, {}",
-                                            write_resource_change.data.as_str()
-                                        )
-                                    });
-                            raw_package_changes.push(package_upgrade);
-                        }
+                }
+                Some(Change::WriteResource(write_resource_change))
+                    if standardize_address(write_resource_change.address.as_str())
+                        == contract_address
+                        && write_resource_change.type_str == "0x1::code::PackageRegistry" =>
+                {
+                    match serde_json::from_str::<PackageUpgradeChangeOnChain>(
+                        write_resource_change.data.as_str(),
+                    ) {
+                        Ok(package_upgrade) => raw_package_changes.push(package_upgrade),
+                        Err(e) => tracing::warn!(
+                            transaction_version = txn_version,
+                            "Failed to parse PackageUpgradeChangeOnChain: {}, {}",
+                            e,
+                            write_resource_change.data.as_str()
+                        ),
                     }
-                    _ => {}
-                },
-                None => {}
-            });
+                }
+                _ => {}
+            }
+        }
+
+        let deployment = if events.iter().any(|event| {
+            OBJECT_CODE_EVENTS.contains(&event.type_str.as_str())
+                && serde_json::from_str::<serde_json::Value>(&event.data)
+                    .ok()
+                    .and_then(|data| data["object_address"].as_str().map(standardize_address))
+                    .as_deref()
+                    == Some(contract_address)
+        }) {
+            DEPLOYMENT_OBJECT
+        } else {
+            DEPLOYMENT_ACCOUNT
+        };
 
         let package_changes = raw_package_changes
             .iter()
             .flat_map(|package_change| {
-                package_change.to_db_package_upgrade(txn_version, contract_address.to_string())
+                package_change.to_db_package_upgrade(
+                    txn_version,
+                    contract_address.to_string(),
+                    deployment,
+                )
             })
             .collect::<Vec<PackageUpgrade>>();
 
-        let module_changes = raw_package_changes
+        let dependency_changes = raw_package_changes
             .iter()
-            .flat_map(|package_change| package_change.packages.clone())
-            .map(|package| {
-                package
-                    .modules
-                    .iter()
-                    .map(|module| {
-                        let raw_module = raw_module_changes
-                            .get(&(contract_address.to_string(), module.name.clone()))
-                            .unwrap_or_else(|| {
-                                panic!("Module bytecode not found for module {}", module.name)
-                            });
-                        ModuleUpgrade {
-                            module_addr: contract_address.to_string(),
-                            module_name: module.name.clone(),
-                            upgrade_number: package.upgrade_number.parse().unwrap(),
-                            module_bytecode: raw_module.bytecode.clone(),
-                            module_source_code: module.source.clone(),
-                            module_abi: serde_json::json!(raw_module.abi.clone().unwrap_or_else(
-                                || { panic!("Module abi is missing for module {}", module.name) }
-                            )),
-                            tx_version: txn_version,
-                        }
-                    })
-                    .collect::<Vec<ModuleUpgrade>>()
+            .flat_map(|package_change| {
+                package_change.to_db_package_dependencies(txn_version, contract_address)
             })
-            .flatten()
-            .collect::<Vec<ModuleUpgrade>>();
+            .collect::<Vec<PackageDependency>>();
+
+        let mut module_changes = vec![];
+        for package in raw_package_changes.iter().flat_map(|change| change.packages.iter()) {
+            for module in package.modules.iter() {
+                let raw_module = raw_module_changes.remove(&module.name);
+                let partial_reason = if raw_module.is_none() {
+                    Some("module is in the PackageRegistry but not in the write set")
+                } else {
+                    None
+                };
+                module_changes.push(ModuleUpgrade::new(
+                    contract_address,
+                    &module.name,
+                    package.parsed_upgrade_number(),
+                    raw_module.as_ref(),
+                    &module.source,
+                    txn_version,
+                    partial_reason,
+                ));
+            }
+        }
+        // Modules written without a registry entry have no upgrade number, they are keyed
+        // on the negated txn version so every occurrence is kept
+        for (name, raw_module) in raw_module_changes {
+            module_changes.push(ModuleUpgrade::new(
+                contract_address,
+                &name,
+                -txn_version,
+                Some(&raw_module),
+                "0x",
+                txn_version,
+                Some("module is in the write set but not in the PackageRegistry"),
+            ));
+        }
+        for (idx, raw_module) in unnamed_module_changes {
+            module_changes.push(ModuleUpgrade::new(
+                contract_address,
+                &format!("unknown_{}", idx),
+                -txn_version,
+                Some(&raw_module),
+                "0x",
+                txn_version,
+                Some("module abi is missing"),
+            ));
+        }
 
         module_changes
             .into_iter()
@@ -350,6 +586,304 @@ impl ContractUpgradeChange {
                     .into_iter()
                     .map(ContractUpgradeChange::PackageUpgradeChange),
             )
+            .chain(
+                dependency_changes
+                    .into_iter()
+                    .map(ContractUpgradeChange::PackageDependencyChange),
+            )
             .collect()
     }
 }
+
+impl RawContractEventOnChain {
+    /// Events, and optionally resources, whose type is defined by an indexed contract.
+    pub fn from_transaction(
+        contracts: &[ContractEntry],
+        include_resources: bool,
+        events: &[EventPB],
+        changes: &[WriteSetChange],
+        txn_version: i64,
+    ) -> Vec<Self> {
+        let raw_events = events.iter().enumerate().filter_map(|(idx, event)| {
+            Self::from_type_str(
+                contracts,
+                RAW_KIND_EVENT,
+                idx,
+                &event.type_str,
+                None,
+                &event.data,
+                txn_version,
+            )
+        });
+        let raw_resources = changes
+            .iter()
+            .enumerate()
+            .filter(|_| include_resources)
+            .filter_map(|(idx, change)| match change.change.as_ref() {
+                Some(Change::WriteResource(resource)) => Self::from_type_str(
+                    contracts,
+                    RAW_KIND_RESOURCE,
+                    idx,
+                    &resource.type_str,
+                    Some(standardize_address(&resource.address)),
+                    &resource.data,
+                    txn_version,
+                ),
+                _ => None,
+            });
+        raw_events.chain(raw_resources).collect()
+    }
+
+    fn from_type_str(
+        contracts: &[ContractEntry],
+        kind: &'static str,
+        idx: usize,
+        type_str: &str,
+        account_address: Option<String>,
+        data: &str,
+        txn_version: i64,
+    ) -> Option<Self> {
+        let base = type_str.split('<').next().unwrap_or_default();
+        let parts = base.split("::").collect::<Vec<_>>();
+        if parts.len() != 3 {
+            return None;
+        }
+        let address = standardize_address(parts[0]);
+        let contract = contracts.iter().find(|contract| {
+            contract.address == address
+                && contract.indexes_module(parts[1])
+                && contract.is_active(txn_version)
+        })?;
+        Some(Self {
+            txn_version,
+            kind,
+            event_index: idx as i64,
+            contract_address: contract.address.clone(),
+            module_name: parts[1].to_string(),
+            struct_name: parts[2].to_string(),
+            type_str: type_str.to_string(),
+            account_address,
+            data: data.to_string(),
+        })
+    }
+}
+
+/// Data of the `Config` resource of a contract's money module, e.g. `mooner_money::Config`,
+/// written by a transaction.
+fn money_config<'a>(
+    contracts: &[ContractEntry],
+    changes: &'a [WriteSetChange],
+    contract_address: &str,
+) -> Option<&'a str> {
+    let contract = contracts
+        .iter()
+        .find(|contract| contract.address == contract_address)?;
+    let config_type = format!("{}::Config", contract.module_names.money);
+    changes.iter().find_map(|change| match change.change.as_ref() {
+        Some(Change::WriteResource(resource))
+            if resource.type_str.split_once("::").is_some_and(|(address, rest)| {
+                standardize_address(address) == contract_address && rest == config_type
+            }) =>
+        {
+            Some(resource.data.as_str())
+        }
+        _ => None,
+    })
+}
+
+impl WhitelistWindow {
+    /// Windows of the tokens created in a transaction. Creating a token bumps the token
+    /// index of `mooner_money::Config`, so the write set holds the duration at creation time.
+    pub fn from_transaction(
+        contracts: &[ContractEntry],
+        events: &[ContractEvent],
+        changes: &[WriteSetChange],
+        txn_version: i64,
+    ) -> Vec<Self> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                ContractEvent::TokenCreatedEvent(token) => Some(token),
+                _ => None,
+            })
+            .filter_map(|token| {
+                let whitelist_duration = money_config(contracts, changes, &token.contract_address)
+                    .and_then(|data| serde_json::from_str::<WhitelistConfigOnChain>(data).ok())
+                    .and_then(|config| config.whitelist_duration.parse::<i64>().ok());
+                match whitelist_duration {
+                    Some(whitelist_duration) => Some(Self::new(token, whitelist_duration)),
+                    None => {
+                        tracing::warn!(
+                            transaction_version = txn_version,
+                            "Whitelist duration not found for token {}",
+                            token.pre_addr
+                        );
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+impl MoneyConfig {
+    /// Fees of every `Config` write of the money modules in a transaction.
+    pub fn from_transaction(
+        contracts: &[ContractEntry],
+        changes: &[WriteSetChange],
+        txn_version: i64,
+    ) -> Vec<Self> {
+        contracts
+            .iter()
+            .filter_map(|contract| {
+                let config = money_config(contracts, changes, &contract.address)
+                    .and_then(|data| serde_json::from_str::<FeeConfigOnChain>(data).ok())?;
+                Some(Self {
+                    contract_address: contract.address.clone(),
+                    txn_version,
+                    fee_bps: config.fee as i64,
+                    creator_fee_bps: config.creator_fee.parse().ok()?,
+                })
+            })
+            .collect()
+    }
+}
+
+impl AddressLabel {
+    /// Protocol addresses seen in a transaction: pools of created tokens and the config
+    /// object owning them, the fee wallet of every `Config` write of the money module, the
+    /// Thala pools tokens graduate to and the games created by the spin module.
+    pub fn from_transaction(
+        contracts: &[ContractEntry],
+        contract_events: &[ContractEvent],
+        events: &[EventPB],
+        changes: &[WriteSetChange],
+        txn_version: i64,
+    ) -> Vec<Self> {
+        let find_contract = |address: &str| {
+            let address = standardize_address(address);
+            contracts
+                .iter()
+                .find(|contract| contract.address == address && contract.is_active(txn_version))
+        };
+        let is_contract = |address: &str| find_contract(address).is_some();
+        let resources = changes
+            .iter()
+            .filter_map(|change| match change.change.as_ref() {
+                Some(Change::WriteResource(resource)) => Some(resource),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let mut labels = vec![];
+
+        for event in contract_events {
+            match event {
+                ContractEvent::TokenCreatedEvent(token) => {
+                    labels.push(Self::auto(
+                        &token.pool_addr,
+                        format!("{} pool", token.symbol),
+                        CATEGORY_POOL,
+                        Some(token.pre_addr.clone()),
+                        txn_version,
+                    ));
+                    let owner = resources
+                        .iter()
+                        .find(|resource| {
+                            resource.type_str == "0x1::object::ObjectCore"
+                                && standardize_address(&resource.address) == token.pool_addr
+                        })
+                        .and_then(|resource| {
+                            serde_json::from_str::<ObjectCoreOnChain>(&resource.data).ok()
+                        });
+                    if let Some(owner) = owner {
+                        labels.push(Self::auto(
+                            &owner.owner,
+                            "mooner_money config".to_string(),
+                            CATEGORY_CONFIG,
+                            None,
+                            txn_version,
+                        ));
+                    }
+                }
+                ContractEvent::PoolCompletedEvent(completed) => {
+                    // The weighted pool is created by the Thala package, whose address
+                    // isn't configured
+                    labels.extend(
+                        resources
+                            .iter()
+                            .filter(|resource| {
+                                resource.type_str.split_once("::").is_some_and(
+                                    |(address, rest)| rest == "pool::Pool" && !is_contract(address),
+                                )
+                            })
+                            .map(|resource| {
+                                Self::auto(
+                                    &resource.address,
+                                    "thala pool".to_string(),
+                                    CATEGORY_THALA_POOL,
+                                    Some(completed.main_addr.clone()),
+                                    txn_version,
+                                )
+                            }),
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        labels.extend(
+            resources
+                .iter()
+                .filter(|resource| {
+                    resource
+                        .type_str
+                        .split_once("::")
+                        .is_some_and(|(address, rest)| {
+                            find_contract(address).is_some_and(|contract| {
+                                rest == format!("{}::Config", contract.module_names.money)
+                            })
+                        })
+                })
+                .filter_map(|resource| {
+                    serde_json::from_str::<FeeWalletConfigOnChain>(&resource.data).ok()
+                })
+                .map(|config| {
+                    Self::auto(
+                        &config.fee_wallet,
+                        "fee wallet".to_string(),
+                        CATEGORY_FEE_WALLET,
+                        None,
+                        txn_version,
+                    )
+                }),
+        );
+
+        labels.extend(
+            events
+                .iter()
+                .filter(|event| {
+                    event
+                        .type_str
+                        .split_once("::")
+                        .is_some_and(|(address, rest)| {
+                            find_contract(address).is_some_and(|contract| {
+                                rest == format!("{}::GameInitEvent", contract.module_names.spin)
+                            })
+                        })
+                })
+                .filter_map(|event| {
+                    serde_json::from_str::<GameInitEventOnChain>(&event.data).ok()
+                })
+                .map(|game| {
+                    Self::auto(
+                        &game.game,
+                        "mooner_spin game".to_string(),
+                        CATEGORY_GAME,
+                        None,
+                        txn_version,
+                    )
+                }),
+        );
+        labels
+    }
+}
@@ -0,0 +1,370 @@
+use ahash::AHashMap;
+use anyhow::Result;
+use aptos_indexer_processor_sdk::{
+    traits::{NamedStep, PollableAsyncRunType, PollableAsyncStep, Processable},
+    types::transaction_context::TransactionContext,
+    utils::errors::ProcessorError,
+};
+use async_trait::async_trait;
+use bigdecimal::{BigDecimal, ToPrimitive};
+use diesel::{insert_into, ExpressionMethods, QueryDsl, QueryResult};
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use std::marker::PhantomData;
+
+use crate::{
+    config::indexer_processor_config::{FeeConfig, LeaderboardConfig},
+    db_models::leaderboards::{
+        LeaderboardEntry, BOARD_CREATOR_FEES, BOARD_STAKER_AMOUNT, BOARD_TRADER_PNL,
+        BOARD_TRADER_VOLUME, LEADERBOARD_WINDOWS,
+    },
+    schema::{creators, leaderboards, positions, realized_pnl, stakings, tokens, trades},
+    utils::{
+        database_connection::get_db_connection,
+        database_utils::{get_config_table_chunk_size, ArcDbPool},
+        fees::creator_fee,
+    },
+};
+
+/// LeaderboardRefresher is a pass-through step that periodically re-ranks the leaderboards
+/// from the stored data, so reads only need to page through `leaderboards`. All time values
+/// come from the per user rollups in `positions` and `creators`, windows are read as ranges
+/// of `trades`, `realized_pnl` and `stakings` by timestamp.
+pub struct LeaderboardRefresher<T>
+where
+    Self: Sized + Send + 'static,
+    T: Send + 'static,
+{
+    pool: ArcDbPool,
+    config: LeaderboardConfig,
+    fee_config: FeeConfig,
+    // Chain timestamp of the latest batch seen, windows are measured back from it
+    latest_ts: Option<i64>,
+    // Whether a batch passed through since the last refresh
+    dirty: bool,
+    _marker: PhantomData<T>,
+}
+
+impl<T> LeaderboardRefresher<T>
+where
+    Self: Sized + Send + 'static,
+    T: Send + 'static,
+{
+    pub fn new(pool: ArcDbPool, config: LeaderboardConfig, fee_config: FeeConfig) -> Self {
+        Self {
+            pool,
+            config,
+            fee_config,
+            latest_ts: None,
+            dirty: true,
+            _marker: PhantomData,
+        }
+    }
+
+    fn rank(
+        &self,
+        board: &str,
+        time_window: &str,
+        refreshed_at_ts: i64,
+        values: AHashMap<String, i128>,
+    ) -> Vec<LeaderboardEntry> {
+        let mut values = values.into_iter().collect::<Vec<(String, i128)>>();
+        values.sort_by(|(a_addr, a), (b_addr, b)| b.cmp(a).then_with(|| a_addr.cmp(b_addr)));
+        values
+            .into_iter()
+            .take(self.config.size as usize)
+            .enumerate()
+            .map(|(idx, (address, value))| LeaderboardEntry {
+                board: board.to_string(),
+                time_window: time_window.to_string(),
+                rank: idx as i32 + 1,
+                address,
+                value: BigDecimal::from(value),
+                refreshed_at_ts,
+            })
+            .collect()
+    }
+
+    async fn refresh_leaderboards(&mut self) -> Result<(), ProcessorError> {
+        let conn = &mut get_db_connection(&self.pool).await?;
+        let now = match self.latest_ts {
+            Some(ts) => ts,
+            None => match get_latest_trade_ts(conn).await.map_err(db_error)? {
+                Some(ts) => ts,
+                None => return Ok(()),
+            },
+        };
+
+        let mut entries = vec![];
+        for (time_window, length) in LEADERBOARD_WINDOWS {
+            let (volume, pnl, fees, staked) = match length {
+                Some(length) => {
+                    let since = now - length;
+                    (
+                        get_trader_volume(conn, since).await.map_err(db_error)?,
+                        get_realized_pnl(conn, since).await.map_err(db_error)?,
+                        get_creator_fees(conn, &self.fee_config, since)
+                            .await
+                            .map_err(db_error)?,
+                        get_staker_amount(conn, since).await.map_err(db_error)?,
+                    )
+                }
+                None => (
+                    get_all_time_trader_volume(conn).await.map_err(db_error)?,
+                    get_all_time_realized_pnl(conn).await.map_err(db_error)?,
+                    get_all_time_creator_fees(conn).await.map_err(db_error)?,
+                    get_all_time_staker_amount(conn).await.map_err(db_error)?,
+                ),
+            };
+            entries.extend(self.rank(BOARD_TRADER_VOLUME, time_window, now, volume));
+            entries.extend(self.rank(BOARD_TRADER_PNL, time_window, now, pnl));
+            entries.extend(self.rank(BOARD_CREATOR_FEES, time_window, now, fees));
+            entries.extend(self.rank(BOARD_STAKER_AMOUNT, time_window, now, staked));
+        }
+
+        let chunk_size =
+            get_config_table_chunk_size::<LeaderboardEntry>("leaderboards", &AHashMap::new());
+        conn.transaction(|conn| {
+            Box::pin(async move {
+                diesel::delete(leaderboards::table).execute(conn).await?;
+                for chunk in entries.chunks(chunk_size) {
+                    insert_into(leaderboards::table)
+                        .values(chunk.to_vec())
+                        .execute(conn)
+                        .await?;
+                }
+                Ok(())
+            })
+        })
+        .await
+        .map_err(db_error)?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+fn db_error(e: diesel::result::Error) -> ProcessorError {
+    tracing::warn!("Error running query: {:?}", e);
+    ProcessorError::DBStoreError {
+        message: format!("Failed to refresh leaderboards: {}", e),
+        query: None,
+    }
+}
+
+fn to_i128(value: Option<BigDecimal>) -> i128 {
+    value.and_then(|v| v.to_i128()).unwrap_or(0)
+}
+
+async fn get_latest_trade_ts(conn: &mut AsyncPgConnection) -> QueryResult<Option<i64>> {
+    trades::table
+        .select(diesel::dsl::max(trades::ts))
+        .first::<Option<i64>>(conn)
+        .await
+}
+
+async fn get_trader_volume(
+    conn: &mut AsyncPgConnection,
+    since: i64,
+) -> QueryResult<AHashMap<String, i128>> {
+    Ok(trades::table
+        .filter(trades::ts.ge(since))
+        .group_by(trades::user_addr)
+        .select((trades::user_addr, diesel::dsl::sum(trades::aptos_amount)))
+        .load::<(String, Option<BigDecimal>)>(conn)
+        .await?
+        .into_iter()
+        .map(|(user_addr, volume)| (user_addr, to_i128(volume)))
+        .collect())
+}
+
+/// Pnl realized by each user's sells since `since`.
+async fn get_realized_pnl(
+    conn: &mut AsyncPgConnection,
+    since: i64,
+) -> QueryResult<AHashMap<String, i128>> {
+    Ok(realized_pnl::table
+        .filter(realized_pnl::ts.ge(since))
+        .group_by(realized_pnl::user_addr)
+        .select((realized_pnl::user_addr, diesel::dsl::sum(realized_pnl::pnl)))
+        .load::<(String, Option<BigDecimal>)>(conn)
+        .await?
+        .into_iter()
+        .map(|(user_addr, pnl)| (user_addr, to_i128(pnl)))
+        .collect())
+}
+
+async fn get_all_time_trader_volume(
+    conn: &mut AsyncPgConnection,
+) -> QueryResult<AHashMap<String, i128>> {
+    Ok(positions::table
+        .group_by(positions::user_addr)
+        .select((positions::user_addr, diesel::dsl::sum(positions::volume)))
+        .load::<(String, Option<BigDecimal>)>(conn)
+        .await?
+        .into_iter()
+        .map(|(user_addr, volume)| (user_addr, to_i128(volume)))
+        .collect())
+}
+
+async fn get_all_time_realized_pnl(
+    conn: &mut AsyncPgConnection,
+) -> QueryResult<AHashMap<String, i128>> {
+    Ok(positions::table
+        .group_by(positions::user_addr)
+        .select((positions::user_addr, diesel::dsl::sum(positions::realized_pnl)))
+        .load::<(String, Option<BigDecimal>)>(conn)
+        .await?
+        .into_iter()
+        .map(|(user_addr, pnl)| (user_addr, to_i128(pnl)))
+        .collect())
+}
+
+async fn get_all_time_creator_fees(
+    conn: &mut AsyncPgConnection,
+) -> QueryResult<AHashMap<String, i128>> {
+    Ok(creators::table
+        .select((creators::creator, creators::creator_fees))
+        .load::<(String, i64)>(conn)
+        .await?
+        .into_iter()
+        .map(|(creator, fees)| (creator, fees as i128))
+        .collect())
+}
+
+async fn get_creator_fees(
+    conn: &mut AsyncPgConnection,
+    fee_config: &FeeConfig,
+    since: i64,
+) -> QueryResult<AHashMap<String, i128>> {
+    let stamped = trades::table
+        .filter(trades::ts.ge(since))
+        .filter(trades::creator_fee.is_not_null())
+        .group_by(trades::token_address)
+        .select((trades::token_address, diesel::dsl::sum(trades::creator_fee)))
+        .load::<(String, Option<BigDecimal>)>(conn)
+        .await?;
+    // Trades stored before the on-chain config was tracked are valued with fee_config
+    let volumes = trades::table
+        .filter(trades::ts.ge(since))
+        .filter(trades::creator_fee.is_null())
+        .group_by((trades::token_address, trades::is_buy))
+        .select((
+            trades::token_address,
+            trades::is_buy,
+            diesel::dsl::sum(trades::aptos_amount),
+        ))
+        .load::<(String, bool, Option<BigDecimal>)>(conn)
+        .await?;
+    let creators = tokens::table
+        .filter(
+            tokens::pre_addr.eq_any(
+                stamped
+                    .iter()
+                    .map(|(addr, _)| addr.clone())
+                    .chain(volumes.iter().map(|(addr, ..)| addr.clone())),
+            ),
+        )
+        .select((tokens::pre_addr, tokens::created_by))
+        .load::<(String, String)>(conn)
+        .await?
+        .into_iter()
+        .collect::<AHashMap<String, String>>();
+    let mut fees: AHashMap<String, i128> = AHashMap::new();
+    for (token_address, fee) in stamped {
+        let Some(created_by) = creators.get(&token_address) else {
+            continue;
+        };
+        *fees.entry(created_by.clone()).or_default() += to_i128(fee);
+    }
+    for (token_address, is_buy, volume) in volumes {
+        let Some(created_by) = creators.get(&token_address) else {
+            continue;
+        };
+        let volume = to_i128(volume).min(i64::MAX as i128) as i64;
+        *fees.entry(created_by.clone()).or_default() +=
+            creator_fee(fee_config, is_buy, volume) as i128;
+    }
+    Ok(fees)
+}
+
+/// Amount each user still has staked in the positions opened since `since`.
+async fn get_staker_amount(
+    conn: &mut AsyncPgConnection,
+    since: i64,
+) -> QueryResult<AHashMap<String, i128>> {
+    Ok(stakings::table
+        .filter(stakings::is_removed.eq(false))
+        .filter(stakings::ts.ge(since))
+        .group_by(stakings::user)
+        .select((stakings::user, diesel::dsl::sum(stakings::amount)))
+        .load::<(String, Option<BigDecimal>)>(conn)
+        .await?
+        .into_iter()
+        .map(|(user, amount)| (user, to_i128(amount)))
+        .collect())
+}
+
+async fn get_all_time_staker_amount(
+    conn: &mut AsyncPgConnection,
+) -> QueryResult<AHashMap<String, i128>> {
+    Ok(stakings::table
+        .filter(stakings::is_removed.eq(false))
+        .group_by(stakings::user)
+        .select((stakings::user, diesel::dsl::sum(stakings::amount)))
+        .load::<(String, Option<BigDecimal>)>(conn)
+        .await?
+        .into_iter()
+        .map(|(user, amount)| (user, to_i128(amount)))
+        .collect())
+}
+
+#[async_trait]
+impl<T> Processable for LeaderboardRefresher<T>
+where
+    Self: Sized + Send + 'static,
+    T: Send + 'static,
+{
+    type Input = T;
+    type Output = T;
+    type RunType = PollableAsyncRunType;
+
+    async fn process(
+        &mut self,
+        current_batch: TransactionContext<T>,
+    ) -> Result<Option<TransactionContext<T>>, ProcessorError> {
+        if let Some(ts) = current_batch.metadata.end_transaction_timestamp.as_ref() {
+            self.latest_ts = Some(self.latest_ts.unwrap_or(0).max(ts.seconds));
+        }
+        self.dirty = true;
+        // Pass through
+        Ok(Some(current_batch))
+    }
+}
+
+#[async_trait]
+impl<T: Send + 'static> PollableAsyncStep for LeaderboardRefresher<T>
+where
+    Self: Sized + Send + Sync + 'static,
+    T: Send + 'static,
+{
+    fn poll_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.config.refresh_interval_secs)
+    }
+
+    async fn poll(&mut self) -> Result<Option<Vec<TransactionContext<T>>>, ProcessorError> {
+        if self.dirty {
+            self.refresh_leaderboards().await?;
+        }
+        // Nothing should be returned
+        Ok(None)
+    }
+}
+
+impl<T> NamedStep for LeaderboardRefresher<T>
+where
+    Self: Sized + Send + 'static,
+    T: Send + 'static,
+{
+    fn name(&self) -> String {
+        format!("LeaderboardRefresher: {}", std::any::type_name::<T>())
+    }
+}
@@ -1,4 +1,10 @@
+pub mod chat_annotator;
 pub mod extractor;
+pub mod leaderboard_refresher;
 pub mod storer;
+pub mod price_oracle;
 pub mod processor;
 pub mod storers;
+pub mod token_metrics_refresher;
+pub mod trade_flagger;
+pub mod trending_refresher;
@@ -0,0 +1,176 @@
+use anyhow::Result;
+use aptos_indexer_processor_sdk::{
+    traits::{NamedStep, PollableAsyncRunType, PollableAsyncStep, Processable},
+    types::transaction_context::TransactionContext,
+    utils::errors::ProcessorError,
+};
+use async_trait::async_trait;
+use diesel::QueryDsl;
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use reqwest::Client;
+use std::{marker::PhantomData, time::Duration};
+
+use crate::{
+    config::indexer_processor_config::{FeeConfig, PriceConfig, PriceSource},
+    db_models::apt_usd_prices::minute_ts,
+    schema::trades,
+    utils::{
+        apt_usd_prices::{
+            backfill_trade_prices, fetch_http_prices, latest_price_minute, upsert_apt_usd_prices,
+        },
+        database_connection::get_db_connection,
+        database_utils::ArcDbPool,
+    },
+};
+
+/// Pages of klines read per poll, the rest of a long gap is read by the next polls
+const HTTP_PAGES_PER_POLL: usize = 10;
+
+/// PriceOracle is a pass-through step that periodically extends the APT/USD minute grid from
+/// the http source and values the stored trades that were stored before their price.
+pub struct PriceOracle<T>
+where
+    Self: Sized + Send + 'static,
+    T: Send + 'static,
+{
+    pool: ArcDbPool,
+    config: PriceConfig,
+    fee_config: FeeConfig,
+    client: Client,
+    // Latest minute of the grid when the trades were last backfilled
+    backfilled_minute: Option<i64>,
+    _marker: PhantomData<T>,
+}
+
+impl<T> PriceOracle<T>
+where
+    Self: Sized + Send + 'static,
+    T: Send + 'static,
+{
+    pub fn new(pool: ArcDbPool, config: PriceConfig, fee_config: FeeConfig) -> Result<Self> {
+        let client = Client::builder().timeout(Duration::from_secs(30)).build()?;
+        Ok(Self {
+            pool,
+            config,
+            fee_config,
+            client,
+            backfilled_minute: None,
+            _marker: PhantomData,
+        })
+    }
+
+    /// Reads the minutes closed since the last one stored, from the first trade when the
+    /// grid is empty.
+    async fn fetch_http_prices(&self, conn: &mut AsyncPgConnection) -> Result<(), ProcessorError> {
+        let mut start = match latest_price_minute(conn).await.map_err(db_error)? {
+            Some(minute) => minute + 60,
+            None => match trades::table
+                .select(diesel::dsl::min(trades::ts))
+                .first::<Option<i64>>(conn)
+                .await
+                .map_err(db_error)?
+            {
+                Some(ts) => minute_ts(ts),
+                None => return Ok(()),
+            },
+        };
+        let end = minute_ts(chrono::Utc::now().timestamp());
+        for _ in 0..HTTP_PAGES_PER_POLL {
+            if start >= end {
+                break;
+            }
+            let prices = match fetch_http_prices(&self.client, &self.config.http_url, start, end)
+                .await
+            {
+                Ok(prices) => prices,
+                Err(e) => {
+                    // The source being down only delays the USD values
+                    tracing::warn!("Failed to fetch apt usd prices: {:?}", e);
+                    return Ok(());
+                }
+            };
+            let Some(last_minute) = prices.last().map(|price| price.minute_ts) else {
+                break;
+            };
+            upsert_apt_usd_prices(conn, prices)
+                .await
+                .map_err(db_error)?;
+            start = last_minute + 60;
+        }
+        Ok(())
+    }
+
+    async fn refresh_prices(&mut self) -> Result<(), ProcessorError> {
+        let conn = &mut get_db_connection(&self.pool).await?;
+        if self.config.source == PriceSource::Http {
+            self.fetch_http_prices(conn).await?;
+        }
+        let latest_minute = latest_price_minute(conn).await.map_err(db_error)?;
+        if latest_minute.is_none() || latest_minute == self.backfilled_minute {
+            return Ok(());
+        }
+        let priced = backfill_trade_prices(conn, &self.config, &self.fee_config)
+            .await
+            .map_err(db_error)?;
+        if priced > 0 {
+            tracing::info!("Valued {} trades in usd", priced);
+        }
+        self.backfilled_minute = latest_minute;
+        Ok(())
+    }
+}
+
+fn db_error(e: diesel::result::Error) -> ProcessorError {
+    tracing::warn!("Error running query: {:?}", e);
+    ProcessorError::ProcessError {
+        message: e.to_string(),
+    }
+}
+
+#[async_trait]
+impl<T> Processable for PriceOracle<T>
+where
+    Self: Sized + Send + 'static,
+    T: Send + 'static,
+{
+    type Input = T;
+    type Output = T;
+    type RunType = PollableAsyncRunType;
+
+    async fn process(
+        &mut self,
+        current_batch: TransactionContext<T>,
+    ) -> Result<Option<TransactionContext<T>>, ProcessorError> {
+        // Pass through
+        Ok(Some(current_batch))
+    }
+}
+
+#[async_trait]
+impl<T: Send + 'static> PollableAsyncStep for PriceOracle<T>
+where
+    Self: Sized + Send + Sync + 'static,
+    T: Send + 'static,
+{
+    fn poll_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.config.poll_interval_secs)
+    }
+
+    async fn poll(&mut self) -> Result<Option<Vec<TransactionContext<T>>>, ProcessorError> {
+        if self.config.enabled() {
+            self.refresh_prices().await?;
+        }
+        // Nothing should be returned
+        Ok(None)
+    }
+}
+
+impl<T> NamedStep for PriceOracle<T>
+where
+    Self: Sized + Send + 'static,
+    T: Send + 'static,
+{
+    fn name(&self) -> String {
+        format!("PriceOracle: {}", std::any::type_name::<T>())
+    }
+}
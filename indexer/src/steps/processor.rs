@@ -6,11 +6,17 @@ use aptos_indexer_processor_sdk::{
     traits::IntoRunnableStep,
 };
 
-use super::{extractor::Extractor, storer::Storer};
+use super::{
+    chat_annotator::ChatAnnotator, extractor::Extractor,
+    leaderboard_refresher::LeaderboardRefresher, price_oracle::PriceOracle, storer::Storer,
+    token_metrics_refresher::TokenMetricsRefresher, trade_flagger::TradeFlagger,
+    trending_refresher::TrendingRefresher,
+};
 use crate::{
     config::indexer_processor_config::IndexerProcessorConfig,
     utils::{
-        chain_id::check_or_update_chain_id, database_connection::new_db_pool,
+        address_labels::import_address_labels, apt_usd_prices::import_apt_usd_prices, chain_id::check_or_update_chain_id,
+        contract_address::backfill_contract_address, database_connection::new_db_pool,
         database_utils::ArcDbPool, latest_processed_version_tracker::LatestVersionProcessedTracker,
         starting_version::get_starting_version,
     },
@@ -23,6 +29,7 @@ pub struct ContractProcessor {
 
 impl ContractProcessor {
     pub async fn new(config: IndexerProcessorConfig) -> Result<Self> {
+        config.validate()?;
         let conn_pool = new_db_pool(
             &config.db_config.postgres_connection_string,
             config.db_config.db_pool_size,
@@ -51,14 +58,59 @@ impl ContractProcessor {
             .await?;
         check_or_update_chain_id(grpc_chain_id as i64, self.db_pool.clone()).await?;
 
+        backfill_contract_address(&self.config.contract_config, self.db_pool.clone()).await?;
+        import_address_labels(&self.config.address_labels_config, self.db_pool.clone()).await?;
+        import_apt_usd_prices(&self.config.price_config, self.db_pool.clone()).await?;
+
         // Define processor steps
         let transaction_stream = TransactionStreamStep::new(TransactionStreamConfig {
             starting_version: Some(starting_version),
             ..self.config.transaction_stream_config
         })
         .await?;
-        let events_extractor = Extractor::new(self.config.contract_config.contract_address);
-        let events_storer = Storer::new(self.db_pool.clone());
+        let events_extractor = Extractor::new(
+            self.config.contract_config.contracts(),
+            self.config.raw_events_config.clone(),
+            self.config.trade_flags_config.clone(),
+            self.config.address_labels_config.clone(),
+            self.config.price_config.clone(),
+        );
+        let events_storer = Storer::new(
+            self.db_pool.clone(),
+            self.config.xp_config.clone(),
+            self.config.fee_config.clone(),
+            self.config.upgrade_config.clone(),
+            self.config.creator_config.clone(),
+            self.config.curve_config.clone(),
+            self.config.price_config.clone(),
+        );
+        let trade_flagger = TradeFlagger::new(
+            self.db_pool.clone(),
+            self.config.trade_flags_config.clone(),
+        );
+        let leaderboard_refresher = LeaderboardRefresher::new(
+            self.db_pool.clone(),
+            self.config.leaderboard_config.clone(),
+            self.config.fee_config.clone(),
+        );
+        let price_oracle = PriceOracle::new(
+            self.db_pool.clone(),
+            self.config.price_config.clone(),
+            self.config.fee_config.clone(),
+        )?;
+        let token_metrics_refresher = TokenMetricsRefresher::new(
+            self.db_pool.clone(),
+            self.config.token_metrics_config.clone(),
+            self.config.price_config.clone(),
+        );
+        let trending_refresher = TrendingRefresher::new(
+            self.db_pool.clone(),
+            self.config.trending_config.clone(),
+        );
+        let chat_annotator = ChatAnnotator::new(
+            self.db_pool.clone(),
+            self.config.chat_context_config.clone(),
+        );
         let version_tracker = LatestVersionProcessedTracker::new(
             self.config.db_config,
             starting_version,
@@ -72,6 +124,12 @@ impl ContractProcessor {
         )
         .connect_to(events_extractor.into_runnable_step(), 10)
         .connect_to(events_storer.into_runnable_step(), 10)
+        .connect_to(trade_flagger.into_runnable_step(), 10)
+        .connect_to(leaderboard_refresher.into_runnable_step(), 10)
+        .connect_to(price_oracle.into_runnable_step(), 10)
+        .connect_to(token_metrics_refresher.into_runnable_step(), 10)
+        .connect_to(trending_refresher.into_runnable_step(), 10)
+        .connect_to(chat_annotator.into_runnable_step(), 10)
         .connect_to(version_tracker.into_runnable_step(), 10)
         .end_and_return_output_receiver(10);
 
@@ -10,13 +10,29 @@ use async_trait::async_trait;
 use super::{
     extractor::{ContractEvent, ContractUpgradeChange, TransactionContextData},
     storers::{
+        address_labels_storer::process_address_labels,
+        apt_usd_prices_storer::{process_apt_usd_prices, process_trade_prices},
+        contract_transactions_storer::process_contract_transactions,
+        creators_storer::process_creators,
+        curve_storer::process_curve_progress,
+        failed_transactions_storer::process_failed_transactions,
+        money_configs_storer::{process_money_configs, process_trade_fees},
+        raw_events_storer::{process_raw_contract_events, sync_abi_registry},
         upgrade_module_change_storer::process_upgrade_module_changes,
-        upgrade_package_change_storer::process_upgrade_package_changes,
+        upgrade_package_change_storer::{
+            process_package_dependencies, process_upgrade_package_changes,
+        },
+        whitelist_storer::{flag_whitelist_trades, process_whitelist_windows},
     },
 };
 use crate::{
-    steps::storers::{spin_events_storer::process_spin_events, staking_events_storer::{process_position_created_events, process_position_removed_events, process_reward_claimed_events}, token_events_storer::{process_pool_completed_events, process_token_created_events}, trade_events_storer::process_trade_created_events},
-    utils::database_utils::ArcDbPool,
+    api_client::events,
+    config::indexer_processor_config::{
+        CreatorConfig, CurveConfig, FeeConfig, PriceConfig, UpgradeConfig, XpConfig,
+    },
+    event_feed::EVENT_FEED,
+    steps::storers::{spin_events_storer::process_spin_events, staking_events_storer::{fill_staking_feed_users, process_position_created_events, process_position_removed_events, process_reward_claimed_events}, token_events_storer::{process_pool_completed_events, process_token_created_events}, trade_events_storer::process_trade_created_events},
+    utils::{abi_decoder::AbiRegistry, database_utils::ArcDbPool},
 };
 
 /// Storer is a step that inserts events in the database.
@@ -25,6 +41,14 @@ where
     Self: Sized + Send + 'static,
 {
     pool: ArcDbPool,
+    xp_config: XpConfig,
+    fee_config: FeeConfig,
+    upgrade_config: UpgradeConfig,
+    creator_config: CreatorConfig,
+    curve_config: CurveConfig,
+    price_config: PriceConfig,
+    // Latest module ABIs, loaded with the first batch that has raw events or calls
+    abi_registry: Option<AbiRegistry>,
 }
 
 impl AsyncStep for Storer {}
@@ -36,8 +60,25 @@ impl NamedStep for Storer {
 }
 
 impl Storer {
-    pub fn new(pool: ArcDbPool) -> Self {
-        Self { pool }
+    pub fn new(
+        pool: ArcDbPool,
+        xp_config: XpConfig,
+        fee_config: FeeConfig,
+        upgrade_config: UpgradeConfig,
+        creator_config: CreatorConfig,
+        curve_config: CurveConfig,
+        price_config: PriceConfig,
+    ) -> Self {
+        Self {
+            pool,
+            xp_config,
+            fee_config,
+            upgrade_config,
+            creator_config,
+            curve_config,
+            price_config,
+            abi_registry: None,
+        }
     }
 }
 
@@ -53,10 +94,11 @@ impl Processable for Storer {
     ) -> Result<Option<TransactionContext<TransactionContextData>>, ProcessorError> {
         let per_table_chunk_sizes: AHashMap<String, usize> = AHashMap::new();
         let data = transaction_context_data.data.clone();
+        let mut feed_events = data.feed_events;
         let (
             token_created_events,
             pool_completed_events,
-            trade_created_events,
+            mut trade_created_events,
             position_created_events,
             position_claimed_events,
             position_removed_events,
@@ -116,24 +158,84 @@ impl Processable for Storer {
             },
         );
 
+        let end_version = transaction_context_data.metadata.end_version as i64;
+        let end_ts = transaction_context_data
+            .metadata
+            .end_transaction_timestamp
+            .as_ref()
+            .map(|ts| ts.seconds);
+
         process_token_created_events(
             self.pool.clone(),
             per_table_chunk_sizes.clone(),
-            token_created_events,
+            token_created_events.clone(),
+            end_version,
         )
         .await?;
 
         process_pool_completed_events(
             self.pool.clone(),
             per_table_chunk_sizes.clone(),
-            pool_completed_events,
+            pool_completed_events.clone(),
+        )
+        .await?;
+
+        process_whitelist_windows(
+            self.pool.clone(),
+            per_table_chunk_sizes.clone(),
+            data.whitelist_windows.clone(),
+        )
+        .await?;
+
+        flag_whitelist_trades(
+            self.pool.clone(),
+            &data.whitelist_windows,
+            &mut trade_created_events,
+        )
+        .await?;
+
+        process_money_configs(self.pool.clone(), data.money_configs.clone()).await?;
+
+        process_trade_fees(self.pool.clone(), &self.fee_config, &mut trade_created_events)
+            .await?;
+
+        process_apt_usd_prices(self.pool.clone(), data.apt_usd_prices.clone()).await?;
+
+        process_trade_prices(
+            self.pool.clone(),
+            &self.price_config,
+            &self.fee_config,
+            &mut trade_created_events,
         )
         .await?;
 
         process_trade_created_events(
             self.pool.clone(),
             per_table_chunk_sizes.clone(),
-            trade_created_events,
+            self.xp_config.clone(),
+            self.fee_config.clone(),
+            trade_created_events.clone(),
+            end_version,
+            end_ts,
+        )
+        .await?;
+
+        process_creators(
+            self.pool.clone(),
+            &self.fee_config,
+            &self.creator_config,
+            &token_created_events,
+            &pool_completed_events,
+            &trade_created_events,
+            end_version,
+        )
+        .await?;
+
+        let graduation_signals = process_curve_progress(
+            self.pool.clone(),
+            &self.curve_config,
+            &trade_created_events,
+            &pool_completed_events,
         )
         .await?;
 
@@ -141,6 +243,7 @@ impl Processable for Storer {
             self.pool.clone(),
             per_table_chunk_sizes.clone(),
             position_created_events,
+            end_version,
         )
         .await?;
 
@@ -162,27 +265,59 @@ impl Processable for Storer {
             self.pool.clone(),
             per_table_chunk_sizes.clone(),
             spin_events,
+            end_version,
         )
         .await?;
 
-        let (module_upgrades, package_upgrades) = data.changes.into_iter().fold(
-            (vec![], vec![]),
-            |(mut module_upgrades, mut package_upgrades), upgrade_change| {
-                match upgrade_change {
-                    ContractUpgradeChange::ModuleUpgradeChange(module_upgrade) => {
-                        module_upgrades.push(module_upgrade);
-                    }
-                    ContractUpgradeChange::PackageUpgradeChange(package_upgrade) => {
-                        package_upgrades.push(package_upgrade);
+        let (module_upgrades, package_upgrades, package_dependencies) =
+            data.changes.into_iter().fold(
+                (vec![], vec![], vec![]),
+                |(mut module_upgrades, mut package_upgrades, mut package_dependencies),
+                 upgrade_change| {
+                    match upgrade_change {
+                        ContractUpgradeChange::ModuleUpgradeChange(module_upgrade) => {
+                            module_upgrades.push(module_upgrade);
+                        }
+                        ContractUpgradeChange::PackageUpgradeChange(package_upgrade) => {
+                            package_upgrades.push(package_upgrade);
+                        }
+                        ContractUpgradeChange::PackageDependencyChange(package_dependency) => {
+                            package_dependencies.push(package_dependency);
+                        }
                     }
-                }
-                (module_upgrades, package_upgrades)
-            },
-        );
+                    (module_upgrades, package_upgrades, package_dependencies)
+                },
+            );
+
+        sync_abi_registry(
+            self.pool.clone(),
+            &mut self.abi_registry,
+            &module_upgrades,
+            !data.raw_events.is_empty() || !data.contract_transactions.is_empty(),
+        )
+        .await?;
+        if let Some(abi_registry) = self.abi_registry.as_ref() {
+            process_contract_transactions(
+                self.pool.clone(),
+                per_table_chunk_sizes.clone(),
+                abi_registry,
+                data.contract_transactions,
+            )
+            .await?;
+
+            process_raw_contract_events(
+                self.pool.clone(),
+                per_table_chunk_sizes.clone(),
+                abi_registry,
+                data.raw_events,
+            )
+            .await?;
+        }
 
         process_upgrade_module_changes(
             self.pool.clone(),
             per_table_chunk_sizes.clone(),
+            &self.upgrade_config,
             module_upgrades,
         )
         .await?;
@@ -194,6 +329,33 @@ impl Processable for Storer {
         )
         .await?;
 
+        process_package_dependencies(
+            self.pool.clone(),
+            per_table_chunk_sizes.clone(),
+            package_dependencies,
+        )
+        .await?;
+
+        process_failed_transactions(
+            self.pool.clone(),
+            per_table_chunk_sizes.clone(),
+            data.failed_transactions,
+        )
+        .await?;
+
+        process_address_labels(
+            self.pool.clone(),
+            per_table_chunk_sizes.clone(),
+            data.address_labels,
+        )
+        .await?;
+
+        fill_staking_feed_users(self.pool.clone(), &mut feed_events).await?;
+        EVENT_FEED.publish_batch(feed_events, &graduation_signals);
+        for signal in graduation_signals {
+            events::emit_near_graduation(signal).await.ok();
+        }
+
         Ok(Some(transaction_context_data))
     }
 }
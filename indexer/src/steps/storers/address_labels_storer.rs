@@ -0,0 +1,55 @@
+use ahash::AHashMap;
+use anyhow::Result;
+use aptos_indexer_processor_sdk::utils::errors::ProcessorError;
+use diesel::{insert_into, QueryResult};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+use crate::{
+    db_models::address_labels::AddressLabel,
+    schema::address_labels,
+    utils::{
+        database_connection::get_db_connection,
+        database_utils::{get_config_table_chunk_size, ArcDbPool},
+    },
+};
+
+async fn execute_address_labels_sql(
+    conn: &mut AsyncPgConnection,
+    items_to_insert: Vec<AddressLabel>,
+) -> QueryResult<usize> {
+    insert_into(address_labels::table)
+        .values(items_to_insert)
+        .on_conflict((
+            address_labels::address,
+            address_labels::label,
+            address_labels::source,
+        ))
+        .do_nothing()
+        .execute(conn)
+        .await
+}
+
+/// Stores the derived labels, an address keeps the first version it was labeled at.
+pub async fn process_address_labels(
+    pool: ArcDbPool,
+    per_table_chunk_sizes: AHashMap<String, usize>,
+    labels: Vec<AddressLabel>,
+) -> Result<(), ProcessorError> {
+    if labels.is_empty() {
+        return Ok(());
+    }
+    let chunk_size =
+        get_config_table_chunk_size::<AddressLabel>("address_labels", &per_table_chunk_sizes);
+    let conn = &mut get_db_connection(&pool).await?;
+    for chunk in labels.chunks(chunk_size) {
+        execute_address_labels_sql(conn, chunk.to_vec())
+            .await
+            .map_err(|e| {
+                tracing::warn!("Error running query: {:?}", e);
+                ProcessorError::ProcessError {
+                    message: e.to_string(),
+                }
+            })?;
+    }
+    Ok(())
+}
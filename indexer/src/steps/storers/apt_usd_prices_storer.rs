@@ -0,0 +1,52 @@
+use anyhow::Result;
+use aptos_indexer_processor_sdk::utils::errors::ProcessorError;
+
+use crate::{
+    config::indexer_processor_config::{FeeConfig, PriceConfig},
+    db_models::{apt_usd_prices::AptUsdPrice, trades::Trade},
+    utils::{
+        apt_usd_prices::{price_trades, upsert_apt_usd_prices},
+        database_connection::get_db_connection,
+        database_utils::ArcDbPool,
+    },
+};
+
+/// Stores the Pyth prices of the batch on the minute grid.
+pub async fn process_apt_usd_prices(
+    pool: ArcDbPool,
+    prices: Vec<AptUsdPrice>,
+) -> Result<(), ProcessorError> {
+    if prices.is_empty() {
+        return Ok(());
+    }
+    let conn = &mut get_db_connection(&pool).await?;
+    upsert_apt_usd_prices(conn, prices).await.map_err(|e| {
+        tracing::warn!("Error running query: {:?}", e);
+        ProcessorError::ProcessError {
+            message: e.to_string(),
+        }
+    })?;
+    Ok(())
+}
+
+/// Values the trades of the batch in USD at the price of their minute. Must run after the
+/// prices of the batch are stored.
+pub async fn process_trade_prices(
+    pool: ArcDbPool,
+    config: &PriceConfig,
+    fee_config: &FeeConfig,
+    trades: &mut [Trade],
+) -> Result<(), ProcessorError> {
+    if trades.is_empty() || !config.enabled() {
+        return Ok(());
+    }
+    let conn = &mut get_db_connection(&pool).await?;
+    price_trades(conn, config, fee_config, trades)
+        .await
+        .map_err(|e| {
+            tracing::warn!("Error running query: {:?}", e);
+            ProcessorError::ProcessError {
+                message: e.to_string(),
+            }
+        })
+}
@@ -0,0 +1,59 @@
+use ahash::AHashMap;
+use anyhow::Result;
+use aptos_indexer_processor_sdk::utils::errors::ProcessorError;
+use diesel::{insert_into, QueryResult};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+use crate::{
+    db_models::contract_transactions::{ContractTransaction, ContractTransactionOnChain},
+    schema::contract_transactions,
+    utils::{
+        abi_decoder::AbiRegistry,
+        database_connection::get_db_connection,
+        database_utils::{get_config_table_chunk_size, ArcDbPool},
+    },
+};
+
+async fn execute_contract_transactions_sql(
+    conn: &mut AsyncPgConnection,
+    items_to_insert: Vec<ContractTransaction>,
+) -> QueryResult<usize> {
+    insert_into(contract_transactions::table)
+        .values(items_to_insert)
+        .on_conflict(contract_transactions::txn_version)
+        .do_nothing()
+        .execute(conn)
+        .await
+}
+
+/// Decodes the arguments of the calls of a batch and stores them.
+pub async fn process_contract_transactions(
+    pool: ArcDbPool,
+    per_table_chunk_sizes: AHashMap<String, usize>,
+    registry: &AbiRegistry,
+    contract_transactions: Vec<ContractTransactionOnChain>,
+) -> Result<(), ProcessorError> {
+    if contract_transactions.is_empty() {
+        return Ok(());
+    }
+    let items = contract_transactions
+        .iter()
+        .map(|transaction| transaction.to_db_contract_transaction(registry))
+        .collect::<Vec<ContractTransaction>>();
+    let chunk_size = get_config_table_chunk_size::<ContractTransaction>(
+        "contract_transactions",
+        &per_table_chunk_sizes,
+    );
+    let conn = &mut get_db_connection(&pool).await?;
+    for chunk in items.chunks(chunk_size) {
+        execute_contract_transactions_sql(conn, chunk.to_vec())
+            .await
+            .map_err(|e| {
+                tracing::warn!("Error running query: {:?}", e);
+                ProcessorError::ProcessError {
+                    message: e.to_string(),
+                }
+            })?;
+    }
+    Ok(())
+}
@@ -0,0 +1,318 @@
+use ahash::{AHashMap, AHashSet};
+use anyhow::Result;
+use aptos_indexer_processor_sdk::utils::errors::ProcessorError;
+use bigdecimal::{BigDecimal, Zero};
+use diesel::{
+    insert_into, sql_query,
+    sql_types::{Array, BigInt, Numeric, Text},
+    upsert::excluded,
+    BoolExpressionMethods, ExpressionMethods, QueryDsl, QueryResult,
+};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+use crate::{
+    config::indexer_processor_config::{CreatorConfig, FeeConfig},
+    db_models::{
+        creators::{Creator, EARLY_SELL_SECS},
+        tokens::{PoolCompleted, Token},
+        trades::Trade,
+    },
+    schema::{creators, positions, tokens, trades},
+    utils::{
+        database_connection::get_db_connection, database_utils::ArcDbPool, fees::creator_fee,
+    },
+};
+
+/// Token columns the rollups are computed from
+type CreatorToken = (String, String, i64, bool, BigDecimal, BigDecimal, BigDecimal);
+
+/// Adds the creator fees and the early creator sells of trades to `creators.creator_fees`
+/// and `tokens.creator_sold_24h`, one statement each. Must run inside the transaction that
+/// stores the trades, with only the trades it inserted, so reprocessed trades aren't counted
+/// twice. Creators without a row yet get their fees summed when [`process_creators`] adds it.
+pub async fn accumulate_creator_trades(
+    conn: &mut AsyncPgConnection,
+    fee_config: &FeeConfig,
+    trades: &[Trade],
+) -> QueryResult<()> {
+    if trades.is_empty() {
+        return Ok(());
+    }
+    let launches = tokens::table
+        .filter(tokens::pre_addr.eq_any(trades.iter().map(|trade| trade.token_address.clone())))
+        .select((tokens::pre_addr, tokens::created_by, tokens::ts))
+        .load::<(String, String, i64)>(conn)
+        .await?
+        .into_iter()
+        .map(|(pre_addr, created_by, ts)| (pre_addr, (created_by, ts)))
+        .collect::<AHashMap<String, (String, i64)>>();
+    let mut fees: AHashMap<String, i64> = AHashMap::new();
+    let mut early_sold: AHashMap<String, BigDecimal> = AHashMap::new();
+    for trade in trades {
+        let Some((created_by, launch_ts)) = launches.get(&trade.token_address) else {
+            continue;
+        };
+        let fee = trade
+            .creator_fee
+            .unwrap_or_else(|| creator_fee(fee_config, trade.is_buy, trade.aptos_amount));
+        let total = fees.entry(created_by.clone()).or_default();
+        *total = total.saturating_add(fee);
+        if !trade.is_buy && trade.user_addr == *created_by && trade.ts < launch_ts + EARLY_SELL_SECS
+        {
+            *early_sold.entry(trade.token_address.clone()).or_default() +=
+                BigDecimal::from(trade.token_amount);
+        }
+    }
+
+    let (creators, fees): (Vec<String>, Vec<i64>) = fees.into_iter().unzip();
+    sql_query(
+        "UPDATE creators SET creator_fees = creators.creator_fees + fees.amount \
+         FROM UNNEST($1, $2) AS fees(creator, amount) WHERE creators.creator = fees.creator",
+    )
+    .bind::<Array<Text>, _>(creators)
+    .bind::<Array<BigInt>, _>(fees)
+    .execute(conn)
+    .await?;
+    if !early_sold.is_empty() {
+        let (token_addresses, amounts): (Vec<String>, Vec<BigDecimal>) =
+            early_sold.into_iter().unzip();
+        sql_query(
+            "UPDATE tokens SET creator_sold_24h = tokens.creator_sold_24h + sold.amount \
+             FROM UNNEST($1, $2) AS sold(pre_addr, amount) WHERE tokens.pre_addr = sold.pre_addr",
+        )
+        .bind::<Array<Text>, _>(token_addresses)
+        .bind::<Array<Numeric>, _>(amounts)
+        .execute(conn)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Recomputes the rollups of the creators whose tokens were created, graduated or traded
+/// in the batch from their stored tokens and holders, so this is safe to call again when a
+/// batch is reprocessed and must run after they are stored. Creator fees and early sells are
+/// accumulated by [`accumulate_creator_trades`] as the trades are stored.
+pub async fn process_creators(
+    pool: ArcDbPool,
+    fee_config: &FeeConfig,
+    creator_config: &CreatorConfig,
+    created_tokens: &[Token],
+    pool_completions: &[PoolCompleted],
+    trades: &[Trade],
+    txn_version: i64,
+) -> Result<(), ProcessorError> {
+    if created_tokens.is_empty() && pool_completions.is_empty() && trades.is_empty() {
+        return Ok(());
+    }
+    let conn = &mut get_db_connection(&pool).await?;
+    update_creators(
+        conn,
+        fee_config,
+        creator_config,
+        created_tokens,
+        pool_completions,
+        trades,
+        txn_version,
+    )
+    .await
+    .map_err(|e| {
+        tracing::warn!("Error running query: {:?}", e);
+        ProcessorError::ProcessError {
+            message: e.to_string(),
+        }
+    })
+}
+
+/// Creator fees of the creators that have no row yet, summed from their stored trades.
+async fn get_new_creator_fees(
+    conn: &mut AsyncPgConnection,
+    fee_config: &FeeConfig,
+    creators: &AHashSet<String>,
+    launches: &[CreatorToken],
+) -> QueryResult<AHashMap<String, i64>> {
+    let existing = creators::table
+        .filter(creators::creator.eq_any(creators.iter().cloned()))
+        .select(creators::creator)
+        .load::<String>(conn)
+        .await?
+        .into_iter()
+        .collect::<AHashSet<String>>();
+    let launch_of = launches
+        .iter()
+        .filter(|(_, created_by, ..)| !existing.contains(created_by))
+        .map(|(pre_addr, created_by, ..)| (pre_addr.clone(), created_by.clone()))
+        .collect::<AHashMap<String, String>>();
+    if launch_of.is_empty() {
+        return Ok(AHashMap::new());
+    }
+    let mut fees: AHashMap<String, i64> = AHashMap::new();
+    for (token_address, is_buy, aptos_amount, fee) in trades::table
+        .filter(trades::token_address.eq_any(launch_of.keys().cloned()))
+        .select((
+            trades::token_address,
+            trades::is_buy,
+            trades::aptos_amount,
+            trades::creator_fee,
+        ))
+        .load::<(String, bool, i64, Option<i64>)>(conn)
+        .await?
+    {
+        let Some(created_by) = launch_of.get(&token_address) else {
+            continue;
+        };
+        let fee = fee.unwrap_or_else(|| creator_fee(fee_config, is_buy, aptos_amount));
+        let total = fees.entry(created_by.clone()).or_default();
+        *total = total.saturating_add(fee);
+    }
+    Ok(fees)
+}
+
+async fn update_creators(
+    conn: &mut AsyncPgConnection,
+    fee_config: &FeeConfig,
+    creator_config: &CreatorConfig,
+    created_tokens: &[Token],
+    pool_completions: &[PoolCompleted],
+    trades: &[Trade],
+    txn_version: i64,
+) -> QueryResult<()> {
+    let mut creators = created_tokens
+        .iter()
+        .map(|token| token.created_by.clone())
+        .collect::<AHashSet<String>>();
+    creators.extend(
+        tokens::table
+            .filter(
+                tokens::pre_addr
+                    .eq_any(trades.iter().map(|trade| trade.token_address.clone()))
+                    .or(tokens::main_addr.eq_any(
+                        pool_completions.iter().map(|completed| completed.main_addr.clone()),
+                    )),
+            )
+            .select(tokens::created_by)
+            .distinct()
+            .load::<String>(conn)
+            .await?,
+    );
+    if creators.is_empty() {
+        return Ok(());
+    }
+
+    let launches = tokens::table
+        .filter(tokens::created_by.eq_any(creators.iter().cloned()))
+        .select((
+            tokens::pre_addr,
+            tokens::created_by,
+            tokens::ts,
+            tokens::is_completed,
+            tokens::virtual_token_reserves,
+            tokens::remain_token_reserves,
+            tokens::creator_sold_24h,
+        ))
+        .load::<CreatorToken>(conn)
+        .await?;
+    let token_addresses = launches
+        .iter()
+        .map(|(pre_addr, ..)| pre_addr.clone())
+        .collect::<Vec<String>>();
+    let fees = get_new_creator_fees(conn, fee_config, &creators, &launches).await?;
+
+    let holders = positions::table
+        .filter(positions::token_address.eq_any(&token_addresses))
+        .filter(positions::balance.gt(BigDecimal::zero()))
+        .group_by(positions::token_address)
+        .select((positions::token_address, diesel::dsl::count_star()))
+        .load::<(String, i64)>(conn)
+        .await?
+        .into_iter()
+        .collect::<AHashMap<String, i64>>();
+
+    let mut rows: AHashMap<String, Creator> = AHashMap::new();
+    let mut early_sell_percentages: AHashMap<String, BigDecimal> = AHashMap::new();
+    let mut holder_counts: AHashMap<String, i64> = AHashMap::new();
+    for (
+        pre_addr,
+        created_by,
+        ts,
+        is_completed,
+        virtual_token_reserves,
+        remain_token_reserves,
+        creator_sold_24h,
+    ) in &launches
+    {
+        let sold = creator_sold_24h.clone();
+        let supply = virtual_token_reserves - remain_token_reserves;
+        if !supply.is_zero() {
+            *early_sell_percentages.entry(created_by.clone()).or_default() +=
+                &sold * BigDecimal::from(100) / &supply;
+        }
+        *holder_counts.entry(created_by.clone()).or_default() +=
+            holders.get(pre_addr).copied().unwrap_or_default();
+
+        let row = rows.entry(created_by.clone()).or_insert_with(|| Creator {
+            creator: created_by.clone(),
+            tokens_launched: 0,
+            tokens_graduated: 0,
+            graduation_rate: BigDecimal::zero(),
+            creator_fees: fees.get(created_by).copied().unwrap_or_default(),
+            avg_holders: BigDecimal::zero(),
+            early_sold_token_amount: BigDecimal::zero(),
+            early_sell_percentage: BigDecimal::zero(),
+            trust_badge: String::new(),
+            first_launch_ts: *ts,
+            last_launch_ts: *ts,
+            updated_at_version: txn_version,
+        });
+        row.tokens_launched += 1;
+        row.tokens_graduated += *is_completed as i32;
+        row.early_sold_token_amount += sold;
+        row.first_launch_ts = row.first_launch_ts.min(*ts);
+        row.last_launch_ts = row.last_launch_ts.max(*ts);
+    }
+
+    let mut items_to_insert = rows
+        .into_values()
+        .map(|mut row| {
+            let launched = BigDecimal::from(row.tokens_launched);
+            row.graduation_rate =
+                (BigDecimal::from(row.tokens_graduated * 100) / &launched).round(4);
+            row.avg_holders = (BigDecimal::from(
+                holder_counts.get(&row.creator).copied().unwrap_or_default(),
+            ) / &launched)
+                .round(4);
+            row.early_sell_percentage = (early_sell_percentages
+                .remove(&row.creator)
+                .unwrap_or_default()
+                / &launched)
+                .round(4);
+            row.trust_badge = Creator::trust_badge(
+                creator_config,
+                row.tokens_launched,
+                &row.graduation_rate,
+                &row.early_sell_percentage,
+            )
+            .to_string();
+            row
+        })
+        .collect::<Vec<Creator>>();
+    items_to_insert.sort_by(|a, b| a.creator.cmp(&b.creator));
+    insert_into(creators::table)
+        .values(items_to_insert)
+        .on_conflict(creators::creator)
+        .do_update()
+        .set((
+            creators::tokens_launched.eq(excluded(creators::tokens_launched)),
+            creators::tokens_graduated.eq(excluded(creators::tokens_graduated)),
+            creators::graduation_rate.eq(excluded(creators::graduation_rate)),
+            creators::avg_holders.eq(excluded(creators::avg_holders)),
+            creators::early_sold_token_amount.eq(excluded(creators::early_sold_token_amount)),
+            creators::early_sell_percentage.eq(excluded(creators::early_sell_percentage)),
+            creators::trust_badge.eq(excluded(creators::trust_badge)),
+            creators::first_launch_ts.eq(excluded(creators::first_launch_ts)),
+            creators::last_launch_ts.eq(excluded(creators::last_launch_ts)),
+            creators::updated_at_version.eq(excluded(creators::updated_at_version)),
+        ))
+        .execute(conn)
+        .await?;
+    Ok(())
+}
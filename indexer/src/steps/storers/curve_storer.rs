@@ -0,0 +1,209 @@
+use ahash::AHashMap;
+use anyhow::Result;
+use aptos_indexer_processor_sdk::utils::errors::ProcessorError;
+use bigdecimal::{BigDecimal, ToPrimitive, Zero};
+use diesel::{
+    insert_into, update, BoolExpressionMethods, ExpressionMethods, OptionalExtension, QueryDsl,
+    QueryResult,
+};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+use crate::{
+    config::indexer_processor_config::CurveConfig,
+    db_models::{graduation_signals::GraduationSignal, tokens::PoolCompleted, trades::Trade},
+    schema::{graduation_signals, tokens, trades},
+    utils::{database_connection::get_db_connection, database_utils::ArcDbPool},
+};
+
+/// Token columns the curve is computed from
+type CurveToken = (
+    String,
+    BigDecimal,
+    BigDecimal,
+    Option<BigDecimal>,
+    Option<BigDecimal>,
+    bool,
+    i64,
+);
+
+/// Updates the curve progress, buy velocity and graduation ETA of the tokens traded or
+/// graduated in the batch, and returns the near graduation signals reached for the first
+/// time. Must run after the trades are stored, the velocity is read back from them.
+pub async fn process_curve_progress(
+    pool: ArcDbPool,
+    config: &CurveConfig,
+    trades: &[Trade],
+    pool_completions: &[PoolCompleted],
+) -> Result<Vec<GraduationSignal>, ProcessorError> {
+    if trades.is_empty() && pool_completions.is_empty() {
+        return Ok(vec![]);
+    }
+    let conn = &mut get_db_connection(&pool).await?;
+    update_curves(conn, config, trades, pool_completions)
+        .await
+        .map_err(|e| {
+            tracing::warn!("Error running query: {:?}", e);
+            ProcessorError::ProcessError {
+                message: e.to_string(),
+            }
+        })
+}
+
+async fn update_curves(
+    conn: &mut AsyncPgConnection,
+    config: &CurveConfig,
+    trades: &[Trade],
+    pool_completions: &[PoolCompleted],
+) -> QueryResult<Vec<GraduationSignal>> {
+    let mut last_trades: AHashMap<&str, &Trade> = AHashMap::new();
+    for trade in trades {
+        let last = last_trades.entry(&trade.token_address).or_insert(trade);
+        if trade.txn_version > last.txn_version {
+            *last = trade;
+        }
+    }
+    let curve_tokens = tokens::table
+        .filter(tokens::pre_addr.eq_any(last_trades.keys().map(|addr| addr.to_string())))
+        .select((
+            tokens::pre_addr,
+            tokens::virtual_token_reserves,
+            tokens::virtual_aptos_reserves,
+            tokens::curve_supply,
+            tokens::graduation_aptos_reserves,
+            tokens::is_completed,
+            tokens::ts,
+        ))
+        .load::<CurveToken>(conn)
+        .await?;
+
+    let hundred = BigDecimal::from(100);
+    let thresholds = config
+        .near_graduation_thresholds
+        .iter()
+        .map(|threshold| (*threshold, BigDecimal::from(*threshold)))
+        .collect::<Vec<_>>();
+    let mut signals = vec![];
+    for (
+        pre_addr,
+        virtual_token_reserves,
+        virtual_aptos_reserves,
+        curve_supply,
+        graduation_aptos_reserves,
+        is_completed,
+        launch_ts,
+    ) in curve_tokens
+    {
+        let (Some(trade), Some(curve_supply)) = (last_trades.get(pre_addr.as_str()), curve_supply)
+        else {
+            continue;
+        };
+        if curve_supply.is_zero() {
+            continue;
+        }
+        let sold = &virtual_token_reserves - &trade.virtual_token_reserves;
+        let curve_progress = if is_completed {
+            hundred.clone()
+        } else {
+            (sold * &hundred / &curve_supply)
+                .clamp(BigDecimal::zero(), hundred.clone())
+                .round(4)
+        };
+
+        // Net aptos that went into the curve since the start of the window, young tokens
+        // are measured from their launch
+        let window_start = trade.ts - config.velocity_window_secs;
+        let window_aptos_reserves = trades::table
+            .filter(trades::token_address.eq(&pre_addr))
+            .filter(trades::ts.le(window_start))
+            .order(trades::txn_version.desc())
+            .select(trades::virtual_aptos_reserves)
+            .first::<BigDecimal>(conn)
+            .await
+            .optional()?
+            .unwrap_or(virtual_aptos_reserves);
+        let elapsed = config
+            .velocity_window_secs
+            .min(trade.ts - launch_ts)
+            .max(1);
+        let buy_velocity = ((&trade.virtual_aptos_reserves - window_aptos_reserves)
+            .max(BigDecimal::zero())
+            / BigDecimal::from(elapsed))
+        .round(4);
+
+        let graduation_eta = match graduation_aptos_reserves {
+            Some(graduation_aptos_reserves)
+                if !is_completed
+                    && !buy_velocity.is_zero()
+                    && graduation_aptos_reserves > trade.virtual_aptos_reserves =>
+            {
+                let secs = (graduation_aptos_reserves - &trade.virtual_aptos_reserves)
+                    / &buy_velocity;
+                let whole_secs = secs.with_scale(0);
+                let whole_secs = if whole_secs < secs {
+                    whole_secs + BigDecimal::from(1)
+                } else {
+                    whole_secs
+                };
+                whole_secs
+                    .to_i64()
+                    .map(|secs| trade.ts.saturating_add(secs))
+            }
+            _ => None,
+        };
+
+        // A reprocessed batch doesn't roll back the curve of a later one
+        update(
+            tokens::table.filter(tokens::pre_addr.eq(&pre_addr)).filter(
+                tokens::curve_updated_version
+                    .is_null()
+                    .or(tokens::curve_updated_version.le(trade.txn_version)),
+            ),
+        )
+        .set((
+            tokens::curve_progress.eq(&curve_progress),
+            tokens::buy_velocity.eq(&buy_velocity),
+            tokens::graduation_eta.eq(graduation_eta),
+            tokens::curve_updated_version.eq(trade.txn_version),
+        ))
+        .execute(conn)
+        .await?;
+
+        // Graduated tokens are past the signals
+        signals.extend(
+            thresholds
+                .iter()
+                .filter(|(_, percentage)| !is_completed && curve_progress >= *percentage)
+                .map(|(threshold, _)| GraduationSignal {
+                    token_address: pre_addr.clone(),
+                    threshold: *threshold,
+                    curve_progress: curve_progress.clone(),
+                    graduation_eta,
+                    txn_version: trade.txn_version,
+                    ts: trade.ts,
+                }),
+        );
+    }
+
+    if !pool_completions.is_empty() {
+        update(tokens::table.filter(
+            tokens::main_addr.eq_any(pool_completions.iter().map(|pool| pool.main_addr.clone())),
+        ))
+        .set((
+            tokens::curve_progress.eq(hundred),
+            tokens::graduation_eta.eq(None::<i64>),
+        ))
+        .execute(conn)
+        .await?;
+    }
+
+    if signals.is_empty() {
+        return Ok(signals);
+    }
+    // Only the signals inserted now are new, thresholds already reached are skipped
+    insert_into(graduation_signals::table)
+        .values(signals)
+        .on_conflict((graduation_signals::token_address, graduation_signals::threshold))
+        .do_nothing()
+        .get_results::<GraduationSignal>(conn)
+        .await
+}
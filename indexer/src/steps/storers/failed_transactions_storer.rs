@@ -0,0 +1,52 @@
+use ahash::AHashMap;
+use anyhow::Result;
+use aptos_indexer_processor_sdk::utils::errors::ProcessorError;
+use diesel::{insert_into, QueryResult};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+use crate::{
+    db_models::failed_transactions::FailedTransaction,
+    schema::failed_transactions,
+    utils::{
+        database_connection::get_db_connection,
+        database_utils::{get_config_table_chunk_size, ArcDbPool},
+    },
+};
+
+async fn execute_failed_transactions_sql(
+    conn: &mut AsyncPgConnection,
+    items_to_insert: Vec<FailedTransaction>,
+) -> QueryResult<usize> {
+    insert_into(failed_transactions::table)
+        .values(items_to_insert)
+        .on_conflict(failed_transactions::txn_version)
+        .do_nothing()
+        .execute(conn)
+        .await
+}
+
+pub async fn process_failed_transactions(
+    pool: ArcDbPool,
+    per_table_chunk_sizes: AHashMap<String, usize>,
+    failed_transactions: Vec<FailedTransaction>,
+) -> Result<(), ProcessorError> {
+    if failed_transactions.is_empty() {
+        return Ok(());
+    }
+    let chunk_size = get_config_table_chunk_size::<FailedTransaction>(
+        "failed_transactions",
+        &per_table_chunk_sizes,
+    );
+    let conn = &mut get_db_connection(&pool).await?;
+    for chunk in failed_transactions.chunks(chunk_size) {
+        execute_failed_transactions_sql(conn, chunk.to_vec())
+            .await
+            .map_err(|e| {
+                tracing::warn!("Error running query: {:?}", e);
+                ProcessorError::ProcessError {
+                    message: e.to_string(),
+                }
+            })?;
+    }
+    Ok(())
+}
@@ -3,4 +3,16 @@ pub mod upgrade_package_change_storer;
 pub mod token_events_storer;
 pub mod trade_events_storer;
 pub mod staking_events_storer;
-pub mod spin_events_storer;
\ No newline at end of file
+pub mod spin_events_storer;
+pub mod task_progress_storer;
+pub mod positions_storer;
+pub mod xp_ledger_storer;
+pub mod raw_events_storer;
+pub mod failed_transactions_storer;
+pub mod contract_transactions_storer;
+pub mod whitelist_storer;
+pub mod address_labels_storer;
+pub mod creators_storer;
+pub mod curve_storer;
+pub mod apt_usd_prices_storer;
+pub mod money_configs_storer;
@@ -0,0 +1,108 @@
+use ahash::{AHashMap, AHashSet};
+use anyhow::Result;
+use aptos_indexer_processor_sdk::utils::errors::ProcessorError;
+use diesel::{insert_into, ExpressionMethods, OptionalExtension, QueryDsl, QueryResult};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+use crate::{
+    config::indexer_processor_config::FeeConfig,
+    db_models::{money_configs::MoneyConfig, trades::Trade},
+    schema::money_configs,
+    utils::{
+        database_connection::get_db_connection,
+        database_utils::ArcDbPool,
+        fees::{creator_fee, trade_fee},
+    },
+};
+
+fn db_error(e: diesel::result::Error) -> ProcessorError {
+    tracing::warn!("Error running query: {:?}", e);
+    ProcessorError::ProcessError {
+        message: e.to_string(),
+    }
+}
+
+pub async fn process_money_configs(
+    pool: ArcDbPool,
+    configs: Vec<MoneyConfig>,
+) -> Result<(), ProcessorError> {
+    if configs.is_empty() {
+        return Ok(());
+    }
+    let conn = &mut get_db_connection(&pool).await?;
+    insert_into(money_configs::table)
+        .values(configs)
+        .on_conflict((money_configs::contract_address, money_configs::txn_version))
+        .do_nothing()
+        .execute(conn)
+        .await
+        .map_err(db_error)?;
+    Ok(())
+}
+
+/// Configs of each contract in version order, from the latest one before `first_version` up
+/// to `last_version`.
+async fn load_money_configs(
+    conn: &mut AsyncPgConnection,
+    contract_addresses: AHashSet<String>,
+    first_version: i64,
+    last_version: i64,
+) -> QueryResult<AHashMap<String, Vec<MoneyConfig>>> {
+    let mut configs: AHashMap<String, Vec<MoneyConfig>> = AHashMap::new();
+    for contract_address in contract_addresses {
+        let previous = money_configs::table
+            .filter(money_configs::contract_address.eq(&contract_address))
+            .filter(money_configs::txn_version.lt(first_version))
+            .order(money_configs::txn_version.desc())
+            .first::<MoneyConfig>(conn)
+            .await
+            .optional()?;
+        let batch = money_configs::table
+            .filter(money_configs::contract_address.eq(&contract_address))
+            .filter(money_configs::txn_version.between(first_version, last_version))
+            .order(money_configs::txn_version)
+            .load::<MoneyConfig>(conn)
+            .await?;
+        configs.insert(contract_address, previous.into_iter().chain(batch).collect());
+    }
+    Ok(configs)
+}
+
+/// Sets the fees of the trades of the batch from the latest `Config` write of their
+/// contract at or before their version, falling back to `fee_config` when none was seen.
+/// Must run after the configs of the batch are stored.
+pub async fn process_trade_fees(
+    pool: ArcDbPool,
+    fee_config: &FeeConfig,
+    trades: &mut [Trade],
+) -> Result<(), ProcessorError> {
+    let (Some(first_version), Some(last_version)) = (
+        trades.iter().map(|trade| trade.txn_version).min(),
+        trades.iter().map(|trade| trade.txn_version).max(),
+    ) else {
+        return Ok(());
+    };
+    let contract_addresses = trades
+        .iter()
+        .map(|trade| trade.contract_address.clone())
+        .collect::<AHashSet<String>>();
+    let conn = &mut get_db_connection(&pool).await?;
+    let configs = load_money_configs(conn, contract_addresses, first_version, last_version)
+        .await
+        .map_err(db_error)?;
+    for trade in trades.iter_mut() {
+        let config = configs
+            .get(&trade.contract_address)
+            .and_then(|configs| {
+                configs
+                    .iter()
+                    .rev()
+                    .find(|config| config.txn_version <= trade.txn_version)
+            })
+            .map(MoneyConfig::fee_config)
+            .unwrap_or_else(|| fee_config.clone());
+        trade.fee = Some(trade_fee(&config, trade.is_buy, trade.aptos_amount));
+        trade.creator_fee = Some(creator_fee(&config, trade.is_buy, trade.aptos_amount));
+    }
+    Ok(())
+}
@@ -0,0 +1,78 @@
+use ahash::AHashMap;
+use diesel::{insert_into, upsert::excluded, ExpressionMethods, QueryDsl, QueryResult};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+use crate::{
+    config::indexer_processor_config::FeeConfig,
+    db_models::{
+        positions::{Position, RealizedPnl},
+        trades::Trade,
+    },
+    schema::{positions, realized_pnl},
+};
+
+/// Applies trades to the positions of their (user, token) in version order and records the
+/// pnl realized by each sell in `realized_pnl`. Trades already
+/// applied to a position are skipped, so this is safe to call again when a batch is
+/// reprocessed. Must run inside the transaction that stores the trades, and trades of the
+/// same position must not be stored concurrently.
+pub async fn update_positions(
+    conn: &mut AsyncPgConnection,
+    fee_config: &FeeConfig,
+    trades: &[Trade],
+) -> QueryResult<()> {
+    if trades.is_empty() {
+        return Ok(());
+    }
+    let mut rows: AHashMap<(String, String), Position> = positions::table
+        .filter(positions::user_addr.eq_any(trades.iter().map(|t| t.user_addr.clone())))
+        .filter(positions::token_address.eq_any(trades.iter().map(|t| t.token_address.clone())))
+        .for_update()
+        .load::<Position>(conn)
+        .await?
+        .into_iter()
+        .map(|row| ((row.user_addr.clone(), row.token_address.clone()), row))
+        .collect();
+
+    let mut trades = trades.iter().collect::<Vec<&Trade>>();
+    trades.sort_by_key(|trade| trade.txn_version);
+    let mut realized: Vec<RealizedPnl> = vec![];
+    for trade in trades {
+        realized.extend(
+            rows.entry((trade.user_addr.clone(), trade.token_address.clone()))
+                .or_insert_with(|| {
+                    Position::new(trade.user_addr.clone(), trade.token_address.clone())
+                })
+                .apply_trade(fee_config, trade),
+        );
+    }
+
+    let mut items_to_insert = rows.into_values().collect::<Vec<Position>>();
+    items_to_insert.sort_by(|a, b| {
+        (&a.user_addr, &a.token_address).cmp(&(&b.user_addr, &b.token_address))
+    });
+    insert_into(positions::table)
+        .values(items_to_insert)
+        .on_conflict((positions::user_addr, positions::token_address))
+        .do_update()
+        .set((
+            positions::balance.eq(excluded(positions::balance)),
+            positions::cost_basis.eq(excluded(positions::cost_basis)),
+            positions::realized_pnl.eq(excluded(positions::realized_pnl)),
+            positions::aptos_bought.eq(excluded(positions::aptos_bought)),
+            positions::aptos_sold.eq(excluded(positions::aptos_sold)),
+            positions::last_txn_version.eq(excluded(positions::last_txn_version)),
+            positions::volume.eq(excluded(positions::volume)),
+        ))
+        .execute(conn)
+        .await?;
+    if !realized.is_empty() {
+        insert_into(realized_pnl::table)
+            .values(realized)
+            .on_conflict(realized_pnl::txn_version)
+            .do_nothing()
+            .execute(conn)
+            .await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use ahash::AHashMap;
+use anyhow::Result;
+use aptos_indexer_processor_sdk::utils::errors::ProcessorError;
+use diesel::{insert_into, QueryResult};
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+
+use crate::{
+    db_models::{
+        module_upgrade::ModuleUpgrade,
+        raw_contract_events::{RawContractEvent, RawContractEventOnChain},
+    },
+    schema::raw_contract_events,
+    utils::{
+        abi_decoder::AbiRegistry,
+        database_connection::get_db_connection,
+        database_utils::{get_config_table_chunk_size, ArcDbPool},
+    },
+};
+
+async fn execute_raw_contract_events_sql(
+    conn: &mut AsyncPgConnection,
+    items_to_insert: Vec<RawContractEvent>,
+    chunk_size: usize,
+) -> QueryResult<()> {
+    conn.transaction(|conn| {
+        Box::pin(async move {
+            for chunk in items_to_insert.chunks(chunk_size) {
+                insert_into(raw_contract_events::table)
+                    .values(chunk.to_vec())
+                    .on_conflict((
+                        raw_contract_events::txn_version,
+                        raw_contract_events::kind,
+                        raw_contract_events::event_index,
+                    ))
+                    .do_nothing()
+                    .execute(conn)
+                    .await?;
+            }
+            Ok(())
+        })
+    })
+    .await
+}
+
+/// Loads the registry from `module_upgrade_history` on first use, when the batch has
+/// anything to decode, and applies the module upgrades of the batch once loaded.
+pub async fn sync_abi_registry(
+    pool: ArcDbPool,
+    registry: &mut Option<AbiRegistry>,
+    module_upgrades: &[ModuleUpgrade],
+    needs_registry: bool,
+) -> Result<(), ProcessorError> {
+    let registry = match registry {
+        Some(registry) => registry,
+        None if needs_registry => {
+            let conn = &mut get_db_connection(&pool).await?;
+            registry.insert(AbiRegistry::load(conn).await.map_err(|e| {
+                tracing::warn!("Error running query: {:?}", e);
+                ProcessorError::ProcessError {
+                    message: e.to_string(),
+                }
+            })?)
+        }
+        None => return Ok(()),
+    };
+    for upgrade in module_upgrades {
+        registry.add_upgrade(upgrade);
+    }
+    Ok(())
+}
+
+/// Decodes the raw events of a batch and stores them.
+pub async fn process_raw_contract_events(
+    pool: ArcDbPool,
+    per_table_chunk_sizes: AHashMap<String, usize>,
+    registry: &AbiRegistry,
+    raw_events: Vec<RawContractEventOnChain>,
+) -> Result<(), ProcessorError> {
+    if raw_events.is_empty() {
+        return Ok(());
+    }
+    let conn = &mut get_db_connection(&pool).await?;
+    let items = raw_events
+        .iter()
+        .map(|event| event.to_db_raw_event(registry))
+        .collect::<Vec<RawContractEvent>>();
+    let chunk_size =
+        get_config_table_chunk_size::<RawContractEvent>("raw_contract_events", &per_table_chunk_sizes);
+    execute_raw_contract_events_sql(conn, items, chunk_size)
+        .await
+        .map_err(|e| {
+            tracing::warn!("Error running query: {:?}", e);
+            ProcessorError::ProcessError {
+                message: e.to_string(),
+            }
+        })
+}
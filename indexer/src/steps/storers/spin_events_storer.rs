@@ -1,35 +1,50 @@
 use ahash::AHashMap;
 use anyhow::Result;
 use aptos_indexer_processor_sdk::utils::errors::ProcessorError;
-use diesel::{QueryResult, ExpressionMethods, update, QueryDsl};
-use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use diesel::QueryResult;
+use diesel_async::{AsyncConnection, AsyncPgConnection};
 use crate::api_client::events;
-use crate::db_models::accounts::{Spin, Account};
-use crate::schema::accounts;
-use crate::{
-    utils::{
-        database_connection::get_db_connection,
-        database_utils::{get_config_table_chunk_size, ArcDbPool},
+use crate::db_models::accounts::Spin;
+use crate::db_models::xp_ledger::{XpLedgerEntry, XP_SOURCE_SPIN};
+use crate::steps::{
+    extractor::ContractEvent,
+    storers::{
+        task_progress_storer::update_task_progress, xp_ledger_storer::insert_xp_ledger_entries,
     },
 };
+use crate::utils::{
+    database_connection::get_db_connection,
+    database_utils::{get_config_table_chunk_size, ArcDbPool},
+};
 
 async fn execute_spin_events_sql(
     conn: &mut AsyncPgConnection,
     items_to_update: Vec<Spin>,
+    chunk_size: usize,
+    end_version: i64,
 ) -> QueryResult<()> {
     conn.transaction(|conn| {
         Box::pin(async move {
-            for spin in items_to_update {
-                if spin.win_type == 0 {
-                    let update_account_query = update(accounts::table.filter(accounts::address.eq(spin.claimer.clone())))
-                    .set((
-                        accounts::xp.eq(accounts::xp + spin.amount.try_into().unwrap_or(0)),
-                        accounts::xp_earned.eq(accounts::xp_earned + spin.amount.try_into().unwrap_or(0))
-                    ));
-                    update_account_query.execute(conn).await?;
-                }
-                events::emit_spin_win(spin).await.ok();
+            for chunk in items_to_update.chunks(chunk_size) {
+                // win_type 0 is an xp win
+                let xp_wins = chunk
+                    .iter()
+                    .filter(|spin| spin.win_type == 0)
+                    .map(|spin| XpLedgerEntry {
+                        address: spin.claimer.clone(),
+                        delta: spin.amount.try_into().unwrap_or(0),
+                        source_type: XP_SOURCE_SPIN.to_string(),
+                        source_ref: spin.txn_version.to_string(),
+                        txn_version: Some(spin.txn_version),
+                    })
+                    .collect();
+                insert_xp_ledger_entries(conn, xp_wins).await?;
             }
+            let events = items_to_update
+                .into_iter()
+                .map(ContractEvent::SpinEvent)
+                .collect::<Vec<ContractEvent>>();
+            update_task_progress(conn, &events, end_version, None).await?;
             Ok(())
         })
     })
@@ -40,34 +55,24 @@ pub async fn process_spin_events(
     pool: ArcDbPool,
     per_table_chunk_sizes: AHashMap<String, usize>,
     create_events: Vec<Spin>,
+    end_version: i64,
 ) -> Result<(), ProcessorError> {
+    if create_events.is_empty() {
+        return Ok(());
+    }
     let chunk_size =
-        get_config_table_chunk_size::<Account>("accounts", &per_table_chunk_sizes);
-    let tasks = create_events
-        .chunks(chunk_size)
-        .map(|chunk| {
-            let pool = pool.clone();
-            let items = chunk.to_vec();
-            tokio::spawn(async move {
-                let conn = &mut get_db_connection(&pool)
-                    .await
-                    .expect("Failed to get connection from pool while processing spin events");
-                execute_spin_events_sql(conn, items).await
-            })
-        })
-        .collect::<Vec<_>>();
-
-    let results = futures_util::future::try_join_all(tasks)
+        get_config_table_chunk_size::<XpLedgerEntry>("xp_ledger", &per_table_chunk_sizes);
+    let conn = &mut get_db_connection(&pool).await?;
+    execute_spin_events_sql(conn, create_events.clone(), chunk_size, end_version)
         .await
-        .expect("Task panicked executing in chunks");
-    for res in results {
-        res.map_err(|e| {
+        .map_err(|e| {
             tracing::warn!("Error running query: {:?}", e);
             ProcessorError::ProcessError {
                 message: e.to_string(),
             }
         })?;
+    for spin in create_events {
+        events::emit_spin_win(spin).await.ok();
     }
     Ok(())
 }
-
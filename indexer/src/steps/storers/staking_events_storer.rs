@@ -6,7 +6,9 @@ use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
 
 use crate::{
     db_models::stakings::{RewardClaimed, Staking, StakingRemoved},
+    event_feed::FeedEvent,
     schema::stakings,
+    steps::{extractor::ContractEvent, storers::task_progress_storer::update_task_progress},
     utils::{
         database_connection::get_db_connection,
         database_utils::{get_config_table_chunk_size, ArcDbPool},
@@ -16,14 +18,23 @@ use crate::{
 async fn execute_position_created_events_sql(
     conn: &mut AsyncPgConnection,
     items_to_insert: Vec<Staking>,
+    chunk_size: usize,
+    end_version: i64,
 ) -> QueryResult<()> {
     conn.transaction(|conn| {
         Box::pin(async move {
-            let create_position_query = insert_into(stakings::table)
-                .values(items_to_insert.clone())
-                .on_conflict(stakings::position_addr)
-                .do_nothing();
-            create_position_query.execute(conn).await?;
+            for chunk in items_to_insert.chunks(chunk_size) {
+                let create_position_query = insert_into(stakings::table)
+                    .values(chunk.to_vec())
+                    .on_conflict(stakings::position_addr)
+                    .do_nothing();
+                create_position_query.execute(conn).await?;
+            }
+            let events = items_to_insert
+                .into_iter()
+                .map(ContractEvent::PositionCreated)
+                .collect::<Vec<ContractEvent>>();
+            update_task_progress(conn, &events, end_version, None).await?;
             Ok(())
         })
     })
@@ -34,35 +45,22 @@ pub async fn process_position_created_events(
     pool: ArcDbPool,
     per_table_chunk_sizes: AHashMap<String, usize>,
     create_events: Vec<Staking>,
+    end_version: i64,
 ) -> Result<(), ProcessorError> {
+    if create_events.is_empty() {
+        return Ok(());
+    }
     let chunk_size =
         get_config_table_chunk_size::<Staking>("stakings", &per_table_chunk_sizes);
-    let tasks = create_events
-        .chunks(chunk_size)
-        .map(|chunk| {
-            let pool = pool.clone();
-            let items = chunk.to_vec();
-            tokio::spawn(async move {
-                let conn = &mut get_db_connection(&pool)
-                    .await
-                    .expect("Failed to get connection from pool while processing position created events");
-                execute_position_created_events_sql(conn, items).await
-            })
-        })
-        .collect::<Vec<_>>();
-
-    let results = futures_util::future::try_join_all(tasks)
+    let conn = &mut get_db_connection(&pool).await?;
+    execute_position_created_events_sql(conn, create_events, chunk_size, end_version)
         .await
-        .expect("Task panicked executing in chunks");
-    for res in results {
-        res.map_err(|e| {
+        .map_err(|e| {
             tracing::warn!("Error running query: {:?}", e);
             ProcessorError::ProcessError {
                 message: e.to_string(),
             }
-        })?;
-    }
-    Ok(())
+        })
 }
 
 
@@ -171,3 +169,43 @@ pub async fn process_reward_claimed_events(
     }
     Ok(())
 }
+
+/// Sets the user of the position removed and reward claimed feed events from the stored
+/// stakings, their events only carry the position address.
+pub async fn fill_staking_feed_users(
+    pool: ArcDbPool,
+    feed_events: &mut [FeedEvent],
+) -> Result<(), ProcessorError> {
+    let position_addr = |event: &FeedEvent| {
+        matches!(event.event_type.as_str(), "position_removed" | "reward_claimed")
+            .then(|| event.data.get("position_addr")?.as_str().map(str::to_string))
+            .flatten()
+    };
+    let position_addrs = feed_events
+        .iter()
+        .filter_map(position_addr)
+        .collect::<Vec<String>>();
+    if position_addrs.is_empty() {
+        return Ok(());
+    }
+    let conn = &mut get_db_connection(&pool).await?;
+    let users = stakings::table
+        .filter(stakings::position_addr.eq_any(&position_addrs))
+        .select((stakings::position_addr, stakings::user))
+        .load::<(String, String)>(conn)
+        .await
+        .map_err(|e| {
+            tracing::warn!("Error running query: {:?}", e);
+            ProcessorError::ProcessError {
+                message: e.to_string(),
+            }
+        })?
+        .into_iter()
+        .collect::<AHashMap<String, String>>();
+    for event in feed_events.iter_mut() {
+        if let Some(user) = position_addr(event).and_then(|addr| users.get(&addr)) {
+            event.users = vec![user.clone()];
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,301 @@
+use ahash::AHashMap;
+use bigdecimal::BigDecimal;
+use diesel::{insert_into, upsert::excluded, ExpressionMethods, QueryDsl, QueryResult};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+use crate::{
+    db_models::task_progress::{TaskProgress, TaskRequirement, TaskToken, TradeSide},
+    schema::{task_progress, tasks, trades},
+    steps::extractor::ContractEvent,
+    utils::database_utils::get_config_table_chunk_size,
+};
+
+/// Progress an address made towards a task in a single event.
+enum ProgressUpdate {
+    /// Adds to the accumulated progress.
+    Add(BigDecimal),
+    /// Balance of the held token after a trade, with the trade ts.
+    Balance(BigDecimal, i64),
+}
+
+struct TaskUpdate {
+    task_id: i32,
+    address: String,
+    txn_version: i64,
+    update: ProgressUpdate,
+}
+
+/// Net token balance of a user built from the trades stored before `before_version`.
+async fn get_trade_balance(
+    conn: &mut AsyncPgConnection,
+    user_addr: &str,
+    token_address: &str,
+    before_version: i64,
+) -> QueryResult<BigDecimal> {
+    let sums = trades::table
+        .filter(trades::user_addr.eq(user_addr))
+        .filter(trades::token_address.eq(token_address))
+        .filter(trades::txn_version.lt(before_version))
+        .group_by(trades::is_buy)
+        .select((trades::is_buy, diesel::dsl::sum(trades::token_amount)))
+        .load::<(bool, Option<BigDecimal>)>(conn)
+        .await?;
+    Ok(sums
+        .into_iter()
+        .fold(BigDecimal::from(0), |balance, (is_buy, sum)| {
+            let sum = sum.unwrap_or_else(|| BigDecimal::from(0));
+            if is_buy {
+                balance + sum
+            } else {
+                balance - sum
+            }
+        }))
+}
+
+async fn get_task_updates(
+    conn: &mut AsyncPgConnection,
+    tasks: &[(i32, TaskRequirement)],
+    events: &[ContractEvent],
+) -> QueryResult<Vec<TaskUpdate>> {
+    let first_version = events
+        .iter()
+        .filter_map(|event| match event {
+            ContractEvent::TradeCreatedEvent(trade) => Some(trade.txn_version),
+            _ => None,
+        })
+        .min();
+    let mut balances: AHashMap<(String, String), BigDecimal> = AHashMap::new();
+    let mut updates = vec![];
+
+    for event in events {
+        if let ContractEvent::TradeCreatedEvent(trade) = event {
+            let key = (trade.user_addr.clone(), trade.token_address.clone());
+            if !balances.contains_key(&key) {
+                let balance = get_trade_balance(
+                    conn,
+                    &trade.user_addr,
+                    &trade.token_address,
+                    first_version.unwrap_or(trade.txn_version),
+                )
+                .await?;
+                balances.insert(key.clone(), balance);
+            }
+            let balance = balances.get_mut(&key).unwrap();
+            if trade.is_buy {
+                *balance += BigDecimal::from(trade.token_amount);
+            } else {
+                *balance -= BigDecimal::from(trade.token_amount);
+            }
+        }
+
+        for (task_id, requirement) in tasks {
+            let update = match (requirement, event) {
+                (TaskRequirement::Trade { side, token, .. }, ContractEvent::TradeCreatedEvent(trade)) => {
+                    let side_matches = match side {
+                        TradeSide::Buy => trade.is_buy,
+                        TradeSide::Sell => !trade.is_buy,
+                        TradeSide::Any => true,
+                    };
+                    match token {
+                        _ if !side_matches => None,
+                        TaskToken::Apt => Some((
+                            trade.user_addr.clone(),
+                            trade.txn_version,
+                            ProgressUpdate::Add(BigDecimal::from(trade.aptos_amount)),
+                        )),
+                        TaskToken::Token(token_address) if *token_address == trade.token_address => Some((
+                            trade.user_addr.clone(),
+                            trade.txn_version,
+                            ProgressUpdate::Add(BigDecimal::from(trade.token_amount)),
+                        )),
+                        TaskToken::Token(_) => None,
+                    }
+                }
+                (TaskRequirement::Hold { token_address, .. }, ContractEvent::TradeCreatedEvent(trade))
+                    if *token_address == trade.token_address =>
+                {
+                    let balance = balances
+                        .get(&(trade.user_addr.clone(), trade.token_address.clone()))
+                        .cloned()
+                        .unwrap_or_else(|| BigDecimal::from(0));
+                    Some((
+                        trade.user_addr.clone(),
+                        trade.txn_version,
+                        ProgressUpdate::Balance(balance, trade.ts),
+                    ))
+                }
+                (TaskRequirement::CreateToken { .. }, ContractEvent::TokenCreatedEvent(token)) => Some((
+                    token.created_by.clone(),
+                    token.txn_version,
+                    ProgressUpdate::Add(BigDecimal::from(1)),
+                )),
+                (TaskRequirement::Stake { .. }, ContractEvent::PositionCreated(staking)) => Some((
+                    staking.user.clone(),
+                    staking.txn_version,
+                    ProgressUpdate::Add(BigDecimal::from(staking.amount)),
+                )),
+                (TaskRequirement::Spin { .. }, ContractEvent::SpinEvent(spin)) => Some((
+                    spin.claimer.clone(),
+                    spin.txn_version,
+                    ProgressUpdate::Add(BigDecimal::from(1)),
+                )),
+                _ => None,
+            };
+            if let Some((address, txn_version, update)) = update {
+                updates.push(TaskUpdate {
+                    task_id: *task_id,
+                    address,
+                    txn_version,
+                    update,
+                });
+            }
+        }
+    }
+    Ok(updates)
+}
+
+/// Evaluates the on-chain task requirements against the events of a batch and updates
+/// `task_progress`. Must run inside the transaction that stores the events, after they are
+/// inserted since hold requirements read balances from `trades`. Every task is fed by a
+/// single kind of event, so each storer passes all of its events of the batch in one call.
+/// Open holds are refreshed against `end_ts` when given.
+pub async fn update_task_progress(
+    conn: &mut AsyncPgConnection,
+    events: &[ContractEvent],
+    end_version: i64,
+    end_ts: Option<i64>,
+) -> QueryResult<()> {
+    if events.is_empty() && end_ts.is_none() {
+        return Ok(());
+    }
+    let tasks = tasks::table
+        .select((tasks::id, tasks::requirement))
+        .load::<(i32, Option<serde_json::Value>)>(conn)
+        .await?
+        .into_iter()
+        .filter_map(|(id, requirement)| {
+            TaskRequirement::from_json(requirement.as_ref()?).map(|r| (id, r))
+        })
+        .collect::<Vec<(i32, TaskRequirement)>>();
+    if tasks.is_empty() {
+        return Ok(());
+    }
+    let requirements = tasks.iter().cloned().collect::<AHashMap<i32, TaskRequirement>>();
+
+    let updates = get_task_updates(conn, &tasks, events).await?;
+    let mut rows: AHashMap<(i32, String), TaskProgress> = if updates.is_empty() {
+        AHashMap::new()
+    } else {
+        task_progress::table
+            .filter(task_progress::task_id.eq_any(updates.iter().map(|u| u.task_id)))
+            .filter(task_progress::address.eq_any(updates.iter().map(|u| u.address.clone())))
+            .load::<TaskProgress>(conn)
+            .await?
+            .into_iter()
+            .map(|row| ((row.task_id, row.address.clone()), row))
+            .collect()
+    };
+    // Versions the rows were stored at. Events at or below them were applied by an earlier
+    // call, e.g. when reprocessing a batch. Several events of one txn are all applied.
+    let applied_versions = rows
+        .iter()
+        .map(|(key, row)| (key.clone(), row.last_txn_version))
+        .collect::<AHashMap<(i32, String), i64>>();
+
+    for TaskUpdate {
+        task_id,
+        address,
+        txn_version,
+        update,
+    } in updates
+    {
+        let requirement = &requirements[&task_id];
+        let key = (task_id, address);
+        if applied_versions
+            .get(&key)
+            .is_some_and(|applied_version| txn_version <= *applied_version)
+        {
+            continue;
+        }
+        let row = rows
+            .entry(key.clone())
+            .or_insert_with(|| TaskProgress::new(key.0, key.1));
+        row.last_txn_version = row.last_txn_version.max(txn_version);
+        match (update, requirement) {
+            (ProgressUpdate::Add(amount), _) => {
+                row.progress += amount;
+                if row.completed_at_version.is_none() && row.progress >= requirement.target() {
+                    row.completed_at_version = Some(txn_version);
+                }
+            }
+            (ProgressUpdate::Balance(balance, ts), TaskRequirement::Hold { amount, .. }) => {
+                if balance >= *amount {
+                    row.hold_started_at.get_or_insert(ts);
+                } else {
+                    row.hold_started_at = None;
+                    if row.completed_at_version.is_none() {
+                        row.progress = BigDecimal::from(0);
+                    }
+                }
+            }
+            (ProgressUpdate::Balance(..), _) => {}
+        }
+    }
+
+    // Hold progress grows with time, so refresh every open hold against the batch clock
+    if let Some(end_ts) = end_ts {
+        let hold_task_ids = tasks
+            .iter()
+            .filter(|(_, r)| matches!(r, TaskRequirement::Hold { .. }))
+            .map(|(id, _)| *id)
+            .collect::<Vec<i32>>();
+        if !hold_task_ids.is_empty() {
+            let open_holds = task_progress::table
+                .filter(task_progress::task_id.eq_any(hold_task_ids))
+                .filter(task_progress::hold_started_at.is_not_null())
+                .filter(task_progress::completed_at_version.is_null())
+                .load::<TaskProgress>(conn)
+                .await?;
+            for open_hold in open_holds {
+                rows.entry((open_hold.task_id, open_hold.address.clone()))
+                    .or_insert(open_hold);
+            }
+        }
+        for row in rows.values_mut() {
+            if let (Some(TaskRequirement::Hold { duration, .. }), Some(started_at), None) = (
+                requirements.get(&row.task_id),
+                row.hold_started_at,
+                row.completed_at_version,
+            ) {
+                let held = (end_ts - started_at).clamp(0, *duration);
+                row.progress = BigDecimal::from(held);
+                if held >= *duration {
+                    row.completed_at_version = Some(end_version);
+                }
+            }
+        }
+    }
+
+    let chunk_size =
+        get_config_table_chunk_size::<TaskProgress>("task_progress", &AHashMap::new());
+    let items_to_insert = rows
+        .into_values()
+        .filter(|row| row.last_txn_version >= 0)
+        .collect::<Vec<TaskProgress>>();
+    for chunk in items_to_insert.chunks(chunk_size) {
+        insert_into(task_progress::table)
+            .values(chunk.to_vec())
+            .on_conflict((task_progress::task_id, task_progress::address))
+            .do_update()
+            .set((
+                task_progress::progress.eq(excluded(task_progress::progress)),
+                task_progress::hold_started_at.eq(excluded(task_progress::hold_started_at)),
+                task_progress::completed_at_version
+                    .eq(excluded(task_progress::completed_at_version)),
+                task_progress::last_txn_version.eq(excluded(task_progress::last_txn_version)),
+            ))
+            .execute(conn)
+            .await?;
+    }
+    Ok(())
+}
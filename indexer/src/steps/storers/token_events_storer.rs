@@ -7,6 +7,7 @@ use crate::api_client::events;
 use crate::{
     db_models::tokens::{PoolCompleted, Token},
     schema::tokens,
+    steps::{extractor::ContractEvent, storers::task_progress_storer::update_task_progress},
     utils::{
         database_connection::get_db_connection,
         database_utils::{get_config_table_chunk_size, ArcDbPool},
@@ -16,14 +17,23 @@ use crate::{
 async fn execute_token_created_events_sql(
     conn: &mut AsyncPgConnection,
     items_to_insert: Vec<Token>,
+    chunk_size: usize,
+    end_version: i64,
 ) -> QueryResult<()> {
     conn.transaction(|conn| {
         Box::pin(async move {
-            let create_token_query = insert_into(tokens::table)
-                .values(items_to_insert.clone())
-                .on_conflict(tokens::pool_addr)
-                .do_nothing();
-            create_token_query.execute(conn).await?;
+            for chunk in items_to_insert.chunks(chunk_size) {
+                let create_token_query = insert_into(tokens::table)
+                    .values(chunk.to_vec())
+                    .on_conflict(tokens::pool_addr)
+                    .do_nothing();
+                create_token_query.execute(conn).await?;
+            }
+            let events = items_to_insert
+                .into_iter()
+                .map(ContractEvent::TokenCreatedEvent)
+                .collect::<Vec<ContractEvent>>();
+            update_task_progress(conn, &events, end_version, None).await?;
             Ok(())
         })
     })
@@ -34,34 +44,22 @@ pub async fn process_token_created_events(
     pool: ArcDbPool,
     per_table_chunk_sizes: AHashMap<String, usize>,
     create_events: Vec<Token>,
+    end_version: i64,
 ) -> Result<(), ProcessorError> {
+    if create_events.is_empty() {
+        return Ok(());
+    }
     let chunk_size =
         get_config_table_chunk_size::<Token>("tokens", &per_table_chunk_sizes);
-    let tasks = create_events
-        .chunks(chunk_size)
-        .map(|chunk| {
-            let pool = pool.clone();
-            let items = chunk.to_vec();
-            tokio::spawn(async move {
-                let conn = &mut get_db_connection(&pool)
-                    .await
-                    .expect("Failed to get connection from pool while processing token created events");
-                execute_token_created_events_sql(conn, items).await
-            })
-        })
-        .collect::<Vec<_>>();
-
-    let results = futures_util::future::try_join_all(tasks)
+    let conn = &mut get_db_connection(&pool).await?;
+    execute_token_created_events_sql(conn, create_events.clone(), chunk_size, end_version)
         .await
-        .expect("Task panicked executing in chunks");
-    for res in results {
-        res.map_err(|e| {
+        .map_err(|e| {
             tracing::warn!("Error running query: {:?}", e);
             ProcessorError::ProcessError {
                 message: e.to_string(),
             }
         })?;
-    }
 
     for item in create_events {
         events::emit_token_created(item.pre_addr).await.ok();
@@ -1,69 +1,106 @@
-use ahash::AHashMap;
+use ahash::{AHashMap, AHashSet};
 use anyhow::Result;
 use aptos_indexer_processor_sdk::utils::errors::ProcessorError;
 use diesel::{insert_into, QueryResult};
 use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
 use crate::api_client::events;
 use crate::{
+    config::indexer_processor_config::{FeeConfig, XpConfig},
     db_models::trades::Trade,
     schema::trades,
+    steps::{
+        extractor::ContractEvent,
+        storers::{
+            creators_storer::accumulate_creator_trades, positions_storer::update_positions,
+            task_progress_storer::update_task_progress, xp_ledger_storer::insert_xp_ledger_entries,
+        },
+    },
     utils::{
         database_connection::get_db_connection,
         database_utils::{get_config_table_chunk_size, ArcDbPool},
+        xp_rules::trade_xp_reward,
     },
 };
 
 async fn execute_trade_created_events_sql(
     conn: &mut AsyncPgConnection,
     items_to_insert: Vec<Trade>,
+    chunk_size: usize,
+    xp_config: XpConfig,
+    fee_config: FeeConfig,
+    end_version: i64,
+    end_ts: Option<i64>,
 ) -> QueryResult<()> {
     conn.transaction(|conn| {
         Box::pin(async move {
-            let create_trade_query = insert_into(trades::table)
-                .values(items_to_insert.clone())
-                .on_conflict(trades::txn_version)
-                .do_nothing();
-            create_trade_query.execute(conn).await?;
+            // Chunks are stored one after another, positions must see trades in version order
+            for chunk in items_to_insert.chunks(chunk_size) {
+                let create_trade_query = insert_into(trades::table)
+                    .values(chunk.to_vec())
+                    .on_conflict(trades::txn_version)
+                    .do_nothing()
+                    .returning(trades::txn_version);
+                let inserted = create_trade_query
+                    .get_results::<i64>(conn)
+                    .await?
+                    .into_iter()
+                    .collect::<AHashSet<i64>>();
+                let inserted_trades = chunk
+                    .iter()
+                    .filter(|trade| inserted.contains(&trade.txn_version))
+                    .cloned()
+                    .collect::<Vec<Trade>>();
+                accumulate_creator_trades(conn, &fee_config, &inserted_trades).await?;
+                let xp_rewards = chunk
+                    .iter()
+                    .filter_map(|trade| trade_xp_reward(&xp_config, trade))
+                    .collect();
+                insert_xp_ledger_entries(conn, xp_rewards).await?;
+                update_positions(conn, &fee_config, chunk).await?;
+            }
+            let events = items_to_insert
+                .into_iter()
+                .map(ContractEvent::TradeCreatedEvent)
+                .collect::<Vec<ContractEvent>>();
+            update_task_progress(conn, &events, end_version, end_ts).await?;
             Ok(())
         })
     })
     .await
 }
 
+/// Stores the trades of a batch with their xp, positions, creator fees and task progress in
+/// one transaction. Runs for every batch, since open holds are refreshed against `end_ts`.
 pub async fn process_trade_created_events(
     pool: ArcDbPool,
     per_table_chunk_sizes: AHashMap<String, usize>,
+    xp_config: XpConfig,
+    fee_config: FeeConfig,
     create_events: Vec<Trade>,
+    end_version: i64,
+    end_ts: Option<i64>,
 ) -> Result<(), ProcessorError> {
     let chunk_size =
         get_config_table_chunk_size::<Trade>("trades", &per_table_chunk_sizes);
-    let tasks = create_events
-        .chunks(chunk_size)
-        .map(|chunk| {
-            let pool = pool.clone();
-            let items = chunk.to_vec();
-            tokio::spawn(async move {
-                let conn = &mut get_db_connection(&pool)
-                    .await
-                    .expect("Failed to get connection from pool while processing trade created events");
-                execute_trade_created_events_sql(conn, items).await
-            })
-        })
-        .collect::<Vec<_>>();
-
-    let results = futures_util::future::try_join_all(tasks)
-        .await
-        .expect("Task panicked executing in chunks");
-    for res in results {
-        res.map_err(|e| {
-            tracing::warn!("Error running query: {:?}", e);
-            ProcessorError::ProcessError {
-                message: e.to_string(),
-            }
-        })?;
-    }
+    let conn = &mut get_db_connection(&pool).await?;
+    execute_trade_created_events_sql(
+        conn,
+        create_events.clone(),
+        chunk_size,
+        xp_config,
+        fee_config,
+        end_version,
+        end_ts,
+    )
+    .await
+    .map_err(|e| {
+        tracing::warn!("Error running query: {:?}", e);
+        ProcessorError::ProcessError {
+            message: e.to_string(),
+        }
+    })?;
     for trade in create_events {
         events::emit_token_traded(trade.txn_version).await.ok();
     }
     Ok(())
-}
\ No newline at end of file
+}
@@ -1,13 +1,15 @@
 use ahash::AHashMap;
 use anyhow::Result;
 use aptos_indexer_processor_sdk::utils::errors::ProcessorError;
-use diesel::{insert_into, QueryResult};
+use diesel::{insert_into, ExpressionMethods, OptionalExtension, QueryDsl, QueryResult};
 use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
 
 use crate::{
-    db_models::module_upgrade::ModuleUpgrade,
-    schema::module_upgrade_history,
+    config::indexer_processor_config::UpgradeConfig,
+    db_models::module_upgrade::{ModuleAbiChange, ModuleUpgrade},
+    schema::{module_abi_changes, module_upgrade_history},
     utils::{
+        abi_diff::diff_module_abis,
         database_connection::get_db_connection,
         database_utils::{get_config_table_chunk_size, ArcDbPool},
     },
@@ -34,11 +36,127 @@ async fn execute_upgrade_module_changes_sql(
     .await
 }
 
+/// Diffs every upgrade against the previous upgrade of the same module, from the batch or
+/// the database, and stores the changes.
+async fn execute_abi_changes_sql(
+    conn: &mut AsyncPgConnection,
+    upgrades: &[ModuleUpgrade],
+    chunk_size: usize,
+) -> QueryResult<Vec<ModuleAbiChange>> {
+    // Partial upgrades may lack the abi, they are neither diffed nor diffed against
+    let mut upgrades = upgrades
+        .iter()
+        .filter(|upgrade| upgrade.partial_reason.is_none())
+        .collect::<Vec<_>>();
+    upgrades.sort_by(|a, b| {
+        (&a.module_addr, &a.module_name, a.upgrade_number)
+            .cmp(&(&b.module_addr, &b.module_name, b.upgrade_number))
+    });
+    let mut previous: AHashMap<(String, String), (i64, serde_json::Value)> = AHashMap::new();
+    let mut changes = vec![];
+    for upgrade in upgrades {
+        let key = (upgrade.module_addr.clone(), upgrade.module_name.clone());
+        let prior = match previous.get(&key) {
+            Some(prior) => Some(prior.clone()),
+            None => module_upgrade_history::table
+                .filter(module_upgrade_history::module_addr.eq(&upgrade.module_addr))
+                .filter(module_upgrade_history::module_name.eq(&upgrade.module_name))
+                .filter(module_upgrade_history::upgrade_number.lt(upgrade.upgrade_number))
+                .filter(module_upgrade_history::partial_reason.is_null())
+                .order(module_upgrade_history::upgrade_number.desc())
+                .select((
+                    module_upgrade_history::upgrade_number,
+                    module_upgrade_history::module_abi,
+                ))
+                .first::<(i64, serde_json::Value)>(conn)
+                .await
+                .optional()?,
+        };
+        if let Some((previous_upgrade_number, previous_abi)) = prior {
+            changes.extend(
+                diff_module_abis(&upgrade.module_name, &previous_abi, &upgrade.module_abi)
+                    .into_iter()
+                    .map(|change| ModuleAbiChange::new(upgrade, previous_upgrade_number, change)),
+            );
+        }
+        previous.insert(key, (upgrade.upgrade_number, upgrade.module_abi.clone()));
+    }
+
+    for chunk in changes.chunks(chunk_size) {
+        insert_into(module_abi_changes::table)
+            .values(chunk.to_vec())
+            .on_conflict((
+                module_abi_changes::module_addr,
+                module_abi_changes::module_name,
+                module_abi_changes::upgrade_number,
+                module_abi_changes::item_kind,
+                module_abi_changes::item_name,
+            ))
+            .do_nothing()
+            .execute(conn)
+            .await?;
+    }
+    Ok(changes)
+}
+
+/// Records the ABI changes of the upgrades and warns about the ones that break the decoding
+/// of an event, halting the processor on them when configured to.
+async fn process_abi_changes(
+    pool: &ArcDbPool,
+    per_table_chunk_sizes: &AHashMap<String, usize>,
+    upgrade_config: &UpgradeConfig,
+    upgrade_changes: &[ModuleUpgrade],
+) -> Result<(), ProcessorError> {
+    let chunk_size =
+        get_config_table_chunk_size::<ModuleAbiChange>("module_abi_changes", per_table_chunk_sizes);
+    let conn = &mut get_db_connection(pool).await?;
+    let changes = execute_abi_changes_sql(conn, upgrade_changes, chunk_size)
+        .await
+        .map_err(|e| {
+            tracing::warn!("Error running query: {:?}", e);
+            ProcessorError::ProcessError {
+                message: e.to_string(),
+            }
+        })?;
+
+    let breaking = changes
+        .iter()
+        .filter(|change| change.is_breaking)
+        .collect::<Vec<_>>();
+    for change in breaking.iter() {
+        tracing::warn!(
+            module_addr = change.module_addr,
+            module_name = change.module_name,
+            upgrade_number = change.upgrade_number,
+            tx_version = change.tx_version,
+            "Upgrade {} {} {} of a decoded event",
+            change.change_type,
+            change.item_kind,
+            change.item_name
+        );
+    }
+    if !breaking.is_empty() && upgrade_config.halt_on_breaking_change {
+        return Err(ProcessorError::ProcessError {
+            message: format!(
+                "Halting on {} breaking ABI changes, see module_abi_changes at tx_version {}",
+                breaking.len(),
+                breaking[0].tx_version
+            ),
+        });
+    }
+    Ok(())
+}
+
 pub async fn process_upgrade_module_changes(
     pool: ArcDbPool,
     per_table_chunk_sizes: AHashMap<String, usize>,
+    upgrade_config: &UpgradeConfig,
     upgrade_changes: Vec<ModuleUpgrade>,
 ) -> Result<(), ProcessorError> {
+    if !upgrade_changes.is_empty() {
+        process_abi_changes(&pool, &per_table_chunk_sizes, upgrade_config, &upgrade_changes)
+            .await?;
+    }
     let chunk_size = get_config_table_chunk_size::<ModuleUpgrade>(
         "module_upgrade_history",
         &per_table_chunk_sizes,
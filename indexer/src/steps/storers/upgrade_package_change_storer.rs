@@ -5,8 +5,8 @@ use diesel::{insert_into, QueryResult};
 use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
 
 use crate::{
-    db_models::package_upgrade::PackageUpgrade,
-    schema::package_upgrade_history,
+    db_models::package_upgrade::{PackageDependency, PackageUpgrade},
+    schema::{package_dependencies, package_upgrade_history},
     utils::{
         database_connection::get_db_connection,
         database_utils::{get_config_table_chunk_size, ArcDbPool},
@@ -34,6 +34,28 @@ async fn execute_upgrade_package_changes_sql(
     .await
 }
 
+async fn execute_package_dependencies_sql(
+    conn: &mut AsyncPgConnection,
+    items_to_insert: Vec<PackageDependency>,
+) -> QueryResult<()> {
+    conn.transaction(|conn| {
+        Box::pin(async move {
+            let create_package_dependency_query = insert_into(package_dependencies::table)
+                .values(items_to_insert.clone())
+                .on_conflict((
+                    package_dependencies::package_addr,
+                    package_dependencies::package_name,
+                    package_dependencies::upgrade_number,
+                    package_dependencies::dependency_name,
+                ))
+                .do_nothing();
+            create_package_dependency_query.execute(conn).await?;
+            Ok(())
+        })
+    })
+    .await
+}
+
 pub async fn process_upgrade_package_changes(
     pool: ArcDbPool,
     per_table_chunk_sizes: AHashMap<String, usize>,
@@ -70,3 +92,29 @@ pub async fn process_upgrade_package_changes(
     }
     Ok(())
 }
+
+pub async fn process_package_dependencies(
+    pool: ArcDbPool,
+    per_table_chunk_sizes: AHashMap<String, usize>,
+    dependencies: Vec<PackageDependency>,
+) -> Result<(), ProcessorError> {
+    if dependencies.is_empty() {
+        return Ok(());
+    }
+    let chunk_size = get_config_table_chunk_size::<PackageDependency>(
+        "package_dependencies",
+        &per_table_chunk_sizes,
+    );
+    let conn = &mut get_db_connection(&pool).await?;
+    for chunk in dependencies.chunks(chunk_size) {
+        execute_package_dependencies_sql(conn, chunk.to_vec())
+            .await
+            .map_err(|e| {
+                tracing::warn!("Error running query: {:?}", e);
+                ProcessorError::ProcessError {
+                    message: e.to_string(),
+                }
+            })?;
+    }
+    Ok(())
+}
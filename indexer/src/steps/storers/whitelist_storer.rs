@@ -0,0 +1,98 @@
+use ahash::AHashMap;
+use anyhow::Result;
+use aptos_indexer_processor_sdk::utils::errors::ProcessorError;
+use diesel::{insert_into, ExpressionMethods, QueryDsl, QueryResult};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+use crate::{
+    db_models::{trades::Trade, whitelist_windows::WhitelistWindow},
+    schema::whitelist_windows,
+    utils::{
+        database_connection::get_db_connection,
+        database_utils::{get_config_table_chunk_size, ArcDbPool},
+    },
+};
+
+async fn execute_whitelist_windows_sql(
+    conn: &mut AsyncPgConnection,
+    items_to_insert: Vec<WhitelistWindow>,
+) -> QueryResult<usize> {
+    insert_into(whitelist_windows::table)
+        .values(items_to_insert)
+        .on_conflict(whitelist_windows::token_address)
+        .do_nothing()
+        .execute(conn)
+        .await
+}
+
+pub async fn process_whitelist_windows(
+    pool: ArcDbPool,
+    per_table_chunk_sizes: AHashMap<String, usize>,
+    windows: Vec<WhitelistWindow>,
+) -> Result<(), ProcessorError> {
+    if windows.is_empty() {
+        return Ok(());
+    }
+    let chunk_size = get_config_table_chunk_size::<WhitelistWindow>(
+        "whitelist_windows",
+        &per_table_chunk_sizes,
+    );
+    let conn = &mut get_db_connection(&pool).await?;
+    for chunk in windows.chunks(chunk_size) {
+        execute_whitelist_windows_sql(conn, chunk.to_vec())
+            .await
+            .map_err(|e| {
+                tracing::warn!("Error running query: {:?}", e);
+                ProcessorError::ProcessError {
+                    message: e.to_string(),
+                }
+            })?;
+    }
+    Ok(())
+}
+
+/// Flags every trade as within or after the whitelist window of its token. Windows of
+/// tokens created in earlier batches are read from the database, trades of tokens without
+/// a known window are left unflagged.
+pub async fn flag_whitelist_trades(
+    pool: ArcDbPool,
+    windows: &[WhitelistWindow],
+    trades: &mut [Trade],
+) -> Result<(), ProcessorError> {
+    if trades.is_empty() {
+        return Ok(());
+    }
+    let mut windows = windows
+        .iter()
+        .map(|window| (window.token_address.clone(), window.clone()))
+        .collect::<AHashMap<String, WhitelistWindow>>();
+    let missing = trades
+        .iter()
+        .map(|trade| trade.token_address.clone())
+        .filter(|token_address| !windows.contains_key(token_address))
+        .collect::<Vec<String>>();
+    if !missing.is_empty() {
+        let conn = &mut get_db_connection(&pool).await?;
+        let stored = whitelist_windows::table
+            .filter(whitelist_windows::token_address.eq_any(missing))
+            .load::<WhitelistWindow>(conn)
+            .await
+            .map_err(|e| {
+                tracing::warn!("Error running query: {:?}", e);
+                ProcessorError::ProcessError {
+                    message: e.to_string(),
+                }
+            })?;
+        windows.extend(
+            stored
+                .into_iter()
+                .map(|window| (window.token_address.clone(), window)),
+        );
+    }
+    for trade in trades.iter_mut() {
+        trade.in_whitelist_window = windows
+            .get(&trade.token_address)
+            .map(|window| window.contains(trade.ts));
+    }
+    Ok(())
+}
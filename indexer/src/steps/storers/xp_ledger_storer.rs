@@ -0,0 +1,55 @@
+use ahash::AHashMap;
+use diesel::{insert_into, upsert::excluded, ExpressionMethods, QueryResult};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+use crate::{
+    db_models::{accounts::Account, xp_ledger::XpLedgerEntry},
+    schema::{accounts, xp_ledger},
+};
+
+/// Appends entries to the xp ledger and applies the newly inserted ones to `accounts`.
+/// Entries whose (source_type, source_ref) is already in the ledger are skipped, so this
+/// is safe to call again when a batch is reprocessed. Must run inside the transaction
+/// that stores the entries' source.
+pub async fn insert_xp_ledger_entries(
+    conn: &mut AsyncPgConnection,
+    entries: Vec<XpLedgerEntry>,
+) -> QueryResult<()> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let inserted = insert_into(xp_ledger::table)
+        .values(entries)
+        .on_conflict((xp_ledger::source_type, xp_ledger::source_ref))
+        .do_nothing()
+        .returning((xp_ledger::address, xp_ledger::delta))
+        .get_results::<(String, i32)>(conn)
+        .await?;
+
+    let mut deltas: AHashMap<String, (i32, i32)> = AHashMap::new();
+    for (address, delta) in inserted {
+        let (xp, xp_earned) = deltas.entry(address).or_insert((0, 0));
+        *xp += delta;
+        *xp_earned += delta.max(0);
+    }
+    // Update accounts in a stable order so concurrent chunks lock rows in the same order
+    let mut deltas = deltas.into_iter().collect::<Vec<(String, (i32, i32))>>();
+    deltas.sort_by(|(a, _), (b, _)| a.cmp(b));
+    for (address, (xp, xp_earned)) in deltas {
+        insert_into(accounts::table)
+            .values(Account {
+                address,
+                xp,
+                xp_earned,
+            })
+            .on_conflict(accounts::address)
+            .do_update()
+            .set((
+                accounts::xp.eq(accounts::xp + excluded(accounts::xp)),
+                accounts::xp_earned.eq(accounts::xp_earned + excluded(accounts::xp_earned)),
+            ))
+            .execute(conn)
+            .await?;
+    }
+    Ok(())
+}
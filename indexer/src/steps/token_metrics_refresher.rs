@@ -0,0 +1,278 @@
+use ahash::AHashMap;
+use anyhow::Result;
+use aptos_indexer_processor_sdk::{
+    traits::{NamedStep, PollableAsyncRunType, PollableAsyncStep, Processable},
+    types::transaction_context::TransactionContext,
+    utils::errors::ProcessorError,
+};
+use async_trait::async_trait;
+use bigdecimal::{BigDecimal, Zero};
+use diesel::{insert_into, BoolExpressionMethods, ExpressionMethods, QueryDsl, QueryResult};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use std::marker::PhantomData;
+
+use crate::{
+    config::indexer_processor_config::{PriceConfig, TokenMetricsConfig},
+    db_models::token_metrics::{MetricsToken, PoolReserves, TokenMetrics, METRICS_WINDOW_SECS},
+    schema::{token_metrics, tokens, trades},
+    utils::{
+        apt_usd_prices::apt_usd_price_at, database_connection::get_db_connection,
+        database_utils::ArcDbPool,
+    },
+};
+
+/// TokenMetricsRefresher is a pass-through step that periodically snapshots the market of
+/// the tokens still on their curve and of the tokens traded in the last 24h into
+/// `token_metrics`, one snapshot per interval of chain time.
+pub struct TokenMetricsRefresher<T>
+where
+    Self: Sized + Send + 'static,
+    T: Send + 'static,
+{
+    pool: ArcDbPool,
+    config: TokenMetricsConfig,
+    price_config: PriceConfig,
+    // Chain timestamp of the latest batch seen, snapshots are aligned on it
+    latest_ts: Option<i64>,
+    // Latest snapshot taken, a snapshot is taken once per interval
+    snapshot_ts: Option<i64>,
+    _marker: PhantomData<T>,
+}
+
+impl<T> TokenMetricsRefresher<T>
+where
+    Self: Sized + Send + 'static,
+    T: Send + 'static,
+{
+    pub fn new(pool: ArcDbPool, config: TokenMetricsConfig, price_config: PriceConfig) -> Self {
+        Self {
+            pool,
+            config,
+            price_config,
+            latest_ts: None,
+            snapshot_ts: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Takes the snapshot of every interval since the latest one stored, so the buckets missed
+    /// while the indexer was down or catching up are filled in. An empty table starts at the
+    /// current interval.
+    async fn snapshot_token_metrics(&mut self) -> Result<(), ProcessorError> {
+        let conn = &mut get_db_connection(&self.pool).await?;
+        let now = match self.latest_ts {
+            Some(ts) => ts,
+            None => match trades::table
+                .select(diesel::dsl::max(trades::ts))
+                .first::<Option<i64>>(conn)
+                .await
+                .map_err(db_error)?
+            {
+                Some(ts) => ts,
+                None => return Ok(()),
+            },
+        };
+        let interval = self.config.snapshot_interval_secs;
+        let latest_snapshot_ts = now - now.rem_euclid(interval);
+        let last_snapshot_ts = match self.snapshot_ts {
+            Some(ts) => Some(ts),
+            None => token_metrics::table
+                .select(diesel::dsl::max(token_metrics::snapshot_ts))
+                .first::<Option<i64>>(conn)
+                .await
+                .map_err(db_error)?,
+        };
+        let mut snapshot_ts = match last_snapshot_ts {
+            Some(ts) => ts - ts.rem_euclid(interval) + interval,
+            None => latest_snapshot_ts,
+        };
+
+        while snapshot_ts <= latest_snapshot_ts {
+            let apt_usd_price = apt_usd_price_at(conn, &self.price_config, snapshot_ts)
+                .await
+                .map_err(db_error)?;
+            let snapshots = get_token_metrics(conn, snapshot_ts, apt_usd_price.as_ref())
+                .await
+                .map_err(db_error)?;
+            for chunk in snapshots.chunks(1000) {
+                insert_into(token_metrics::table)
+                    .values(chunk)
+                    .on_conflict((token_metrics::token_address, token_metrics::snapshot_ts))
+                    .do_nothing()
+                    .execute(conn)
+                    .await
+                    .map_err(db_error)?;
+            }
+            self.snapshot_ts = Some(snapshot_ts);
+            snapshot_ts += interval;
+        }
+        Ok(())
+    }
+}
+
+fn db_error(e: diesel::result::Error) -> ProcessorError {
+    tracing::warn!("Error running query: {:?}", e);
+    ProcessorError::ProcessError {
+        message: e.to_string(),
+    }
+}
+
+/// Latest reserves of each token at or before a timestamp.
+async fn get_reserves(
+    conn: &mut AsyncPgConnection,
+    token_addresses: &[String],
+    ts: i64,
+) -> QueryResult<AHashMap<String, PoolReserves>> {
+    Ok(trades::table
+        .filter(trades::token_address.eq_any(token_addresses))
+        .filter(trades::ts.le(ts))
+        .distinct_on(trades::token_address)
+        .order((trades::token_address, trades::txn_version.desc()))
+        .select((
+            trades::token_address,
+            trades::txn_version,
+            trades::virtual_aptos_reserves,
+            trades::virtual_token_reserves,
+        ))
+        .load::<PoolReserves>(conn)
+        .await?
+        .into_iter()
+        .map(|reserves| (reserves.token_address.clone(), reserves))
+        .collect())
+}
+
+/// Snapshots of the tokens still on their curve and of the tokens traded in the window
+/// before `snapshot_ts`, at their latest reserves. Tokens not traded yet are at their launch
+/// reserves.
+async fn get_token_metrics(
+    conn: &mut AsyncPgConnection,
+    snapshot_ts: i64,
+    apt_usd_price: Option<&BigDecimal>,
+) -> QueryResult<Vec<TokenMetrics>> {
+    let since = snapshot_ts - METRICS_WINDOW_SECS;
+    let volumes = trades::table
+        .filter(trades::ts.gt(since))
+        .filter(trades::ts.le(snapshot_ts))
+        .group_by(trades::token_address)
+        .select((
+            trades::token_address,
+            diesel::dsl::sum(trades::aptos_amount),
+            diesel::dsl::sum(trades::aptos_amount_usd),
+            diesel::dsl::count_star(),
+            diesel::dsl::count(trades::apt_usd_price),
+        ))
+        .load::<(String, Option<BigDecimal>, Option<BigDecimal>, i64, i64)>(conn)
+        .await?
+        .into_iter()
+        .map(|(token_address, volume, volume_usd, trades, priced_trades)| {
+            (token_address, (volume, volume_usd, trades, priced_trades))
+        })
+        .collect::<AHashMap<String, (Option<BigDecimal>, Option<BigDecimal>, i64, i64)>>();
+    let metrics_tokens = tokens::table
+        .filter(tokens::ts.le(snapshot_ts))
+        .filter(
+            tokens::is_completed
+                .eq(false)
+                .or(tokens::pre_addr.eq_any(volumes.keys().cloned())),
+        )
+        .select((
+            tokens::pre_addr,
+            tokens::decimals,
+            tokens::virtual_aptos_reserves,
+            tokens::virtual_token_reserves,
+            tokens::is_completed,
+            tokens::txn_version,
+        ))
+        .load::<MetricsToken>(conn)
+        .await?;
+    if metrics_tokens.is_empty() {
+        return Ok(vec![]);
+    }
+    let token_addresses = metrics_tokens
+        .iter()
+        .map(|token| token.pre_addr.clone())
+        .collect::<Vec<String>>();
+    let reserves = get_reserves(conn, &token_addresses, snapshot_ts).await?;
+    let reserves_24h_ago = get_reserves(conn, &token_addresses, since).await?;
+
+    Ok(metrics_tokens
+        .iter()
+        .filter_map(|token| {
+            let launch_reserves = token.launch_reserves();
+            let (volume, volume_usd) = match volumes.get(&token.pre_addr) {
+                // Only when every trade of the window is valued
+                Some((volume, volume_usd, trades, priced_trades)) => (
+                    volume.clone().unwrap_or_default(),
+                    volume_usd
+                        .as_ref()
+                        .filter(|_| priced_trades == trades)
+                        .map(|volume_usd| volume_usd.round(8)),
+                ),
+                None => (
+                    BigDecimal::zero(),
+                    apt_usd_price.map(|_| BigDecimal::zero()),
+                ),
+            };
+            TokenMetrics::snapshot(
+                token,
+                snapshot_ts,
+                reserves.get(&token.pre_addr).unwrap_or(&launch_reserves),
+                reserves_24h_ago.get(&token.pre_addr),
+                &volume,
+                volume_usd,
+                apt_usd_price,
+            )
+        })
+        .collect())
+}
+
+#[async_trait]
+impl<T> Processable for TokenMetricsRefresher<T>
+where
+    Self: Sized + Send + 'static,
+    T: Send + 'static,
+{
+    type Input = T;
+    type Output = T;
+    type RunType = PollableAsyncRunType;
+
+    async fn process(
+        &mut self,
+        current_batch: TransactionContext<T>,
+    ) -> Result<Option<TransactionContext<T>>, ProcessorError> {
+        if let Some(ts) = current_batch.metadata.end_transaction_timestamp.as_ref() {
+            self.latest_ts = Some(self.latest_ts.unwrap_or(0).max(ts.seconds));
+        }
+        // Pass through
+        Ok(Some(current_batch))
+    }
+}
+
+#[async_trait]
+impl<T: Send + 'static> PollableAsyncStep for TokenMetricsRefresher<T>
+where
+    Self: Sized + Send + Sync + 'static,
+    T: Send + 'static,
+{
+    fn poll_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.config.snapshot_interval_secs as u64)
+    }
+
+    async fn poll(&mut self) -> Result<Option<Vec<TransactionContext<T>>>, ProcessorError> {
+        if self.config.enabled {
+            self.snapshot_token_metrics().await?;
+        }
+        // Nothing should be returned
+        Ok(None)
+    }
+}
+
+impl<T> NamedStep for TokenMetricsRefresher<T>
+where
+    Self: Sized + Send + 'static,
+    T: Send + 'static,
+{
+    fn name(&self) -> String {
+        format!("TokenMetricsRefresher: {}", std::any::type_name::<T>())
+    }
+}
@@ -0,0 +1,418 @@
+use ahash::{AHashMap, AHashSet};
+use anyhow::Result;
+use aptos_indexer_processor_sdk::{
+    traits::{async_step::AsyncRunType, AsyncStep, NamedStep, Processable},
+    types::transaction_context::TransactionContext,
+    utils::errors::ProcessorError,
+};
+use async_trait::async_trait;
+use bigdecimal::{BigDecimal, ToPrimitive};
+use diesel::{
+    delete, dsl::not, insert_into, pg::upsert::excluded, ExpressionMethods, QueryDsl,
+    QueryResult,
+};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde_json::json;
+
+use super::extractor::{ContractEvent, TransactionContextData};
+use crate::{
+    config::indexer_processor_config::TradeFlagsConfig,
+    db_models::{
+        trade_flags::{
+            AptTransfer, CreatorFunding, TokenRiskScore, TradeFlag, FLAG_BUNDLE,
+            FLAG_ROUND_TRIP, FLAG_SNIPER, FLAG_WASH,
+        },
+        trades::Trade,
+    },
+    schema::{creator_fundings, token_risk_scores, tokens, trade_flags, trades},
+    utils::{
+        database_connection::get_db_connection,
+        database_utils::{get_config_table_chunk_size, ArcDbPool},
+    },
+};
+
+/// TradeFlagger is a pass-through step that runs the bot heuristics over the trades of each
+/// stored batch. Buys in the launch block are snipers, buys by wallets the creator funded
+/// are bundles, quick buy/sell pairs of a user are round trips and opposite trades inside
+/// the creator's cluster are wash trades. Every touched token gets its risk score updated.
+///
+/// Wash trades are only matched inside the creator's cluster: rings of wallets that trade
+/// against each other without having been funded by the creator are not detected, since the
+/// transfers of wallets that never create a token are pruned after `funding_lookback_secs`.
+pub struct TradeFlagger
+where
+    Self: Sized + Send + 'static,
+{
+    pool: ArcDbPool,
+    config: TradeFlagsConfig,
+}
+
+impl TradeFlagger {
+    pub fn new(pool: ArcDbPool, config: TradeFlagsConfig) -> Self {
+        Self { pool, config }
+    }
+
+    /// Stores the transfers of the batch as candidate fundings and prunes the ones older than
+    /// the lookback whose sender still did not create a token.
+    async fn store_creator_fundings(
+        &self,
+        conn: &mut AsyncPgConnection,
+        transfers: Vec<AptTransfer>,
+        latest_ts: i64,
+    ) -> QueryResult<()> {
+        let fundings = transfers
+            .iter()
+            .map(AptTransfer::to_db_creator_funding)
+            .collect::<Vec<CreatorFunding>>();
+        let chunk_size =
+            get_config_table_chunk_size::<CreatorFunding>("creator_fundings", &AHashMap::new());
+        for chunk in fundings.chunks(chunk_size) {
+            insert_into(creator_fundings::table)
+                .values(chunk.to_vec())
+                .on_conflict((creator_fundings::txn_version, creator_fundings::recipient))
+                .do_nothing()
+                .execute(conn)
+                .await?;
+        }
+        // The tokens of the batch are already stored, so its new creators keep their fundings
+        let since = latest_ts - self.config.funding_lookback_secs;
+        delete(
+            creator_fundings::table
+                .filter(creator_fundings::ts.lt(since))
+                .filter(not(creator_fundings::creator.eq_any(
+                    tokens::table.select(tokens::created_by),
+                ))),
+        )
+        .execute(conn)
+        .await?;
+        Ok(())
+    }
+
+    async fn flag_trades(
+        &self,
+        conn: &mut AsyncPgConnection,
+        batch_trades: &[Trade],
+    ) -> QueryResult<Vec<TradeFlag>> {
+        let token_addresses = batch_trades
+            .iter()
+            .map(|trade| trade.token_address.clone())
+            .collect::<AHashSet<String>>();
+        // pre_addr -> (creator, creation txn_version, creation block)
+        let launches = tokens::table
+            .filter(tokens::pre_addr.eq_any(token_addresses.iter().cloned()))
+            .select((
+                tokens::pre_addr,
+                tokens::created_by,
+                tokens::txn_version,
+                tokens::block_height,
+            ))
+            .load::<(String, String, i64, Option<i64>)>(conn)
+            .await?
+            .into_iter()
+            .map(|(pre_addr, created_by, txn_version, block_height)| {
+                (pre_addr, (created_by, txn_version, block_height))
+            })
+            .collect::<AHashMap<String, (String, i64, Option<i64>)>>();
+        // creator -> [(recipient, funding txn_version, amount)]
+        let mut fundings: AHashMap<String, Vec<(String, i64, i64)>> = AHashMap::new();
+        for funding in creator_fundings::table
+            .filter(
+                creator_fundings::creator
+                    .eq_any(launches.values().map(|(creator, ..)| creator.clone())),
+            )
+            .load::<CreatorFunding>(conn)
+            .await?
+        {
+            fundings.entry(funding.creator).or_default().push((
+                funding.recipient,
+                funding.txn_version,
+                funding.amount,
+            ));
+        }
+        // Trades the round trips and wash trades are matched against, the batch included
+        let window = self.config.round_trip_secs.max(self.config.wash_window_secs);
+        let since = batch_trades.iter().map(|trade| trade.ts).min().unwrap_or(0) - window;
+        let recent_trades = trades::table
+            .filter(trades::token_address.eq_any(token_addresses.iter().cloned()))
+            .filter(trades::ts.ge(since))
+            .order(trades::txn_version.asc())
+            .load::<Trade>(conn)
+            .await?;
+
+        let mut flags = vec![];
+        for trade in batch_trades {
+            let Some((creator, token_version, token_block)) = launches.get(&trade.token_address)
+            else {
+                continue;
+            };
+            let mut flag = |flag: &str, detail: serde_json::Value| {
+                flags.push(TradeFlag {
+                    txn_version: trade.txn_version,
+                    flag: flag.to_string(),
+                    token_address: trade.token_address.clone(),
+                    user_addr: trade.user_addr.clone(),
+                    detail,
+                    ts: trade.ts,
+                });
+            };
+            let funding = fundings.get(creator).and_then(|fundings| {
+                fundings.iter().find(|(recipient, funding_version, _)| {
+                    *recipient == trade.user_addr && *funding_version < trade.txn_version
+                })
+            });
+            let in_cluster = |user_addr: &str, txn_version: i64| {
+                user_addr == creator
+                    || fundings.get(creator).is_some_and(|fundings| {
+                        fundings.iter().any(|(recipient, funding_version, _)| {
+                            recipient == user_addr && *funding_version < txn_version
+                        })
+                    })
+            };
+
+            if trade.is_buy && trade.user_addr != *creator {
+                let same_transaction = trade.txn_version == *token_version;
+                let same_block = token_block.is_some() && trade.block_height == *token_block;
+                if same_transaction || same_block {
+                    flag(
+                        FLAG_SNIPER,
+                        json!({
+                            "token_txn_version": token_version,
+                            "same_transaction": same_transaction,
+                        }),
+                    );
+                }
+            }
+            if let Some((_, funding_version, amount)) = funding.filter(|_| trade.is_buy) {
+                flag(
+                    FLAG_BUNDLE,
+                    json!({ "funding_txn_version": funding_version, "funding_amount": amount }),
+                );
+            }
+            if !trade.is_buy {
+                let buy = recent_trades.iter().rev().find(|other| {
+                    other.is_buy
+                        && other.user_addr == trade.user_addr
+                        && other.token_address == trade.token_address
+                        && other.txn_version < trade.txn_version
+                        && other.ts >= trade.ts - self.config.round_trip_secs
+                });
+                if let Some(buy) = buy {
+                    flag(
+                        FLAG_ROUND_TRIP,
+                        json!({ "buy_txn_version": buy.txn_version, "secs": trade.ts - buy.ts }),
+                    );
+                }
+            }
+            if in_cluster(&trade.user_addr, trade.txn_version) {
+                let counterpart = recent_trades.iter().rev().find(|other| {
+                    other.is_buy != trade.is_buy
+                        && other.user_addr != trade.user_addr
+                        && other.token_address == trade.token_address
+                        && other.txn_version < trade.txn_version
+                        && other.ts >= trade.ts - self.config.wash_window_secs
+                        && in_cluster(&other.user_addr, other.txn_version)
+                });
+                if let Some(counterpart) = counterpart {
+                    flag(
+                        FLAG_WASH,
+                        json!({
+                            "counterpart_txn_version": counterpart.txn_version,
+                            "counterpart": counterpart.user_addr,
+                        }),
+                    );
+                }
+            }
+        }
+        Ok(flags)
+    }
+
+    /// Recomputes the risk score of the tokens from all their flags, the score is the share
+    /// of the traded volume that was flagged.
+    async fn update_risk_scores(
+        &self,
+        conn: &mut AsyncPgConnection,
+        token_addresses: Vec<String>,
+        end_version: i64,
+    ) -> QueryResult<()> {
+        let flag_counts = trade_flags::table
+            .filter(trade_flags::token_address.eq_any(token_addresses.iter().cloned()))
+            .group_by((trade_flags::token_address, trade_flags::flag))
+            .select((
+                trade_flags::token_address,
+                trade_flags::flag,
+                diesel::dsl::count_star(),
+            ))
+            .load::<(String, String, i64)>(conn)
+            .await?;
+        let total_volumes = trades::table
+            .filter(trades::token_address.eq_any(token_addresses.iter().cloned()))
+            .group_by(trades::token_address)
+            .select((trades::token_address, diesel::dsl::sum(trades::aptos_amount)))
+            .load::<(String, Option<BigDecimal>)>(conn)
+            .await?
+            .into_iter()
+            .collect::<AHashMap<String, Option<BigDecimal>>>();
+        let flagged_volumes = trades::table
+            .filter(trades::token_address.eq_any(token_addresses.iter().cloned()))
+            .filter(
+                trades::txn_version.eq_any(
+                    trade_flags::table
+                        .filter(trade_flags::token_address.eq_any(token_addresses.iter().cloned()))
+                        .select(trade_flags::txn_version),
+                ),
+            )
+            .group_by(trades::token_address)
+            .select((trades::token_address, diesel::dsl::sum(trades::aptos_amount)))
+            .load::<(String, Option<BigDecimal>)>(conn)
+            .await?
+            .into_iter()
+            .collect::<AHashMap<String, Option<BigDecimal>>>();
+
+        let scores = token_addresses
+            .into_iter()
+            .map(|token_address| {
+                let count = |flag: &str| {
+                    flag_counts
+                        .iter()
+                        .find(|(address, f, _)| *address == token_address && f == flag)
+                        .map(|(.., count)| *count as i32)
+                        .unwrap_or(0)
+                };
+                let volume = |volumes: &AHashMap<String, Option<BigDecimal>>| {
+                    volumes
+                        .get(&token_address)
+                        .and_then(|volume| volume.as_ref())
+                        .and_then(|volume| volume.to_i64())
+                        .unwrap_or(0)
+                };
+                let total_volume = volume(&total_volumes);
+                let flagged_volume = volume(&flagged_volumes);
+                let risk_score = if total_volume > 0 {
+                    (flagged_volume as i128 * 100 / total_volume as i128).min(100) as i32
+                } else {
+                    0
+                };
+                TokenRiskScore {
+                    risk_score,
+                    sniper_trades: count(FLAG_SNIPER),
+                    bundle_trades: count(FLAG_BUNDLE),
+                    round_trip_trades: count(FLAG_ROUND_TRIP),
+                    wash_trades: count(FLAG_WASH),
+                    flagged_volume,
+                    total_volume,
+                    updated_at_version: end_version,
+                    token_address,
+                }
+            })
+            .collect::<Vec<TokenRiskScore>>();
+        insert_into(token_risk_scores::table)
+            .values(scores)
+            .on_conflict(token_risk_scores::token_address)
+            .do_update()
+            .set((
+                token_risk_scores::risk_score.eq(excluded(token_risk_scores::risk_score)),
+                token_risk_scores::sniper_trades.eq(excluded(token_risk_scores::sniper_trades)),
+                token_risk_scores::bundle_trades.eq(excluded(token_risk_scores::bundle_trades)),
+                token_risk_scores::round_trip_trades
+                    .eq(excluded(token_risk_scores::round_trip_trades)),
+                token_risk_scores::wash_trades.eq(excluded(token_risk_scores::wash_trades)),
+                token_risk_scores::flagged_volume.eq(excluded(token_risk_scores::flagged_volume)),
+                token_risk_scores::total_volume.eq(excluded(token_risk_scores::total_volume)),
+                token_risk_scores::updated_at_version
+                    .eq(excluded(token_risk_scores::updated_at_version)),
+            ))
+            .execute(conn)
+            .await?;
+        Ok(())
+    }
+}
+
+fn db_error(e: diesel::result::Error) -> ProcessorError {
+    tracing::warn!("Error running query: {:?}", e);
+    ProcessorError::DBStoreError {
+        message: format!("Failed to flag trades: {}", e),
+        query: None,
+    }
+}
+
+impl AsyncStep for TradeFlagger {}
+
+impl NamedStep for TradeFlagger {
+    fn name(&self) -> String {
+        "TradeFlagger".to_string()
+    }
+}
+
+#[async_trait]
+impl Processable for TradeFlagger {
+    type Input = TransactionContextData;
+    type Output = TransactionContextData;
+    type RunType = AsyncRunType;
+
+    async fn process(
+        &mut self,
+        current_batch: TransactionContext<TransactionContextData>,
+    ) -> Result<Option<TransactionContext<TransactionContextData>>, ProcessorError> {
+        if !self.config.enabled {
+            return Ok(Some(current_batch));
+        }
+        let batch_trades = current_batch
+            .data
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                ContractEvent::TradeCreatedEvent(trade) => Some(trade.clone()),
+                _ => None,
+            })
+            .collect::<Vec<Trade>>();
+        let latest_ts = current_batch
+            .metadata
+            .end_transaction_timestamp
+            .as_ref()
+            .map(|ts| ts.seconds)
+            .unwrap_or_default();
+
+        let pool = self.pool.clone();
+        let conn = &mut get_db_connection(&pool).await?;
+        self.store_creator_fundings(
+            conn,
+            current_batch.data.apt_transfers.clone(),
+            latest_ts,
+        )
+        .await
+        .map_err(db_error)?;
+        if batch_trades.is_empty() {
+            return Ok(Some(current_batch));
+        }
+
+        let flags = self
+            .flag_trades(conn, &batch_trades)
+            .await
+            .map_err(db_error)?;
+        let chunk_size = get_config_table_chunk_size::<TradeFlag>("trade_flags", &AHashMap::new());
+        for chunk in flags.chunks(chunk_size) {
+            insert_into(trade_flags::table)
+                .values(chunk.to_vec())
+                .on_conflict((trade_flags::txn_version, trade_flags::flag))
+                .do_nothing()
+                .execute(conn)
+                .await
+                .map_err(db_error)?;
+        }
+
+        let token_addresses = batch_trades
+            .iter()
+            .map(|trade| trade.token_address.clone())
+            .collect::<AHashSet<String>>()
+            .into_iter()
+            .collect();
+        self.update_risk_scores(
+            conn,
+            token_addresses,
+            current_batch.metadata.end_version as i64,
+        )
+        .await
+        .map_err(db_error)?;
+        Ok(Some(current_batch))
+    }
+}
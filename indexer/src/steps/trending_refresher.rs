@@ -0,0 +1,240 @@
+use ahash::{AHashMap, AHashSet};
+use anyhow::Result;
+use aptos_indexer_processor_sdk::{
+    traits::{NamedStep, PollableAsyncRunType, PollableAsyncStep, Processable},
+    types::transaction_context::TransactionContext,
+    utils::errors::ProcessorError,
+};
+use async_trait::async_trait;
+use bigdecimal::{BigDecimal, ToPrimitive, Zero};
+use diesel::{insert_into, ExpressionMethods, QueryDsl, QueryResult};
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+use std::marker::PhantomData;
+
+use crate::{
+    config::indexer_processor_config::TrendingConfig,
+    db_models::trending_tokens::{decay, TrendingActivity, TrendingToken},
+    schema::{positions, tokens, trades, trending_tokens},
+    utils::{
+        database_connection::get_db_connection,
+        database_utils::{get_config_table_chunk_size, ArcDbPool},
+    },
+};
+
+/// TrendingRefresher is a pass-through step that periodically re-ranks the tokens traded
+/// within the trending window into `trending_tokens`.
+pub struct TrendingRefresher<T>
+where
+    Self: Sized + Send + 'static,
+    T: Send + 'static,
+{
+    pool: ArcDbPool,
+    config: TrendingConfig,
+    // Chain timestamp of the latest batch seen, trades are decayed from it
+    latest_ts: Option<i64>,
+    // Whether a batch passed through since the last refresh
+    dirty: bool,
+    _marker: PhantomData<T>,
+}
+
+impl<T> TrendingRefresher<T>
+where
+    Self: Sized + Send + 'static,
+    T: Send + 'static,
+{
+    pub fn new(pool: ArcDbPool, config: TrendingConfig) -> Self {
+        Self {
+            pool,
+            config,
+            latest_ts: None,
+            dirty: true,
+            _marker: PhantomData,
+        }
+    }
+
+    async fn refresh_trending_tokens(&mut self) -> Result<(), ProcessorError> {
+        let conn = &mut get_db_connection(&self.pool).await?;
+        let now = match self.latest_ts {
+            Some(ts) => ts,
+            None => match trades::table
+                .select(diesel::dsl::max(trades::ts))
+                .first::<Option<i64>>(conn)
+                .await
+                .map_err(db_error)?
+            {
+                Some(ts) => ts,
+                None => return Ok(()),
+            },
+        };
+
+        let activities = get_trending_activities(conn, &self.config, now)
+            .await
+            .map_err(db_error)?;
+        let ranked = TrendingToken::rank(&self.config.weights, activities, self.config.size, now);
+        let chunk_size =
+            get_config_table_chunk_size::<TrendingToken>("trending_tokens", &AHashMap::new());
+        conn.transaction(|conn| {
+            Box::pin(async move {
+                diesel::delete(trending_tokens::table).execute(conn).await?;
+                for chunk in ranked.chunks(chunk_size) {
+                    insert_into(trending_tokens::table)
+                        .values(chunk.to_vec())
+                        .execute(conn)
+                        .await?;
+                }
+                Ok(())
+            })
+        })
+        .await
+        .map_err(db_error)?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+fn db_error(e: diesel::result::Error) -> ProcessorError {
+    tracing::warn!("Error running query: {:?}", e);
+    ProcessorError::DBStoreError {
+        message: format!("Failed to refresh trending tokens: {}", e),
+        query: None,
+    }
+}
+
+/// Decayed activity of every token traded in the window before `now`.
+async fn get_trending_activities(
+    conn: &mut AsyncPgConnection,
+    config: &TrendingConfig,
+    now: i64,
+) -> QueryResult<AHashMap<String, TrendingActivity>> {
+    let since = now - config.window_secs;
+    let window_trades = trades::table
+        .filter(trades::ts.gt(since))
+        .filter(trades::ts.le(now))
+        .select((
+            trades::token_address,
+            trades::user_addr,
+            trades::is_buy,
+            trades::aptos_amount,
+            trades::ts,
+        ))
+        .load::<(String, String, bool, i64, i64)>(conn)
+        .await?;
+    if window_trades.is_empty() {
+        return Ok(AHashMap::new());
+    }
+
+    let mut activities: AHashMap<String, TrendingActivity> = AHashMap::new();
+    // Weight of the latest buy of each buyer
+    let mut buyers: AHashMap<(String, String), f64> = AHashMap::new();
+    for (token_address, user_addr, is_buy, aptos_amount, ts) in window_trades {
+        let weight = decay(now - ts, config.half_life_secs);
+        let activity = activities.entry(token_address.clone()).or_default();
+        activity.volume += weight * aptos_amount as f64 / 100_000_000.0;
+        activity.trade_count += weight;
+        if is_buy {
+            let buyer = buyers.entry((token_address, user_addr)).or_default();
+            *buyer = buyer.max(weight);
+        }
+    }
+    for ((token_address, _), weight) in buyers.iter() {
+        if let Some(activity) = activities.get_mut(token_address) {
+            activity.unique_buyers += weight;
+        }
+    }
+
+    let token_addresses = activities.keys().cloned().collect::<Vec<String>>();
+    let holders = positions::table
+        .filter(positions::token_address.eq_any(&token_addresses))
+        .filter(positions::balance.gt(BigDecimal::zero()))
+        .select((positions::token_address, positions::user_addr))
+        .load::<(String, String)>(conn)
+        .await?
+        .into_iter()
+        .collect::<AHashSet<(String, String)>>();
+    let first_buys = trades::table
+        .filter(trades::token_address.eq_any(&token_addresses))
+        .filter(trades::is_buy.eq(true))
+        .group_by((trades::token_address, trades::user_addr))
+        .select((
+            trades::token_address,
+            trades::user_addr,
+            diesel::dsl::min(trades::ts),
+        ))
+        .load::<(String, String, Option<i64>)>(conn)
+        .await?;
+    for (token_address, user_addr, first_buy_ts) in first_buys {
+        let Some(first_buy_ts) = first_buy_ts.filter(|ts| *ts > since && *ts <= now) else {
+            continue;
+        };
+        if !holders.contains(&(token_address.clone(), user_addr)) {
+            continue;
+        }
+        if let Some(activity) = activities.get_mut(&token_address) {
+            activity.holder_growth += decay(now - first_buy_ts, config.half_life_secs);
+        }
+    }
+
+    let curve_progress = tokens::table
+        .filter(tokens::pre_addr.eq_any(&token_addresses))
+        .select((tokens::pre_addr, tokens::curve_progress))
+        .load::<(String, BigDecimal)>(conn)
+        .await?;
+    for (pre_addr, progress) in curve_progress {
+        if let Some(activity) = activities.get_mut(&pre_addr) {
+            activity.curve_progress = progress.to_f64().unwrap_or_default();
+        }
+    }
+    Ok(activities)
+}
+
+#[async_trait]
+impl<T> Processable for TrendingRefresher<T>
+where
+    Self: Sized + Send + 'static,
+    T: Send + 'static,
+{
+    type Input = T;
+    type Output = T;
+    type RunType = PollableAsyncRunType;
+
+    async fn process(
+        &mut self,
+        current_batch: TransactionContext<T>,
+    ) -> Result<Option<TransactionContext<T>>, ProcessorError> {
+        if let Some(ts) = current_batch.metadata.end_transaction_timestamp.as_ref() {
+            self.latest_ts = Some(self.latest_ts.unwrap_or(0).max(ts.seconds));
+        }
+        self.dirty = true;
+        // Pass through
+        Ok(Some(current_batch))
+    }
+}
+
+#[async_trait]
+impl<T: Send + 'static> PollableAsyncStep for TrendingRefresher<T>
+where
+    Self: Sized + Send + Sync + 'static,
+    T: Send + 'static,
+{
+    fn poll_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.config.refresh_interval_secs)
+    }
+
+    async fn poll(&mut self) -> Result<Option<Vec<TransactionContext<T>>>, ProcessorError> {
+        if self.config.enabled && self.dirty {
+            self.refresh_trending_tokens().await?;
+        }
+        // Nothing should be returned
+        Ok(None)
+    }
+}
+
+impl<T> NamedStep for TrendingRefresher<T>
+where
+    Self: Sized + Send + 'static,
+    T: Send + 'static,
+{
+    fn name(&self) -> String {
+        format!("TrendingRefresher: {}", std::any::type_name::<T>())
+    }
+}
@@ -0,0 +1,524 @@
+//! Decodes the JSON of Move events and resources using the module ABIs stored in
+//! `module_upgrade_history`. Integers that don't fit a JSON number (u64 and up) are kept as
+//! strings, options become nullable values, objects and addresses become standardized
+//! addresses, and structs of indexed modules are rebuilt field by field from their ABI.
+//! Arguments of entry function calls are decoded the same way from the function params.
+
+use ahash::AHashMap;
+use aptos_indexer_processor_sdk::utils::convert::standardize_address;
+use diesel::{ExpressionMethods, QueryDsl, QueryResult};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde_json::{Map, Value};
+
+use crate::{db_models::module_upgrade::ModuleUpgrade, schema::module_upgrade_history};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MoveTypeAbi {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    U256,
+    Address,
+    Signer,
+    Vector(Box<MoveTypeAbi>),
+    Struct {
+        address: String,
+        module: String,
+        name: String,
+        generic_type_params: Vec<MoveTypeAbi>,
+    },
+    GenericTypeParam(usize),
+    Reference(Box<MoveTypeAbi>),
+    Unknown,
+}
+
+impl MoveTypeAbi {
+    /// Parses a type as it is written in the ABI JSON of a module. Both the enum names and
+    /// the numeric values of `MoveTypes` are accepted.
+    pub fn from_abi_json(value: &Value) -> Self {
+        let type_name = match value.get("type") {
+            Some(Value::String(name)) => name.trim_start_matches("MOVE_TYPES_").to_string(),
+            Some(Value::Number(n)) => match n.as_u64() {
+                Some(1) => "BOOL",
+                Some(2) => "U8",
+                Some(3) => "U64",
+                Some(4) => "U128",
+                Some(5) => "ADDRESS",
+                Some(6) => "SIGNER",
+                Some(7) => "VECTOR",
+                Some(8) => "STRUCT",
+                Some(9) => "GENERIC_TYPE_PARAM",
+                Some(10) => "REFERENCE",
+                Some(12) => "U16",
+                Some(13) => "U32",
+                Some(14) => "U256",
+                _ => "UNPARSABLE",
+            }
+            .to_string(),
+            _ => return Self::Unknown,
+        };
+        match type_name.as_str() {
+            "BOOL" => Self::Bool,
+            "U8" => Self::U8,
+            "U16" => Self::U16,
+            "U32" => Self::U32,
+            "U64" => Self::U64,
+            "U128" => Self::U128,
+            "U256" => Self::U256,
+            "ADDRESS" => Self::Address,
+            "SIGNER" => Self::Signer,
+            "VECTOR" => match value.get("vector") {
+                Some(inner) => Self::Vector(Box::new(Self::from_abi_json(inner))),
+                None => Self::Unknown,
+            },
+            "STRUCT" => match value.get("struct") {
+                Some(tag) => Self::Struct {
+                    address: standardize_address(tag["address"].as_str().unwrap_or_default()),
+                    module: tag["module"].as_str().unwrap_or_default().to_string(),
+                    name: tag["name"].as_str().unwrap_or_default().to_string(),
+                    generic_type_params: tag
+                        .get("genericTypeParams")
+                        .or_else(|| tag.get("generic_type_params"))
+                        .and_then(Value::as_array)
+                        .map(|params| params.iter().map(Self::from_abi_json).collect())
+                        .unwrap_or_default(),
+                },
+                None => Self::Unknown,
+            },
+            "GENERIC_TYPE_PARAM" => value
+                .get("genericTypeParamIndex")
+                .or_else(|| value.get("generic_type_param_index"))
+                .and_then(Value::as_u64)
+                .map(|idx| Self::GenericTypeParam(idx as usize))
+                .unwrap_or(Self::Unknown),
+            "REFERENCE" => match value.get("reference").and_then(|r| r.get("to")) {
+                Some(inner) => Self::Reference(Box::new(Self::from_abi_json(inner))),
+                None => Self::Unknown,
+            },
+            _ => Self::Unknown,
+        }
+    }
+
+    /// Parses a type string such as `0x1::option::Option<vector<u8>>`.
+    pub fn parse(type_str: &str) -> Self {
+        let type_str = type_str.trim();
+        match type_str {
+            "bool" => return Self::Bool,
+            "u8" => return Self::U8,
+            "u16" => return Self::U16,
+            "u32" => return Self::U32,
+            "u64" => return Self::U64,
+            "u128" => return Self::U128,
+            "u256" => return Self::U256,
+            "address" => return Self::Address,
+            "signer" => return Self::Signer,
+            _ => {}
+        }
+        let (base, params) = match type_str.find('<') {
+            Some(idx) if type_str.ends_with('>') => (
+                &type_str[..idx],
+                split_type_params(&type_str[idx + 1..type_str.len() - 1]),
+            ),
+            _ => (type_str, vec![]),
+        };
+        if base == "vector" {
+            return match params.as_slice() {
+                [inner] => Self::Vector(Box::new(Self::parse(inner))),
+                _ => Self::Unknown,
+            };
+        }
+        let parts = base.split("::").collect::<Vec<_>>();
+        if parts.len() != 3 {
+            return Self::Unknown;
+        }
+        Self::Struct {
+            address: standardize_address(parts[0]),
+            module: parts[1].to_string(),
+            name: parts[2].to_string(),
+            generic_type_params: params.iter().map(|p| Self::parse(p)).collect(),
+        }
+    }
+}
+
+/// Splits the top level type parameters of `A, B<C, D>`.
+fn split_type_params(params: &str) -> Vec<&str> {
+    let mut result = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (idx, c) in params.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            ',' if depth == 0 => {
+                result.push(params[start..idx].trim());
+                start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+    if !params[start..].trim().is_empty() {
+        result.push(params[start..].trim());
+    }
+    result
+}
+
+#[derive(Clone, Debug)]
+struct StructAbi {
+    fields: Vec<(String, MoveTypeAbi)>,
+}
+
+/// Struct layouts and function params of the latest ABI of every indexed module.
+#[derive(Clone, Debug, Default)]
+pub struct AbiRegistry {
+    // (module address, module name, struct name) -> layout
+    structs: AHashMap<(String, String, String), StructAbi>,
+    // (module address, module name, function name) -> params
+    functions: AHashMap<(String, String, String), Vec<MoveTypeAbi>>,
+}
+
+impl AbiRegistry {
+    /// Loads the latest complete upgrade of every module.
+    pub async fn load(conn: &mut AsyncPgConnection) -> QueryResult<Self> {
+        let modules = module_upgrade_history::table
+            .filter(module_upgrade_history::partial_reason.is_null())
+            .order(module_upgrade_history::upgrade_number.asc())
+            .select((
+                module_upgrade_history::module_addr,
+                module_upgrade_history::module_name,
+                module_upgrade_history::module_abi,
+            ))
+            .load::<(String, String, Value)>(conn)
+            .await?;
+        let mut registry = Self::default();
+        for (module_addr, module_name, module_abi) in modules {
+            registry.add_module(&module_addr, &module_name, &module_abi);
+        }
+        Ok(registry)
+    }
+
+    /// Replaces the structs and functions of a module with the ones of its new ABI.
+    pub fn add_module(&mut self, module_addr: &str, module_name: &str, module_abi: &Value) {
+        let module_addr = standardize_address(module_addr);
+        self.structs
+            .retain(|(addr, module, _), _| !(*addr == module_addr && module == module_name));
+        self.functions
+            .retain(|(addr, module, _), _| !(*addr == module_addr && module == module_name));
+        let functions = module_abi
+            .get("exposedFunctions")
+            .or_else(|| module_abi.get("exposed_functions"))
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        for f in functions {
+            let Some(name) = f.get("name").and_then(Value::as_str) else {
+                continue;
+            };
+            let params = f
+                .get("params")
+                .and_then(Value::as_array)
+                .map(|params| params.iter().map(MoveTypeAbi::from_abi_json).collect())
+                .unwrap_or_default();
+            self.functions.insert(
+                (module_addr.clone(), module_name.to_string(), name.to_string()),
+                params,
+            );
+        }
+        let structs = module_abi
+            .get("structs")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        for s in structs {
+            let Some(name) = s.get("name").and_then(Value::as_str) else {
+                continue;
+            };
+            let fields = s
+                .get("fields")
+                .and_then(Value::as_array)
+                .map(|fields| {
+                    fields
+                        .iter()
+                        .map(|field| {
+                            (
+                                field["name"].as_str().unwrap_or_default().to_string(),
+                                field
+                                    .get("type")
+                                    .map(MoveTypeAbi::from_abi_json)
+                                    .unwrap_or(MoveTypeAbi::Unknown),
+                            )
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            self.structs.insert(
+                (module_addr.clone(), module_name.to_string(), name.to_string()),
+                StructAbi { fields },
+            );
+        }
+    }
+
+    /// Partial upgrades may lack the abi and keep the structs of the previous upgrade.
+    pub fn add_upgrade(&mut self, upgrade: &ModuleUpgrade) {
+        if upgrade.partial_reason.is_some() {
+            return;
+        }
+        self.add_module(&upgrade.module_addr, &upgrade.module_name, &upgrade.module_abi);
+    }
+
+    pub fn has_struct(&self, address: &str, module: &str, name: &str) -> bool {
+        self.structs.contains_key(&(
+            standardize_address(address),
+            module.to_string(),
+            name.to_string(),
+        ))
+    }
+
+    /// Decodes the JSON of a value of the given type, `None` when the type is not a struct
+    /// of an indexed module.
+    pub fn decode(&self, type_str: &str, data: &Value) -> Option<Value> {
+        match MoveTypeAbi::parse(type_str) {
+            MoveTypeAbi::Struct {
+                address,
+                module,
+                name,
+                ..
+            } if !self.has_struct(&address, &module, &name) => None,
+            move_type => Some(self.decode_value(&move_type, &[], data)),
+        }
+    }
+
+    /// Decodes the JSON arguments of an entry function call, `None` when the function is
+    /// not in the registry or the arguments don't match its params. The leading signers are
+    /// not part of the arguments, the type arguments are in the ABI JSON format.
+    pub fn decode_arguments(
+        &self,
+        module_addr: &str,
+        module_name: &str,
+        function_name: &str,
+        type_arguments: &[Value],
+        arguments: &[Value],
+    ) -> Option<Value> {
+        let params = self.functions.get(&(
+            standardize_address(module_addr),
+            module_name.to_string(),
+            function_name.to_string(),
+        ))?;
+        let params = params
+            .iter()
+            .filter(|param| {
+                !matches!(param, MoveTypeAbi::Signer)
+                    && !matches!(param, MoveTypeAbi::Reference(inner) if **inner == MoveTypeAbi::Signer)
+            })
+            .collect::<Vec<_>>();
+        if params.len() != arguments.len() {
+            return None;
+        }
+        let generics = type_arguments
+            .iter()
+            .map(MoveTypeAbi::from_abi_json)
+            .collect::<Vec<_>>();
+        Some(Value::Array(
+            params
+                .iter()
+                .zip(arguments)
+                .map(|(param, argument)| self.decode_value(param, &generics, argument))
+                .collect(),
+        ))
+    }
+
+    fn decode_value(&self, move_type: &MoveTypeAbi, generics: &[MoveTypeAbi], data: &Value) -> Value {
+        match move_type {
+            MoveTypeAbi::U8 | MoveTypeAbi::U16 | MoveTypeAbi::U32 => match data {
+                Value::String(s) => s.parse::<u64>().map(Value::from).unwrap_or(data.clone()),
+                _ => data.clone(),
+            },
+            MoveTypeAbi::U64 | MoveTypeAbi::U128 | MoveTypeAbi::U256 => match data {
+                Value::Number(n) => Value::String(n.to_string()),
+                _ => data.clone(),
+            },
+            MoveTypeAbi::Address | MoveTypeAbi::Signer => match data {
+                Value::String(s) => Value::String(standardize_address(s)),
+                _ => data.clone(),
+            },
+            // Byte vectors are already hex strings
+            MoveTypeAbi::Vector(inner) => match data {
+                Value::Array(items) => Value::Array(
+                    items
+                        .iter()
+                        .map(|item| self.decode_value(inner, generics, item))
+                        .collect(),
+                ),
+                _ => data.clone(),
+            },
+            MoveTypeAbi::GenericTypeParam(idx) => match generics.get(*idx) {
+                Some(generic) => self.decode_value(generic, &[], data),
+                None => data.clone(),
+            },
+            MoveTypeAbi::Reference(inner) => self.decode_value(inner, generics, data),
+            MoveTypeAbi::Struct {
+                address,
+                module,
+                name,
+                generic_type_params,
+            } => {
+                // Resolve the params against the enclosing struct before going down a level
+                let params = generic_type_params
+                    .iter()
+                    .map(|param| match param {
+                        MoveTypeAbi::GenericTypeParam(idx) => {
+                            generics.get(*idx).cloned().unwrap_or(MoveTypeAbi::Unknown)
+                        }
+                        param => param.clone(),
+                    })
+                    .collect::<Vec<_>>();
+                self.decode_struct(address, module, name, &params, data)
+            }
+            MoveTypeAbi::Bool | MoveTypeAbi::Unknown => data.clone(),
+        }
+    }
+
+    fn decode_struct(
+        &self,
+        address: &str,
+        module: &str,
+        name: &str,
+        params: &[MoveTypeAbi],
+        data: &Value,
+    ) -> Value {
+        match (address == standardize_address("0x1"), module, name) {
+            (true, "option", "Option") => {
+                match data.get("vec").and_then(Value::as_array).map(Vec::as_slice) {
+                    Some([value]) => match params.first() {
+                        Some(inner) => self.decode_value(inner, &[], value),
+                        None => value.clone(),
+                    },
+                    Some([]) => Value::Null,
+                    _ => data.clone(),
+                }
+            }
+            (true, "object", "Object") => match data {
+                // Entry function arguments pass objects by address
+                Value::String(inner) => Value::String(standardize_address(inner)),
+                _ => match data.get("inner") {
+                    Some(Value::String(inner)) => Value::String(standardize_address(inner)),
+                    _ => data.clone(),
+                },
+            },
+            _ => {
+                let key = (address.to_string(), module.to_string(), name.to_string());
+                let (Some(abi), Value::Object(object)) = (self.structs.get(&key), data) else {
+                    return data.clone();
+                };
+                let mut decoded = Map::new();
+                for (field, field_type) in abi.fields.iter() {
+                    if let Some(value) = object.get(field) {
+                        decoded.insert(field.clone(), self.decode_value(field_type, params, value));
+                    }
+                }
+                Value::Object(decoded)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_decode_with_abi() {
+        let mut registry = AbiRegistry::default();
+        registry.add_module(
+            "0xcafe",
+            "mooner_money",
+            &json!({
+                "structs": [{
+                    "name": "TokenTraded",
+                    "fields": [
+                        { "name": "user", "type": { "type": "MOVE_TYPES_ADDRESS" } },
+                        { "name": "amount", "type": { "type": "MOVE_TYPES_U64" } },
+                        { "name": "decimals", "type": { "type": "MOVE_TYPES_U8" } },
+                        { "name": "referrer", "type": { "type": "MOVE_TYPES_STRUCT", "struct": {
+                            "address": "0x1", "module": "option", "name": "Option",
+                            "genericTypeParams": [{ "type": "MOVE_TYPES_ADDRESS" }]
+                        } } },
+                        { "name": "pool", "type": { "type": 8, "struct": {
+                            "address": "0x1", "module": "object", "name": "Object",
+                            "genericTypeParams": [{ "type": "MOVE_TYPES_GENERIC_TYPE_PARAM", "genericTypeParamIndex": 0 }]
+                        } } }
+                    ]
+                }]
+            }),
+        );
+        assert_eq!(
+            registry.decode(
+                "0xcafe::mooner_money::TokenTraded",
+                &json!({
+                    "user": "0x1",
+                    "amount": 100,
+                    "decimals": "8",
+                    "referrer": { "vec": [] },
+                    "pool": { "inner": "0x2" }
+                })
+            ),
+            Some(json!({
+                "user": standardize_address("0x1"),
+                "amount": "100",
+                "decimals": 8,
+                "referrer": null,
+                "pool": standardize_address("0x2")
+            }))
+        );
+        assert_eq!(registry.decode("0xcafe::mooner_money::Unknown", &json!({})), None);
+        assert_eq!(
+            MoveTypeAbi::parse("0x1::option::Option<vector<u8>>"),
+            MoveTypeAbi::Struct {
+                address: standardize_address("0x1"),
+                module: "option".to_string(),
+                name: "Option".to_string(),
+                generic_type_params: vec![MoveTypeAbi::Vector(Box::new(MoveTypeAbi::U8))],
+            }
+        );
+    }
+
+    #[test]
+    fn test_decode_arguments() {
+        let mut registry = AbiRegistry::default();
+        registry.add_module(
+            "0xcafe",
+            "mooner_money",
+            &json!({
+                "exposedFunctions": [{
+                    "name": "buy_entry",
+                    "params": [
+                        { "type": "MOVE_TYPES_REFERENCE", "reference": { "to": { "type": "MOVE_TYPES_SIGNER" } } },
+                        { "type": "MOVE_TYPES_STRUCT", "struct": {
+                            "address": "0x1", "module": "object", "name": "Object",
+                            "genericTypeParams": [{ "type": "MOVE_TYPES_GENERIC_TYPE_PARAM", "genericTypeParamIndex": 0 }]
+                        } },
+                        { "type": "MOVE_TYPES_U64" },
+                        { "type": "MOVE_TYPES_U8" }
+                    ]
+                }]
+            }),
+        );
+        assert_eq!(
+            registry.decode_arguments(
+                "0xcafe",
+                "mooner_money",
+                "buy_entry",
+                &[],
+                &[json!("0x2"), json!("100"), json!("1")]
+            ),
+            Some(json!([standardize_address("0x2"), "100", 1]))
+        );
+        assert_eq!(
+            registry.decode_arguments("0xcafe", "mooner_money", "buy_entry", &[], &[json!("0x2")]),
+            None
+        );
+    }
+}
@@ -0,0 +1,228 @@
+//! Compares two ABIs of the same module, as stored in `module_upgrade_history`.
+
+use serde_json::Value;
+
+pub const ITEM_FUNCTION: &str = "function";
+pub const ITEM_STRUCT: &str = "struct";
+pub const ITEM_FIELD: &str = "field";
+
+pub const CHANGE_ADDED: &str = "added";
+pub const CHANGE_REMOVED: &str = "removed";
+pub const CHANGE_CHANGED: &str = "changed";
+
+/// Event structs decoded by hand in the extractor, as (module, struct). A change to any of
+/// them breaks their decoding.
+pub const DECODED_EVENT_STRUCTS: [(&str, &str); 7] = [
+    ("mooner_money", "TokenCreated"),
+    ("mooner_money", "PoolCompleted"),
+    ("mooner_money", "TokenTraded"),
+    ("staking", "PositionCreated"),
+    ("staking", "RewardsClaimed"),
+    ("staking", "PositionRemoved"),
+    ("mooner_spin", "SpinEvent"),
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AbiChange {
+    pub item_kind: &'static str,
+    /// Function or struct name, `Struct.field` for fields
+    pub item_name: String,
+    pub change_type: &'static str,
+    pub old_abi: Option<Value>,
+    pub new_abi: Option<Value>,
+    /// Whether the change touches an event struct the extractor decodes
+    pub is_breaking: bool,
+}
+
+/// Items of a list keyed by `name`, the ABI json may use either camel or snake case keys.
+fn named_items(abi: &Value, keys: &[&str]) -> Vec<(String, Value)> {
+    keys.iter()
+        .find_map(|key| abi.get(key))
+        .and_then(Value::as_array)
+        .map(|items| {
+            items
+                .iter()
+                .map(|item| {
+                    (
+                        item["name"].as_str().unwrap_or_default().to_string(),
+                        item.clone(),
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Items added, removed or changed between two lists, compared by name.
+fn diff_items(
+    old: &[(String, Value)],
+    new: &[(String, Value)],
+) -> Vec<(String, &'static str, Option<Value>, Option<Value>)> {
+    let find = |items: &[(String, Value)], name: &str| {
+        items.iter().find(|(n, _)| n == name).map(|(_, v)| v.clone())
+    };
+    let mut changes = vec![];
+    for (name, old_item) in old {
+        match find(new, name) {
+            None => changes.push((name.clone(), CHANGE_REMOVED, Some(old_item.clone()), None)),
+            Some(new_item) if new_item != *old_item => changes.push((
+                name.clone(),
+                CHANGE_CHANGED,
+                Some(old_item.clone()),
+                Some(new_item),
+            )),
+            Some(_) => {}
+        }
+    }
+    for (name, new_item) in new {
+        if find(old, name).is_none() {
+            changes.push((name.clone(), CHANGE_ADDED, None, Some(new_item.clone())));
+        }
+    }
+    changes
+}
+
+/// Struct without its fields, so field changes are only reported on the fields.
+fn struct_header(item: &Value) -> Value {
+    let mut header = item.clone();
+    if let Some(object) = header.as_object_mut() {
+        object.remove("fields");
+    }
+    header
+}
+
+pub fn diff_module_abis(module_name: &str, old: &Value, new: &Value) -> Vec<AbiChange> {
+    let is_decoded = |struct_name: &str| {
+        DECODED_EVENT_STRUCTS
+            .iter()
+            .any(|(module, name)| *module == module_name && *name == struct_name)
+    };
+    let mut changes = vec![];
+
+    const FUNCTIONS: [&str; 2] = ["exposedFunctions", "exposed_functions"];
+    for (name, change_type, old_abi, new_abi) in diff_items(
+        &named_items(old, &FUNCTIONS),
+        &named_items(new, &FUNCTIONS),
+    ) {
+        changes.push(AbiChange {
+            item_kind: ITEM_FUNCTION,
+            item_name: name,
+            change_type,
+            old_abi,
+            new_abi,
+            is_breaking: false,
+        });
+    }
+
+    let old_structs = named_items(old, &["structs"]);
+    let new_structs = named_items(new, &["structs"]);
+    let headers = |structs: &[(String, Value)]| {
+        structs
+            .iter()
+            .map(|(name, item)| (name.clone(), struct_header(item)))
+            .collect::<Vec<_>>()
+    };
+    for (name, change_type, old_abi, new_abi) in
+        diff_items(&headers(&old_structs), &headers(&new_structs))
+    {
+        // Report the whole struct when it comes or goes
+        let find = |structs: &[(String, Value)]| {
+            structs.iter().find(|(n, _)| *n == name).map(|(_, v)| v.clone())
+        };
+        let (old_abi, new_abi) = match change_type {
+            CHANGE_CHANGED => (old_abi, new_abi),
+            _ => (find(&old_structs), find(&new_structs)),
+        };
+        changes.push(AbiChange {
+            item_kind: ITEM_STRUCT,
+            // Abilities don't change the shape of the event json
+            is_breaking: change_type == CHANGE_REMOVED && is_decoded(&name),
+            item_name: name,
+            change_type,
+            old_abi,
+            new_abi,
+        });
+    }
+    for (struct_name, old_struct) in old_structs.iter() {
+        let Some((_, new_struct)) = new_structs.iter().find(|(n, _)| n == struct_name) else {
+            continue;
+        };
+        for (name, change_type, old_abi, new_abi) in diff_items(
+            &named_items(old_struct, &["fields"]),
+            &named_items(new_struct, &["fields"]),
+        ) {
+            changes.push(AbiChange {
+                item_kind: ITEM_FIELD,
+                item_name: format!("{}.{}", struct_name, name),
+                change_type,
+                old_abi,
+                new_abi,
+                is_breaking: is_decoded(struct_name),
+            });
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_diff_module_abis() {
+        let old = json!({
+            "exposed_functions": [
+                {"name": "buy", "params": ["&signer", "u64"]},
+                {"name": "sell", "params": ["&signer", "u64"]},
+            ],
+            "structs": [
+                {"name": "TokenTraded", "abilities": ["drop", "store"], "fields": [
+                    {"name": "is_buy", "type": "bool"},
+                    {"name": "aptos_amount", "type": "u64"},
+                ]},
+                {"name": "Pool", "abilities": ["key"], "fields": [
+                    {"name": "creator", "type": "address"},
+                ]},
+            ],
+        });
+        let new = json!({
+            "exposedFunctions": [
+                {"name": "buy", "params": ["&signer", "u64", "u64"]},
+                {"name": "claim", "params": ["&signer"]},
+            ],
+            "structs": [
+                {"name": "TokenTraded", "abilities": ["drop", "store"], "fields": [
+                    {"name": "is_buy", "type": "bool"},
+                    {"name": "aptos_amount", "type": "u128"},
+                ]},
+            ],
+        });
+        let changes = diff_module_abis("mooner_money", &old, &new)
+            .into_iter()
+            .map(|change| {
+                (
+                    change.item_kind,
+                    change.item_name,
+                    change.change_type,
+                    change.is_breaking,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            vec![
+                (ITEM_FUNCTION, "buy".to_string(), CHANGE_CHANGED, false),
+                (ITEM_FUNCTION, "sell".to_string(), CHANGE_REMOVED, false),
+                (ITEM_FUNCTION, "claim".to_string(), CHANGE_ADDED, false),
+                (ITEM_STRUCT, "Pool".to_string(), CHANGE_REMOVED, false),
+                (ITEM_FIELD, "TokenTraded.aptos_amount".to_string(), CHANGE_CHANGED, true),
+            ]
+        );
+        // The same field change outside of the decoded modules is not breaking
+        assert!(diff_module_abis("other", &old, &new)
+            .iter()
+            .all(|change| !change.is_breaking));
+    }
+}
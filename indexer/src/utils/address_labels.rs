@@ -0,0 +1,84 @@
+//! Labels of known addresses, imported from `address_labels_config.file` at startup and
+//! derived from the transactions of the contracts by the extractor.
+
+use anyhow::{Context, Result};
+use diesel::{delete, insert_into, ExpressionMethods, QueryDsl, QueryResult};
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+
+use super::database_utils::ArcDbPool;
+use crate::{
+    config::indexer_processor_config::AddressLabelsConfig,
+    db_models::address_labels::{AddressLabel, LabelFileEntry, LABEL_SOURCE_FILE},
+    schema::address_labels,
+    utils::database_connection::get_db_connection,
+};
+
+/// Replaces the labels of the previous import with the labels of the configured file, in one
+/// transaction. The auto labels are kept next to the file labels of the same name. Nothing is
+/// changed when no file is configured.
+pub async fn import_address_labels(config: &AddressLabelsConfig, db_pool: ArcDbPool) -> Result<()> {
+    let Some(path) = config.file.as_ref() else {
+        return Ok(());
+    };
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read label file {}", path))?;
+    let mut labels = LabelFileEntry::parse(path, &content)
+        .with_context(|| format!("Failed to parse label file {}", path))?
+        .iter()
+        .map(LabelFileEntry::to_db_address_label)
+        .collect::<Vec<_>>();
+    // The last entry of a repeated address and label wins
+    labels.reverse();
+    labels.sort_by(|a, b| (&a.address, &a.label).cmp(&(&b.address, &b.label)));
+    labels.dedup_by(|a, b| a.address == b.address && a.label == b.label);
+
+    let mut conn = get_db_connection(&db_pool)
+        .await
+        .context("Failed to get connection from pool while importing address labels")?;
+    let imported = labels.len();
+    conn.transaction(|conn| {
+        Box::pin(async move {
+            delete(address_labels::table.filter(address_labels::source.eq(LABEL_SOURCE_FILE)))
+                .execute(conn)
+                .await?;
+            for chunk in labels.chunks(1000) {
+                insert_into(address_labels::table)
+                    .values(chunk)
+                    .execute(conn)
+                    .await?;
+            }
+            Ok::<(), diesel::result::Error>(())
+        })
+    })
+    .await
+    .context("Error importing address labels")?;
+    tracing::info!("Imported {} address labels from {}", imported, path);
+    Ok(())
+}
+
+/// Labels of a set of addresses, in address then label order. A file label hides the auto
+/// label of the same name.
+pub async fn load_address_labels(
+    conn: &mut AsyncPgConnection,
+    addresses: &[String],
+) -> QueryResult<Vec<AddressLabel>> {
+    address_labels::table
+        .filter(address_labels::address.eq_any(addresses))
+        .distinct_on((address_labels::address, address_labels::label))
+        .order((
+            address_labels::address,
+            address_labels::label,
+            address_labels::source.desc(),
+        ))
+        .load::<AddressLabel>(conn)
+        .await
+}
+
+/// Labels of an address within a set loaded by [`load_address_labels`].
+pub fn labels_of(labels: &[AddressLabel], address: &str) -> Vec<AddressLabel> {
+    labels
+        .iter()
+        .filter(|label| label.address == address)
+        .cloned()
+        .collect()
+}
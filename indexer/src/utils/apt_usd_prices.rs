@@ -0,0 +1,244 @@
+//! APT/USD price grid in `apt_usd_prices` and the USD values of trades priced from it.
+//! Prices come from the source in `price_config`: a polled klines endpoint, a csv imported
+//! at startup or the Pyth price feed read by the extractor.
+//!
+//! Fees have no table of their own, they are only stored as columns of `trades`: `fee` and
+//! `creator_fee` in octas and `fee_usd` in dollars.
+
+use std::{collections::BTreeMap, str::FromStr};
+
+use anyhow::{Context, Result};
+use bigdecimal::BigDecimal;
+use diesel::{
+    insert_into, sql_query, sql_types::BigInt, upsert::excluded, ExpressionMethods, QueryDsl,
+    QueryResult,
+};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use reqwest::Client;
+
+use super::database_utils::ArcDbPool;
+use crate::{
+    config::indexer_processor_config::{FeeConfig, PriceConfig},
+    db_models::{
+        apt_usd_prices::{minute_ts, AptUsdPrice, PriceFileEntry, PRICE_SOURCE_HTTP},
+        trades::Trade,
+    },
+    schema::apt_usd_prices,
+    utils::{database_connection::get_db_connection, fees::trade_fee},
+};
+
+/// Klines returned per request by the Binance api
+const KLINES_LIMIT: i64 = 1000;
+
+/// Imports the prices of the configured csv file, nothing is done when no file is set.
+pub async fn import_apt_usd_prices(config: &PriceConfig, db_pool: ArcDbPool) -> Result<()> {
+    let Some(path) = config.csv_file.as_ref() else {
+        return Ok(());
+    };
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read price file {}", path))?;
+    let prices = PriceFileEntry::parse(&content)
+        .with_context(|| format!("Failed to parse price file {}", path))?
+        .iter()
+        .map(PriceFileEntry::to_db_apt_usd_price)
+        .collect::<Vec<_>>();
+    let mut conn = get_db_connection(&db_pool)
+        .await
+        .context("Failed to get connection from pool while importing apt usd prices")?;
+    let count = upsert_apt_usd_prices(&mut conn, prices)
+        .await
+        .context("Error importing apt usd prices")?;
+    tracing::info!("Imported {} apt usd prices from {}", count, path);
+    Ok(())
+}
+
+/// Stores the latest observation of every minute, a minute keeps its price when the stored
+/// observation is later. Returns the number of minutes written.
+pub async fn upsert_apt_usd_prices(
+    conn: &mut AsyncPgConnection,
+    prices: Vec<AptUsdPrice>,
+) -> QueryResult<usize> {
+    let prices = AptUsdPrice::latest_per_minute(prices);
+    let mut count = 0;
+    for chunk in prices.chunks(1000) {
+        let query = insert_into(apt_usd_prices::table)
+            .values(chunk)
+            .on_conflict(apt_usd_prices::minute_ts)
+            .do_update()
+            .set((
+                apt_usd_prices::price.eq(excluded(apt_usd_prices::price)),
+                apt_usd_prices::price_ts.eq(excluded(apt_usd_prices::price_ts)),
+                apt_usd_prices::source.eq(excluded(apt_usd_prices::source)),
+            ));
+        // Only replaced by a later observation of the minute
+        count += diesel::query_dsl::methods::FilterDsl::filter(
+            query,
+            apt_usd_prices::price_ts.le(excluded(apt_usd_prices::price_ts)),
+        )
+        .execute(conn)
+        .await?;
+    }
+    Ok(count)
+}
+
+/// Latest minute of the grid, if any.
+pub async fn latest_price_minute(conn: &mut AsyncPgConnection) -> QueryResult<Option<i64>> {
+    apt_usd_prices::table
+        .select(diesel::dsl::max(apt_usd_prices::minute_ts))
+        .first::<Option<i64>>(conn)
+        .await
+}
+
+/// Closing prices of the minutes from `start_ts` that closed before `end_ts`, read from a
+/// Binance compatible klines endpoint. A single page is read, the caller continues from the
+/// last minute returned.
+pub async fn fetch_http_prices(
+    client: &Client,
+    url: &str,
+    start_ts: i64,
+    end_ts: i64,
+) -> Result<Vec<AptUsdPrice>> {
+    let url = format!(
+        "{}{}startTime={}&endTime={}&limit={}",
+        url,
+        if url.contains('?') { "&" } else { "?" },
+        start_ts * 1000,
+        end_ts * 1000 - 1,
+        KLINES_LIMIT
+    );
+    let response = client.get(&url).send().await?.error_for_status()?;
+    // [open time, open, high, low, close, volume, close time, ...], times in milliseconds
+    let klines = response.json::<Vec<Vec<serde_json::Value>>>().await?;
+    klines
+        .iter()
+        .filter(|kline| {
+            kline
+                .get(6)
+                .and_then(|close_ts| close_ts.as_i64())
+                .is_some_and(|close_ts| close_ts < end_ts * 1000)
+        })
+        .map(|kline| {
+            let open_ts = kline
+                .as_slice()
+                .first()
+                .and_then(|open| open.as_i64())
+                .context("Kline without an open time")?;
+            let close = kline
+                .get(4)
+                .and_then(|close| close.as_str())
+                .context("Kline without a close price")?;
+            Ok(AptUsdPrice::new(
+                open_ts / 1000,
+                BigDecimal::from_str(close)?,
+                PRICE_SOURCE_HTTP,
+            ))
+        })
+        .collect()
+}
+
+/// Prices of the minutes a set of timestamps can be valued at.
+async fn load_prices(
+    conn: &mut AsyncPgConnection,
+    config: &PriceConfig,
+    timestamps: impl Iterator<Item = i64>,
+) -> QueryResult<BTreeMap<i64, BigDecimal>> {
+    let (min_ts, max_ts) = timestamps.fold((i64::MAX, i64::MIN), |(min, max), ts| {
+        (min.min(ts), max.max(ts))
+    });
+    if min_ts > max_ts {
+        return Ok(BTreeMap::new());
+    }
+    Ok(apt_usd_prices::table
+        .filter(apt_usd_prices::minute_ts.ge(minute_ts(min_ts) - config.max_price_age_secs))
+        .filter(apt_usd_prices::minute_ts.le(minute_ts(max_ts)))
+        .select((apt_usd_prices::minute_ts, apt_usd_prices::price))
+        .load::<(i64, BigDecimal)>(conn)
+        .await?
+        .into_iter()
+        .collect())
+}
+
+/// Price of the latest minute at or before `ts`, if it is at most `max_price_age_secs` old.
+fn price_at<'a>(
+    prices: &'a BTreeMap<i64, BigDecimal>,
+    config: &PriceConfig,
+    ts: i64,
+) -> Option<&'a BigDecimal> {
+    let minute = minute_ts(ts);
+    prices
+        .range(..=minute)
+        .next_back()
+        .filter(|(price_minute, _)| minute - **price_minute <= config.max_price_age_secs)
+        .map(|(_, price)| price)
+}
+
+/// APT/USD price at a timestamp, from the latest minute at most `max_price_age_secs` old.
+pub async fn apt_usd_price_at(
+    conn: &mut AsyncPgConnection,
+    config: &PriceConfig,
+    ts: i64,
+) -> QueryResult<Option<BigDecimal>> {
+    let prices = load_prices(conn, config, std::iter::once(ts)).await?;
+    Ok(price_at(&prices, config, ts).cloned())
+}
+
+/// USD value of the aptos amount and of the fee of a trade, in dollars.
+pub fn trade_usd(aptos_amount: i64, fee: i64, price: &BigDecimal) -> (BigDecimal, BigDecimal) {
+    let octas = BigDecimal::from(100_000_000);
+    (
+        (BigDecimal::from(aptos_amount) * price / &octas).round(6),
+        (BigDecimal::from(fee) * price / &octas).round(6),
+    )
+}
+
+/// Sets the USD values of trades about to be stored, trades without a recent enough price
+/// are left for [`backfill_trade_prices`].
+pub async fn price_trades(
+    conn: &mut AsyncPgConnection,
+    config: &PriceConfig,
+    fee_config: &FeeConfig,
+    trades: &mut [Trade],
+) -> QueryResult<()> {
+    let prices = load_prices(conn, config, trades.iter().map(|trade| trade.ts)).await?;
+    for trade in trades.iter_mut() {
+        if let Some(price) = price_at(&prices, config, trade.ts) {
+            let fee = trade
+                .fee
+                .unwrap_or_else(|| trade_fee(fee_config, trade.is_buy, trade.aptos_amount));
+            let (aptos_amount_usd, fee_usd) = trade_usd(trade.aptos_amount, fee, price);
+            trade.apt_usd_price = Some(price.clone());
+            trade.aptos_amount_usd = Some(aptos_amount_usd);
+            trade.fee_usd = Some(fee_usd);
+        }
+    }
+    Ok(())
+}
+
+/// Values the stored trades that have no USD values yet at the latest price of the grid at
+/// most `max_price_age_secs` before their minute, in one statement. Trades stored before
+/// their fees were stamped are valued with `fee_config`. Returns the number of trades priced.
+pub async fn backfill_trade_prices(
+    conn: &mut AsyncPgConnection,
+    config: &PriceConfig,
+    fee_config: &FeeConfig,
+) -> QueryResult<usize> {
+    sql_query(
+        "UPDATE trades SET \
+             apt_usd_price = prices.price, \
+             aptos_amount_usd = ROUND(trades.aptos_amount * prices.price / 100000000, 6), \
+             fee_usd = ROUND(COALESCE(trades.fee, CASE WHEN trades.is_buy \
+                 THEN trades.aptos_amount * $2 / (10000 + $2) \
+                 ELSE trades.aptos_amount * $2 / 10000 END) * prices.price / 100000000, 6) \
+         FROM apt_usd_prices AS prices \
+         WHERE trades.apt_usd_price IS NULL \
+             AND trades.ts >= (SELECT MIN(minute_ts) FROM apt_usd_prices) \
+             AND prices.minute_ts = ( \
+                 SELECT MAX(minute_ts) FROM apt_usd_prices \
+                 WHERE minute_ts <= trades.ts - trades.ts % 60 \
+                     AND minute_ts >= trades.ts - trades.ts % 60 - $1)",
+    )
+    .bind::<BigInt, _>(config.max_price_age_secs)
+    .bind::<BigInt, _>(fee_config.fee_bps)
+    .execute(conn)
+    .await
+}
@@ -0,0 +1,53 @@
+//! Tags the rows indexed before rows carried their contract address.
+
+use anyhow::{Context, Result};
+use diesel::{update, ExpressionMethods, QueryDsl};
+use diesel_async::{AsyncConnection, RunQueryDsl};
+
+use super::database_utils::ArcDbPool;
+use crate::{
+    config::indexer_processor_config::ContractConfig,
+    schema::{stakings, tokens, trades},
+    utils::database_connection::get_db_connection,
+};
+
+/// Sets the untagged tokens, trades and stakings to the contract configured for them, see
+/// [`ContractConfig::untagged_contract`]. Nothing is changed when none is.
+pub async fn backfill_contract_address(config: &ContractConfig, db_pool: ArcDbPool) -> Result<()> {
+    let Some(contract_address) = config.untagged_contract() else {
+        return Ok(());
+    };
+    let mut conn = get_db_connection(&db_pool)
+        .await
+        .context("Failed to get connection from pool while tagging rows")?;
+    let (tokens_tagged, trades_tagged, stakings_tagged) = conn
+        .transaction(|conn| {
+            Box::pin(async move {
+                let tokens_tagged = update(tokens::table.filter(tokens::contract_address.eq("")))
+                    .set(tokens::contract_address.eq(&contract_address))
+                    .execute(conn)
+                    .await?;
+                let trades_tagged = update(trades::table.filter(trades::contract_address.eq("")))
+                    .set(trades::contract_address.eq(&contract_address))
+                    .execute(conn)
+                    .await?;
+                let stakings_tagged =
+                    update(stakings::table.filter(stakings::contract_address.eq("")))
+                        .set(stakings::contract_address.eq(&contract_address))
+                        .execute(conn)
+                        .await?;
+                Ok::<_, diesel::result::Error>((tokens_tagged, trades_tagged, stakings_tagged))
+            })
+        })
+        .await
+        .context("Error tagging rows with their contract address")?;
+    if tokens_tagged + trades_tagged + stakings_tagged > 0 {
+        tracing::info!(
+            "Tagged {} tokens, {} trades and {} stakings with their contract address",
+            tokens_tagged,
+            trades_tagged,
+            stakings_tagged
+        );
+    }
+    Ok(())
+}
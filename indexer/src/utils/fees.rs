@@ -0,0 +1,68 @@
+use crate::config::indexer_processor_config::FeeConfig;
+
+/// Total fee paid on a trade, in octas. Buys report the aptos amount including the fee,
+/// sells report it before the fee is taken.
+pub fn trade_fee(fee_config: &FeeConfig, is_buy: bool, aptos_amount: i64) -> i64 {
+    let aptos_amount = aptos_amount as i128;
+    let fee_bps = fee_config.fee_bps as i128;
+    let fee = if is_buy {
+        aptos_amount * fee_bps / (10_000 + fee_bps)
+    } else {
+        aptos_amount * fee_bps / 10_000
+    };
+    fee as i64
+}
+
+/// Part of the trade fee paid to the token creator, in octas.
+pub fn creator_fee(fee_config: &FeeConfig, is_buy: bool, aptos_amount: i64) -> i64 {
+    trade_fee(fee_config, is_buy, aptos_amount) * fee_config.creator_fee_bps / 10_000
+}
+
+/// Aptos the user actually paid on a buy or received on a sell, in octas.
+pub fn net_aptos_amount(fee_config: &FeeConfig, is_buy: bool, aptos_amount: i64) -> i64 {
+    if is_buy {
+        aptos_amount
+    } else {
+        aptos_amount - trade_fee(fee_config, is_buy, aptos_amount)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// `math64::mul_div` of the Move contract
+    fn mul_div(a: u64, b: u64, c: u64) -> u64 {
+        (a as u128 * b as u128 / c as u128) as u64
+    }
+
+    #[test]
+    fn test_trade_fee_matches_move() {
+        let fee_config = FeeConfig::default();
+        let fee = fee_config.fee_bps as u64;
+        let creator_fee_bps = fee_config.creator_fee_bps as u64;
+        for amount in (1..2_000_000u64).step_by(997).chain([100_000_000, 123_456_789_012]) {
+            // mooner_money::buy reports the aptos in plus the fee on it
+            let buy_fee = mul_div(amount, fee, 10_000);
+            let aptos_amount = (amount + buy_fee) as i64;
+            assert_eq!(trade_fee(&fee_config, true, aptos_amount), buy_fee as i64);
+            assert_eq!(
+                creator_fee(&fee_config, true, aptos_amount),
+                mul_div(buy_fee, creator_fee_bps, 10_000) as i64
+            );
+            assert_eq!(net_aptos_amount(&fee_config, true, aptos_amount), aptos_amount);
+
+            // mooner_money::sell_entry reports the aptos out before the fee
+            let sell_fee = mul_div(amount, fee, 10_000);
+            assert_eq!(trade_fee(&fee_config, false, amount as i64), sell_fee as i64);
+            assert_eq!(
+                creator_fee(&fee_config, false, amount as i64),
+                mul_div(sell_fee, creator_fee_bps, 10_000) as i64
+            );
+            assert_eq!(
+                net_aptos_amount(&fee_config, false, amount as i64),
+                (amount - sell_fee) as i64
+            );
+        }
+    }
+}
@@ -1,6 +1,18 @@
+pub mod address_labels;
+pub mod apt_usd_prices;
+pub mod abi_decoder;
+pub mod abi_diff;
 pub mod chain_id;
+pub mod contract_address;
 pub mod database_connection;
 pub mod database_execution;
 pub mod database_utils;
+pub mod fees;
 pub mod latest_processed_version_tracker;
+pub mod move_errors;
+pub mod package_metadata;
+pub mod pnl;
 pub mod starting_version;
+pub mod whitelist_report;
+pub mod xp_reconcile;
+pub mod xp_rules;
@@ -0,0 +1,132 @@
+//! Abort codes of the contract modules, mirroring the `ERR_*` constants in `move/sources`.
+
+use aptos_indexer_processor_sdk::utils::convert::standardize_address;
+
+pub const MOONER_MONEY_ERRORS: [&str; 16] = [
+    "ERR_NOT_ADMIN",
+    "ERR_NOT_INITIALIZED",
+    "ERR_PAUSED",
+    "ERR_INVALID_PERCENTAGE",
+    "ERR_DURATION_TOO_HIGH",
+    "ERR_NO_WHITELISTED_WALLETS",
+    "ERR_ZERO_DIVISOR",
+    "ERR_POOL_COMPLETED",
+    "ERR_ZERO_AMOUNT",
+    "ERR_ZERO_RESERVES",
+    "ERR_MAX_INPUT_TOO_SMALL",
+    "ERR_INSUFFICIENT_OUTPUT_AMOUNT",
+    "ERR_NOT_WHITELISTED",
+    "ERR_MIN_OUTPUT_TOO_SMALL",
+    "ERR_POOL_NOT_COMPLETED",
+    "ERR_ZERO_BALANCE",
+];
+
+pub const MOONER_SPIN_ERRORS: [&str; 4] = [
+    "ERR_NOT_GAME_OWNER",
+    "ERR_GAME_STATUS_NOT_INITITALIZED",
+    "ERR_NOT_PENDING_GAME_OWNER",
+    "ERR_GAME_STATUS_ALREADY_INITIALIZED",
+];
+
+pub const HELPER_ERRORS: [&str; 1] = ["ERR_NOT_ADMIN"];
+
+/// Name of the error constant of a contract module for an abort code.
+pub fn error_name(module_name: &str, code: u64) -> Option<&'static str> {
+    let errors: &[&str] = match module_name {
+        "mooner_money" => &MOONER_MONEY_ERRORS,
+        "mooner_spin" => &MOONER_SPIN_ERRORS,
+        "helper" => &HELPER_ERRORS,
+        _ => return None,
+    };
+    errors.get(code as usize).copied()
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MoveAbort {
+    pub module_address: String,
+    pub module_name: String,
+    pub code: u64,
+    /// From the vm status when the module ships an error map, otherwise from our constants
+    /// for the contract modules
+    pub name: Option<String>,
+}
+
+impl MoveAbort {
+    /// Parses a vm status such as `Move abort in 0x1::coin: EINSUFFICIENT_BALANCE(0x10006): ...`
+    /// or `Move abort in 0xcafe::mooner_money: 0xc`.
+    pub fn parse(vm_status: &str, contract_addresses: &[String]) -> Option<Self> {
+        let rest = vm_status.strip_prefix("Move abort in ")?;
+        let (location, abort) = rest.split_once(": ")?;
+        let (module_address, module_name) = location.split_once("::")?;
+        let (name, hex_code) = match abort.strip_prefix("0x") {
+            Some(hex_code) => (None, hex_code),
+            None => {
+                let (name, code) = abort.split_once("(0x")?;
+                (Some(name.to_string()), code)
+            }
+        };
+        let hex_code = hex_code
+            .split(|c: char| !c.is_ascii_hexdigit())
+            .next()
+            .unwrap_or_default();
+        let code = u64::from_str_radix(hex_code, 16).ok()?;
+        let module_address = standardize_address(module_address);
+        let name = match name {
+            Some(name) => Some(name),
+            None if contract_addresses.contains(&module_address) => {
+                error_name(module_name, code).map(String::from)
+            }
+            None => None,
+        };
+        Some(Self {
+            module_address,
+            module_name: module_name.to_string(),
+            code,
+            name,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_move_abort() {
+        let contract = standardize_address("0xcafe");
+        let contract_addresses = vec![contract.clone()];
+        assert_eq!(
+            MoveAbort::parse(
+                "Move abort in 0x1::coin: EINSUFFICIENT_BALANCE(0x10006): Not enough coins",
+                &contract_addresses,
+            ),
+            Some(MoveAbort {
+                module_address: standardize_address("0x1"),
+                module_name: "coin".to_string(),
+                code: 0x10006,
+                name: Some("EINSUFFICIENT_BALANCE".to_string()),
+            })
+        );
+        assert_eq!(
+            MoveAbort::parse("Move abort in 0xcafe::mooner_money: 0xc", &contract_addresses),
+            Some(MoveAbort {
+                module_address: contract.clone(),
+                module_name: "mooner_money".to_string(),
+                code: 12,
+                name: Some("ERR_NOT_WHITELISTED".to_string()),
+            })
+        );
+        // Codes of modules outside the contracts are not named from our constants
+        assert_eq!(
+            MoveAbort::parse("Move abort in 0xbeef::mooner_money: 0xc", &contract_addresses)
+                .and_then(|abort| abort.name),
+            None
+        );
+        assert_eq!(
+            MoveAbort::parse("Move abort in 0xcafe::mooner_spin: 0x63", &contract_addresses)
+                .and_then(|abort| abort.name),
+            None
+        );
+        assert_eq!(MoveAbort::parse("Out of gas", &contract_addresses), None);
+    }
+}
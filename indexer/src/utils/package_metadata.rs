@@ -0,0 +1,194 @@
+//! Decoding of the package metadata published in `0x1::code::PackageRegistry`. Manifests and
+//! module sources are gzip'd and arrive as hex strings, `0x` when the deployer hid them.
+
+use diesel::{insert_into, ExpressionMethods, QueryDsl, QueryResult};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use flate2::read::GzDecoder;
+use std::io::Read;
+
+use crate::{
+    db_models::package_upgrade::{PackageOnChain, UpgradePolicyOnChain},
+    schema::{module_upgrade_history, package_dependencies, package_upgrade_history},
+};
+
+/// Text of a gzip'd hex string, `None` when it is empty or doesn't decode.
+pub fn decode_gzip_hex(value: &str) -> Option<String> {
+    let bytes = hex::decode(value.trim_start_matches("0x")).ok()?;
+    if bytes.is_empty() {
+        return None;
+    }
+    let mut text = String::new();
+    GzDecoder::new(bytes.as_slice())
+        .read_to_string(&mut text)
+        .ok()?;
+    Some(text)
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ManifestDependency {
+    pub name: String,
+    pub git: Option<String>,
+    pub rev: Option<String>,
+    pub subdir: Option<String>,
+    pub local: Option<String>,
+    /// Set for dependencies resolved from chain, `{ aptos = "0x1" }`
+    pub aptos: Option<String>,
+}
+
+/// Entries of the `[dependencies]` table of a Move.toml.
+pub fn parse_manifest_dependencies(manifest: &str) -> Vec<ManifestDependency> {
+    let manifest = match toml::from_str::<toml::Value>(manifest) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            tracing::warn!("Failed to parse package manifest: {}", e);
+            return vec![];
+        }
+    };
+    let Some(dependencies) = manifest.get("dependencies").and_then(|d| d.as_table()) else {
+        return vec![];
+    };
+    dependencies
+        .iter()
+        .map(|(name, source)| {
+            let field = |key: &str| source.get(key).and_then(|v| v.as_str()).map(String::from);
+            ManifestDependency {
+                name: name.clone(),
+                git: field("git"),
+                rev: field("rev"),
+                subdir: field("subdir"),
+                local: field("local"),
+                aptos: field("aptos"),
+            }
+        })
+        .collect()
+}
+
+/// Decodes the manifests and sources of upgrades stored before they were decoded on insert,
+/// returning the number of (packages, modules) updated. Dependencies of those packages are
+/// taken from their manifest only, the on-chain deps were not kept.
+pub async fn backfill_package_metadata(
+    conn: &mut AsyncPgConnection,
+) -> QueryResult<(usize, usize)> {
+    let packages = package_upgrade_history::table
+        .filter(package_upgrade_history::manifest_text.is_null())
+        .select((
+            package_upgrade_history::package_addr,
+            package_upgrade_history::package_name,
+            package_upgrade_history::upgrade_number,
+            package_upgrade_history::package_manifest,
+            package_upgrade_history::tx_version,
+        ))
+        .load::<(String, String, i64, String, i64)>(conn)
+        .await?;
+    let mut packages_updated = 0;
+    for (package_addr, package_name, upgrade_number, manifest, tx_version) in packages {
+        let Some(manifest_text) = decode_gzip_hex(&manifest) else {
+            continue;
+        };
+        let package = PackageOnChain {
+            deps: vec![],
+            manifest,
+            modules: vec![],
+            name: package_name.clone(),
+            source_digest: String::new(),
+            upgrade_number: upgrade_number.to_string(),
+            upgrade_policy: UpgradePolicyOnChain { policy: 0 },
+        };
+        let dependencies =
+            package.to_db_package_dependencies(tx_version, &package_addr, Some(&manifest_text));
+        diesel::update(
+            package_upgrade_history::table
+                .filter(package_upgrade_history::package_addr.eq(&package_addr))
+                .filter(package_upgrade_history::package_name.eq(&package_name))
+                .filter(package_upgrade_history::upgrade_number.eq(upgrade_number)),
+        )
+        .set(package_upgrade_history::manifest_text.eq(&manifest_text))
+        .execute(conn)
+        .await?;
+        insert_into(package_dependencies::table)
+            .values(dependencies)
+            .on_conflict_do_nothing()
+            .execute(conn)
+            .await?;
+        packages_updated += 1;
+    }
+
+    let modules = module_upgrade_history::table
+        .filter(module_upgrade_history::module_source_text.is_null())
+        .select((
+            module_upgrade_history::module_addr,
+            module_upgrade_history::module_name,
+            module_upgrade_history::upgrade_number,
+            module_upgrade_history::module_source_code,
+        ))
+        .load::<(String, String, i64, String)>(conn)
+        .await?;
+    let mut modules_updated = 0;
+    for (module_addr, module_name, upgrade_number, source) in modules {
+        let Some(source_text) = decode_gzip_hex(&source) else {
+            continue;
+        };
+        diesel::update(
+            module_upgrade_history::table
+                .filter(module_upgrade_history::module_addr.eq(&module_addr))
+                .filter(module_upgrade_history::module_name.eq(&module_name))
+                .filter(module_upgrade_history::upgrade_number.eq(upgrade_number)),
+        )
+        .set(module_upgrade_history::module_source_text.eq(&source_text))
+        .execute(conn)
+        .await?;
+        modules_updated += 1;
+    }
+    Ok((packages_updated, modules_updated))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use flate2::{write::GzEncoder, Compression};
+    use std::io::Write;
+
+    #[test]
+    fn test_decode_package_metadata() {
+        let manifest = r#"[package]
+name = "mooner"
+version = "1.0.0"
+
+[dependencies]
+AptosFramework = { git = "https://github.com/aptos-labs/aptos-core.git", rev = "mainnet", subdir = "aptos-move/framework/aptos-framework" }
+Local = { local = "../local" }
+Std = { aptos = "0x1" }
+"#;
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(manifest.as_bytes()).unwrap();
+        let hex_manifest = format!("0x{}", hex::encode(encoder.finish().unwrap()));
+        assert_eq!(decode_gzip_hex(&hex_manifest).as_deref(), Some(manifest));
+        assert_eq!(decode_gzip_hex("0x"), None);
+        assert_eq!(decode_gzip_hex("0x1234"), None);
+
+        let dependencies = parse_manifest_dependencies(manifest);
+        assert_eq!(
+            dependencies,
+            vec![
+                ManifestDependency {
+                    name: "AptosFramework".to_string(),
+                    git: Some("https://github.com/aptos-labs/aptos-core.git".to_string()),
+                    rev: Some("mainnet".to_string()),
+                    subdir: Some("aptos-move/framework/aptos-framework".to_string()),
+                    ..Default::default()
+                },
+                ManifestDependency {
+                    name: "Local".to_string(),
+                    local: Some("../local".to_string()),
+                    ..Default::default()
+                },
+                ManifestDependency {
+                    name: "Std".to_string(),
+                    aptos: Some("0x1".to_string()),
+                    ..Default::default()
+                },
+            ]
+        );
+        assert!(parse_manifest_dependencies("not toml [").is_empty());
+    }
+}
@@ -0,0 +1,115 @@
+use ahash::AHashMap;
+use diesel::{insert_into, ExpressionMethods, QueryDsl, QueryResult};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+
+use crate::{
+    config::indexer_processor_config::FeeConfig,
+    db_models::{
+        positions::{Position, RealizedPnl},
+        trades::Trade,
+    },
+    schema::{realized_pnl, trades},
+    utils::database_utils::get_config_table_chunk_size,
+};
+
+/// Average cost basis position of a user in a token, in octas and token base units.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CostBasisPosition {
+    pub balance: i128,
+    pub cost_basis: i128,
+    pub realized_pnl: i128,
+}
+
+impl CostBasisPosition {
+    /// Applies a trade with the net aptos amount the user paid or received, and returns
+    /// the pnl realized by it. Selling more than the tracked balance (tokens received
+    /// outside of trades) realizes the extra proceeds at a zero cost basis.
+    pub fn apply_trade(&mut self, is_buy: bool, net_aptos_amount: i64, token_amount: i64) -> i128 {
+        let aptos_amount = net_aptos_amount as i128;
+        let token_amount = token_amount as i128;
+        if is_buy {
+            self.balance += token_amount;
+            self.cost_basis += aptos_amount;
+            return 0;
+        }
+        let sold = token_amount.min(self.balance);
+        let cost_removed = if self.balance > 0 {
+            self.cost_basis * sold / self.balance
+        } else {
+            0
+        };
+        self.balance -= sold;
+        self.cost_basis -= cost_removed;
+        let realized = aptos_amount - cost_removed;
+        self.realized_pnl += realized;
+        realized
+    }
+}
+
+/// Replays every stored trade through the positions and records the pnl realized by the
+/// sells stored before `realized_pnl` existed, returning the number of sells inserted.
+pub async fn backfill_realized_pnl(
+    conn: &mut AsyncPgConnection,
+    fee_config: &FeeConfig,
+) -> QueryResult<usize> {
+    let all_trades = trades::table
+        .order(trades::txn_version.asc())
+        .load::<Trade>(conn)
+        .await?;
+    let mut positions: AHashMap<(String, String), Position> = AHashMap::new();
+    let mut realized: Vec<RealizedPnl> = vec![];
+    for trade in all_trades.iter() {
+        realized.extend(
+            positions
+                .entry((trade.user_addr.clone(), trade.token_address.clone()))
+                .or_insert_with(|| {
+                    Position::new(trade.user_addr.clone(), trade.token_address.clone())
+                })
+                .apply_trade(fee_config, trade),
+        );
+    }
+
+    let chunk_size = get_config_table_chunk_size::<RealizedPnl>("realized_pnl", &AHashMap::new());
+    let mut inserted = 0;
+    for chunk in realized.chunks(chunk_size) {
+        inserted += insert_into(realized_pnl::table)
+            .values(chunk.to_vec())
+            .on_conflict(realized_pnl::txn_version)
+            .do_nothing()
+            .execute(conn)
+            .await?;
+    }
+    Ok(inserted)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_apply_trade() {
+        let mut position = CostBasisPosition::default();
+        assert_eq!(position.apply_trade(true, 1_000, 100), 0);
+        assert_eq!(position.apply_trade(true, 3_000, 100), 0);
+        // Average cost of 20 per token
+        assert_eq!(position.apply_trade(false, 1_500, 50), 500);
+        assert_eq!(
+            position,
+            CostBasisPosition {
+                balance: 150,
+                cost_basis: 3_000,
+                realized_pnl: 500,
+            }
+        );
+        // 50 tokens received outside of trades are sold at a zero cost basis
+        assert_eq!(position.apply_trade(false, 4_000, 200), 1_000);
+        assert_eq!(
+            position,
+            CostBasisPosition {
+                balance: 0,
+                cost_basis: 0,
+                realized_pnl: 1_500,
+            }
+        );
+    }
+}
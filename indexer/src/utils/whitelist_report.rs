@@ -0,0 +1,169 @@
+//! Supply taken by the wallets that bought a token during its whitelist window, for sniper
+//! and insider analysis. Trades are attributed to the window by `trades.in_whitelist_window`.
+
+use bigdecimal::{BigDecimal, Zero};
+use diesel::{ExpressionMethods, QueryDsl, QueryResult};
+use diesel_async::{AsyncPgConnection, RunQueryDsl};
+use serde::Serialize;
+
+use crate::{
+    db_models::whitelist_windows::WhitelistWindow,
+    schema::{positions, tokens, trades, whitelist_windows},
+};
+
+#[derive(Clone, Debug, Serialize)]
+pub struct WhitelistWallet {
+    pub address: String,
+    pub token_amount: BigDecimal,
+    pub aptos_amount: BigDecimal,
+    /// Current balance in the token, from the positions
+    pub balance: BigDecimal,
+}
+
+#[derive(Clone, Debug, Serialize)]
+/// Supply bought by the wallets that traded in the whitelist window of a token
+pub struct WhitelistReport {
+    pub window: WhitelistWindow,
+    /// Supply of the pre token, sold through the pool
+    pub supply: BigDecimal,
+    pub token_amount: BigDecimal,
+    pub aptos_amount: BigDecimal,
+    /// Share of the supply bought in the window, in percent
+    pub supply_percentage: BigDecimal,
+    /// Balance the wallets still hold
+    pub balance: BigDecimal,
+    /// Largest buyers first
+    pub wallets: Vec<WhitelistWallet>,
+}
+
+/// Report of the whitelist window of a token by its pre address, `None` when the window
+/// is unknown.
+pub async fn load_whitelist_report(
+    conn: &mut AsyncPgConnection,
+    token_address: &str,
+) -> QueryResult<Option<WhitelistReport>> {
+    let window = match whitelist_windows::table
+        .find(token_address)
+        .first::<WhitelistWindow>(conn)
+        .await
+    {
+        Ok(window) => window,
+        Err(diesel::result::Error::NotFound) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    let (virtual_token_reserves, remain_token_reserves) = tokens::table
+        .filter(tokens::pre_addr.eq(token_address))
+        .select((tokens::virtual_token_reserves, tokens::remain_token_reserves))
+        .first::<(BigDecimal, BigDecimal)>(conn)
+        .await?;
+    let supply = virtual_token_reserves - remain_token_reserves;
+
+    let buys = trades::table
+        .filter(trades::token_address.eq(token_address))
+        .filter(trades::is_buy.eq(true))
+        .filter(trades::in_whitelist_window.eq(true))
+        .group_by(trades::user_addr)
+        .select((
+            trades::user_addr,
+            diesel::dsl::sum(trades::token_amount),
+            diesel::dsl::sum(trades::aptos_amount),
+        ))
+        .load::<(String, Option<BigDecimal>, Option<BigDecimal>)>(conn)
+        .await?;
+    let balances = positions::table
+        .filter(positions::token_address.eq(token_address))
+        .filter(positions::user_addr.eq_any(buys.iter().map(|(address, _, _)| address)))
+        .select((positions::user_addr, positions::balance))
+        .load::<(String, BigDecimal)>(conn)
+        .await?;
+
+    let wallets = buys
+        .into_iter()
+        .map(|(address, token_amount, aptos_amount)| WhitelistWallet {
+            balance: balances
+                .iter()
+                .find(|(user_addr, _)| *user_addr == address)
+                .map(|(_, balance)| balance.clone())
+                .unwrap_or_default(),
+            address,
+            token_amount: token_amount.unwrap_or_default(),
+            aptos_amount: aptos_amount.unwrap_or_default(),
+        })
+        .collect::<Vec<_>>();
+    Ok(Some(WhitelistReport::new(window, supply, wallets)))
+}
+
+impl WhitelistReport {
+    /// Totals of the window buys of `wallets` against the pre token `supply`.
+    pub fn new(
+        window: WhitelistWindow,
+        supply: BigDecimal,
+        mut wallets: Vec<WhitelistWallet>,
+    ) -> Self {
+        wallets.sort_by(|a, b| b.token_amount.cmp(&a.token_amount));
+
+        let token_amount = wallets
+            .iter()
+            .map(|wallet| &wallet.token_amount)
+            .sum::<BigDecimal>();
+        let supply_percentage = if supply.is_zero() {
+            BigDecimal::zero()
+        } else {
+            (&token_amount * BigDecimal::from(100) / &supply).round(4)
+        };
+        Self {
+            supply,
+            aptos_amount: wallets.iter().map(|wallet| &wallet.aptos_amount).sum(),
+            balance: wallets.iter().map(|wallet| &wallet.balance).sum(),
+            supply_percentage,
+            token_amount,
+            wallets,
+            window,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_whitelist_report() {
+        let window = WhitelistWindow {
+            token_address: "0xa".to_string(),
+            pool_addr: "0xb".to_string(),
+            contract_address: "0xc".to_string(),
+            whitelist_duration: 60,
+            starts_at: 1_000,
+            ends_at: 1_060,
+            txn_version: 1,
+        };
+        assert!(window.contains(1_000));
+        assert!(window.contains(1_059));
+        assert!(!window.contains(1_060));
+        assert!(!window.contains(999));
+
+        let wallet = |address: &str, token_amount: i64, aptos_amount: i64, balance: i64| {
+            WhitelistWallet {
+                address: address.to_string(),
+                token_amount: BigDecimal::from(token_amount),
+                aptos_amount: BigDecimal::from(aptos_amount),
+                balance: BigDecimal::from(balance),
+            }
+        };
+        let report = WhitelistReport::new(
+            window.clone(),
+            BigDecimal::from(3_000),
+            vec![wallet("0x1", 100, 10, 0), wallet("0x2", 400, 50, 400)],
+        );
+        assert_eq!(report.wallets[0].address, "0x2");
+        assert_eq!(report.token_amount, BigDecimal::from(500));
+        assert_eq!(report.aptos_amount, BigDecimal::from(60));
+        assert_eq!(report.balance, BigDecimal::from(400));
+        // 500 / 3000
+        assert_eq!(report.supply_percentage, "16.6667".parse::<BigDecimal>().unwrap());
+
+        let report = WhitelistReport::new(window, BigDecimal::zero(), vec![]);
+        assert!(report.supply_percentage.is_zero());
+    }
+}
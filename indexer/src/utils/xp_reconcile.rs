@@ -0,0 +1,157 @@
+use ahash::{AHashMap, AHashSet};
+use diesel::{insert_into, upsert::excluded, ExpressionMethods, QueryDsl, QueryResult};
+use diesel_async::{AsyncConnection, AsyncPgConnection, RunQueryDsl};
+
+use crate::schema::{accounts, xp_ledger};
+
+/// An account whose stored xp doesn't match the sum of its xp ledger entries.
+#[derive(Clone, Debug)]
+pub struct XpDrift {
+    pub address: String,
+    // The address only has ledger entries, its stored xp are 0
+    pub missing_account: bool,
+    pub xp: i32,
+    pub ledger_xp: i32,
+    pub xp_earned: i32,
+    pub ledger_xp_earned: i32,
+}
+
+/// Recomputes `accounts.xp` and `accounts.xp_earned` from `xp_ledger` and returns every
+/// account that differs, including the addresses with ledger entries but no account.
+/// `xp_earned` only counts positive ledger entries. Besides the
+/// indexed trades and spins, the ledger holds the task claims and spin costs written by the
+/// backend and one opening entry per account for the xp credited before it existed.
+pub async fn find_xp_drift(conn: &mut AsyncPgConnection) -> QueryResult<Vec<XpDrift>> {
+    let ledger_xp = xp_ledger::table
+        .group_by(xp_ledger::address)
+        .select((xp_ledger::address, diesel::dsl::sum(xp_ledger::delta)))
+        .load::<(String, Option<i64>)>(conn)
+        .await?
+        .into_iter()
+        .collect::<AHashMap<String, Option<i64>>>();
+    let ledger_xp_earned = xp_ledger::table
+        .filter(xp_ledger::delta.gt(0))
+        .group_by(xp_ledger::address)
+        .select((xp_ledger::address, diesel::dsl::sum(xp_ledger::delta)))
+        .load::<(String, Option<i64>)>(conn)
+        .await?
+        .into_iter()
+        .collect::<AHashMap<String, Option<i64>>>();
+    let accounts = accounts::table
+        .select((accounts::address, accounts::xp, accounts::xp_earned))
+        .load::<(String, i32, i32)>(conn)
+        .await?;
+
+    Ok(get_xp_drift(accounts, &ledger_xp, &ledger_xp_earned))
+}
+
+/// Accounts whose (xp, xp_earned) differ from the ledger sums of their address, joined in
+/// full so ledger addresses without an account are reported with zero stored xp.
+fn get_xp_drift(
+    accounts: Vec<(String, i32, i32)>,
+    ledger_xp: &AHashMap<String, Option<i64>>,
+    ledger_xp_earned: &AHashMap<String, Option<i64>>,
+) -> Vec<XpDrift> {
+    let account_addresses = accounts
+        .iter()
+        .map(|(address, _, _)| address.clone())
+        .collect::<AHashSet<String>>();
+    let mut ledger_only = ledger_xp
+        .keys()
+        .filter(|address| !account_addresses.contains(*address))
+        .map(|address| (address.clone(), true, 0, 0))
+        .collect::<Vec<(String, bool, i32, i32)>>();
+    ledger_only.sort();
+    accounts
+        .into_iter()
+        .map(|(address, xp, xp_earned)| (address, false, xp, xp_earned))
+        .chain(ledger_only)
+        .map(|(address, missing_account, xp, xp_earned)| XpDrift {
+            ledger_xp: ledger_xp.get(&address).copied().flatten().unwrap_or(0) as i32,
+            ledger_xp_earned: ledger_xp_earned
+                .get(&address)
+                .copied()
+                .flatten()
+                .unwrap_or(0) as i32,
+            address,
+            missing_account,
+            xp,
+            xp_earned,
+        })
+        .filter(|drift| drift.xp != drift.ledger_xp || drift.xp_earned != drift.ledger_xp_earned)
+        .collect()
+}
+
+/// Overwrites the drifted accounts with their ledger values, creating the missing ones.
+pub async fn apply_xp_drift(conn: &mut AsyncPgConnection, drift: Vec<XpDrift>) -> QueryResult<()> {
+    conn.transaction(|conn| {
+        Box::pin(async move {
+            for account in drift {
+                insert_into(accounts::table)
+                    .values((
+                        accounts::address.eq(account.address),
+                        accounts::xp.eq(account.ledger_xp),
+                        accounts::xp_earned.eq(account.ledger_xp_earned),
+                    ))
+                    .on_conflict(accounts::address)
+                    .do_update()
+                    .set((
+                        accounts::xp.eq(excluded(accounts::xp)),
+                        accounts::xp_earned.eq(excluded(accounts::xp_earned)),
+                    ))
+                    .execute(conn)
+                    .await?;
+            }
+            Ok(())
+        })
+    })
+    .await
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_xp_drift() {
+        let ledger_xp = AHashMap::from_iter([
+            ("0x1".to_string(), Some(7)),
+            ("0x2".to_string(), Some(5)),
+            ("0x3".to_string(), None),
+            ("0x5".to_string(), Some(3)),
+        ]);
+        let ledger_xp_earned = AHashMap::from_iter([
+            ("0x1".to_string(), Some(10)),
+            ("0x2".to_string(), Some(5)),
+            ("0x5".to_string(), Some(3)),
+        ]);
+        let drift = get_xp_drift(
+            vec![
+                // In sync, 10 earned and 3 spent
+                ("0x1".to_string(), 7, 10),
+                // A spin cost missing from the ledger
+                ("0x2".to_string(), 4, 5),
+                // Credited outside of the ledger
+                ("0x3".to_string(), 2, 2),
+                ("0x4".to_string(), 0, 0),
+            ],
+            &ledger_xp,
+            &ledger_xp_earned,
+        );
+        let drift = drift
+            .into_iter()
+            .map(|d| {
+                (d.address, d.missing_account, d.xp, d.ledger_xp, d.xp_earned, d.ledger_xp_earned)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            drift,
+            vec![
+                ("0x2".to_string(), false, 4, 5, 5, 5),
+                ("0x3".to_string(), false, 2, 0, 2, 0),
+                // Ledger entries without an account
+                ("0x5".to_string(), true, 0, 3, 0, 3),
+            ]
+        );
+    }
+}
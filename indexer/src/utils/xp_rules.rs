@@ -0,0 +1,108 @@
+use crate::{
+    config::indexer_processor_config::XpConfig,
+    db_models::{
+        trades::Trade,
+        xp_ledger::{XpLedgerEntry, XP_SOURCE_TRADE},
+    },
+};
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Stable hash of the seed, txn_version and user. Unlike `std::hash`, the output never
+/// changes between builds, so reindexing awards exactly the same XP.
+pub fn xp_roll(seed: u64, txn_version: i64, user: &str) -> u64 {
+    let mut hash = FNV_OFFSET_BASIS;
+    for byte in seed
+        .to_le_bytes()
+        .iter()
+        .chain(txn_version.to_le_bytes().iter())
+        .chain(user.as_bytes())
+    {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    // splitmix64 finalizer, spreads the FNV output over all bits
+    hash = (hash ^ (hash >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    hash = (hash ^ (hash >> 27)).wrapping_mul(0x94d049bb133111eb);
+    hash ^ (hash >> 31)
+}
+
+/// XP awarded for a trade, if any. Trades under `min_aptos` are never rewarded, the rest
+/// are rewarded with a `reward_chance_bps` chance and an amount in `[min_xp, max_xp]`.
+pub fn trade_xp_reward(config: &XpConfig, trade: &Trade) -> Option<XpLedgerEntry> {
+    if trade.aptos_amount < config.min_aptos || config.max_xp < config.min_xp {
+        return None;
+    }
+    let roll = xp_roll(config.seed, trade.txn_version, &trade.user_addr);
+    if roll % 10_000 >= config.reward_chance_bps {
+        return None;
+    }
+    let range = (config.max_xp - config.min_xp) as u64 + 1;
+    Some(XpLedgerEntry {
+        address: trade.user_addr.clone(),
+        delta: config.min_xp + ((roll >> 32) % range) as i32,
+        source_type: XP_SOURCE_TRADE.to_string(),
+        source_ref: trade.txn_version.to_string(),
+        txn_version: Some(trade.txn_version),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use bigdecimal::BigDecimal;
+
+    use super::*;
+
+    fn trade(txn_version: i64, aptos_amount: i64) -> Trade {
+        Trade {
+            txn_version,
+            is_buy: true,
+            user_addr: "0x1".to_string(),
+            aptos_amount,
+            token_amount: 1,
+            token_address: "0x2".to_string(),
+            virtual_aptos_reserves: BigDecimal::from(0),
+            virtual_token_reserves: BigDecimal::from(0),
+            ts: 0,
+            contract_address: "0x3".to_string(),
+            in_whitelist_window: None,
+            block_height: None,
+            apt_usd_price: None,
+            aptos_amount_usd: None,
+            fee_usd: None,
+            fee: None,
+            creator_fee: None,
+        }
+    }
+
+    #[test]
+    fn test_trade_xp_reward() {
+        // Pinned so a change of the hash, which would change past rewards, fails here
+        assert_eq!(xp_roll(0, 1, "0x1"), 0x171963df2aed155c);
+        assert_ne!(xp_roll(1, 1, "0x1"), xp_roll(0, 1, "0x1"));
+
+        let config = XpConfig {
+            reward_chance_bps: 10_000,
+            ..XpConfig::default()
+        };
+        assert!(trade_xp_reward(&config, &trade(1, config.min_aptos - 1)).is_none());
+        for txn_version in 0..100 {
+            let reward = trade_xp_reward(&config, &trade(txn_version, config.min_aptos)).unwrap();
+            assert!((config.min_xp..=config.max_xp).contains(&reward.delta));
+            assert_eq!(reward.source_ref, txn_version.to_string());
+        }
+
+        // Roll of version 1 is 6332 out of 10000
+        let config = XpConfig {
+            reward_chance_bps: 6_332,
+            ..XpConfig::default()
+        };
+        assert!(trade_xp_reward(&config, &trade(1, config.min_aptos)).is_none());
+        let config = XpConfig {
+            reward_chance_bps: 6_333,
+            ..XpConfig::default()
+        };
+        assert_eq!(trade_xp_reward(&config, &trade(1, config.min_aptos)).unwrap().delta, 1);
+    }
+}